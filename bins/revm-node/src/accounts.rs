@@ -0,0 +1,39 @@
+use revm::primitives::{sha3, Address};
+use revm_chain::ed448::recover_address;
+use std::vec::Vec;
+
+/// Seed the keys of the development accounts are derived from.
+const SEED: &[u8] = b"revm-node development accounts";
+
+/// Prefunded account with a well known key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DevAccount {
+    /// Ed448 private key of the account.
+    pub secret_key: [u8; 57],
+    pub address: Address,
+}
+
+impl DevAccount {
+    /// Derives the account with the index.
+    ///
+    /// Keys are derived from a fixed seed so the accounts are the same on every start of the
+    /// node. They are public and must never hold real funds.
+    pub fn derive(index: u32) -> Self {
+        let first = sha3([SEED, &index.to_be_bytes()].concat());
+        let second = sha3(first);
+        let mut secret_key = [0u8; 57];
+        secret_key[..32].copy_from_slice(first.as_slice());
+        secret_key[32..].copy_from_slice(&second[..25]);
+        // key is 57 bytes long so the derivation can't fail.
+        let address = recover_address(&secret_key).unwrap();
+        Self {
+            secret_key,
+            address,
+        }
+    }
+}
+
+/// First `count` development accounts.
+pub fn dev_accounts(count: u32) -> Vec<DevAccount> {
+    (0..count).map(DevAccount::derive).collect()
+}
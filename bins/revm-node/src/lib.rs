@@ -0,0 +1,10 @@
+//! Local Core development node built on revm.
+//!
+//! Node keeps a chain of blocks on top of an in memory state, accepts Ed448 signed raw
+//! transactions and serves the `xcb_` JSON-RPC methods over HTTP.
+
+pub mod accounts;
+pub mod node;
+pub mod rpc;
+pub mod server;
+pub mod types;
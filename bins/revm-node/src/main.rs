@@ -0,0 +1,129 @@
+use revm::{
+    db::{Genesis, GenesisAccount},
+    primitives::{SpecId, U256},
+};
+use revm_node::{
+    accounts::dev_accounts,
+    node::{now, Node, NodeConfig},
+    server::serve,
+};
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use structopt::StructOpt;
+
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error("Invalid genesis: {0}")]
+    InvalidGenesis(serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Local Core development node.
+#[derive(StructOpt, Debug)]
+#[structopt(name = "revm-node")]
+struct Cmd {
+    #[structopt(long, default_value = "127.0.0.1")]
+    host: String,
+    #[structopt(long, default_value = "8545")]
+    port: u16,
+    /// Seconds between mined blocks, every transaction is mined right away if not set.
+    #[structopt(long)]
+    block_time: Option<u64>,
+    /// Number of prefunded development accounts.
+    #[structopt(long, default_value = "10")]
+    accounts: u32,
+    /// Balance of every development account.
+    #[structopt(long, default_value = "10000000000000000000000")]
+    balance: U256,
+    /// Path to go-core genesis file with the accounts of the initial state.
+    #[structopt(long)]
+    genesis: Option<PathBuf>,
+    #[structopt(long, default_value = "1")]
+    network_id: u64,
+    #[structopt(long, default_value = "Cancun", parse(try_from_str = parse_spec))]
+    spec: SpecId,
+    /// Energy limit of every block.
+    #[structopt(long, default_value = "30000000")]
+    energy_limit: u64,
+}
+
+/// Parses the name of the hardfork, `SpecId::from` falls back to the latest spec on unknown
+/// names.
+fn parse_spec(name: &str) -> Result<SpecId, String> {
+    let spec_id = SpecId::from(name);
+    if <&str>::from(spec_id) != name {
+        return Err(format!("unknown spec {name}"));
+    }
+    Ok(spec_id)
+}
+
+impl Cmd {
+    fn run(&self) -> Result<(), Error> {
+        let mut genesis = match &self.genesis {
+            Some(path) => {
+                serde_json::from_str(&fs::read_to_string(path)?).map_err(Error::InvalidGenesis)?
+            }
+            None => Genesis::default(),
+        };
+        let accounts = dev_accounts(self.accounts);
+        for account in &accounts {
+            genesis.alloc.insert(
+                account.address,
+                GenesisAccount {
+                    balance: self.balance,
+                    ..Default::default()
+                },
+            );
+        }
+        let config = NodeConfig {
+            network_id: self.network_id,
+            spec_id: self.spec,
+            energy_limit: self.energy_limit,
+            coinbase: genesis.coinbase,
+            auto_mine: self.block_time.is_none(),
+            ..Default::default()
+        };
+        let node = Arc::new(Mutex::new(Node::new(config, genesis.in_memory_db(), now())));
+
+        println!("Available accounts");
+        println!("==================");
+        for (i, account) in accounts.iter().enumerate() {
+            println!("({i}) {}", account.address.to_ican(self.network_id));
+        }
+        println!();
+        println!("Private keys");
+        println!("==================");
+        for (i, account) in accounts.iter().enumerate() {
+            println!("({i}) 0x{}", hex::encode(account.secret_key));
+        }
+        println!();
+
+        if let Some(block_time) = self.block_time {
+            let node = node.clone();
+            thread::spawn(move || loop {
+                thread::sleep(Duration::from_secs(block_time));
+                let mut node = node.lock().unwrap();
+                for (hash, error) in node.mine() {
+                    eprintln!("Dropped transaction {hash}: {error}");
+                }
+                println!("Mined block {}", node.block_number());
+            });
+        }
+
+        let listener = TcpListener::bind((self.host.as_str(), self.port))?;
+        println!("Listening on {}", listener.local_addr()?);
+        serve(listener, node)?;
+        Ok(())
+    }
+}
+
+fn main() {
+    if let Err(e) = Cmd::from_args().run() {
+        println!("{e}");
+    }
+}
@@ -0,0 +1,526 @@
+use crate::types::{Block, BlockTag, CallRequest, Header, LogFilter, RpcLog, TransactionReceipt};
+use revm::{
+    db::{DbAccount, InMemoryDB},
+    interpreter::CreateScheme,
+    primitives::{
+        Address, BlockEnv, Bytes, Env, ExecutionResult, HashMap, SpecId, TransactTo, TxEnv, B256,
+        U256,
+    },
+    Evm,
+};
+use revm_chain::{logs_bloom, trie::ordered_trie_root, BlockExecutor, SignedTransaction};
+use std::mem;
+use std::string::String;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::vec::Vec;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NodeError {
+    #[error("transaction can't be decoded: {0}")]
+    Rlp(#[from] alloy_rlp::Error),
+    #[error("invalid transaction: {0}")]
+    InvalidTransaction(String),
+    #[error("execution reverted")]
+    Reverted(Bytes),
+    #[error("execution halted: {0}")]
+    Halted(String),
+    #[error("state of block {0} is not available, only the latest state is kept")]
+    HistoricalState(u64),
+    #[error("block {0} does not exist")]
+    UnknownBlock(u64),
+}
+
+/// Configuration of the node.
+#[derive(Clone, Debug)]
+pub struct NodeConfig {
+    pub network_id: u64,
+    pub spec_id: SpecId,
+    /// Energy limit of every block.
+    pub energy_limit: u64,
+    pub coinbase: Address,
+    pub basefee: U256,
+    /// Mine a block for every transaction as soon as it is received.
+    pub auto_mine: bool,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        Self {
+            network_id: 1,
+            spec_id: SpecId::CANCUN,
+            energy_limit: 30_000_000,
+            coinbase: Address::ZERO,
+            basefee: U256::ZERO,
+            auto_mine: true,
+        }
+    }
+}
+
+/// Chain of blocks on top of the in memory state.
+///
+/// Only the state of the latest block is kept, transactions are executed in the order they
+/// were received.
+#[derive(Debug)]
+pub struct Node {
+    config: NodeConfig,
+    db: InMemoryDB,
+    blocks: Vec<Block>,
+    pending: Vec<SignedTransaction>,
+    receipts: HashMap<B256, TransactionReceipt>,
+}
+
+impl Node {
+    /// Creates the node with the genesis block on top of the state.
+    pub fn new(config: NodeConfig, db: InMemoryDB, timestamp: u64) -> Self {
+        let mut node = Self {
+            config,
+            db,
+            blocks: Vec::new(),
+            pending: Vec::new(),
+            receipts: HashMap::new(),
+        };
+        let header = Header {
+            parent_hash: B256::ZERO,
+            coinbase: node.config.coinbase,
+            transactions_root: ordered_trie_root(Vec::<Vec<u8>>::new()),
+            receipts_root: ordered_trie_root(Vec::<Vec<u8>>::new()),
+            logs_bloom: Default::default(),
+            number: 0,
+            energy_limit: node.config.energy_limit,
+            energy_used: 0,
+            timestamp,
+        };
+        node.seal(header, Vec::new());
+        node
+    }
+
+    pub fn config(&self) -> &NodeConfig {
+        &self.config
+    }
+
+    pub fn db(&self) -> &InMemoryDB {
+        &self.db
+    }
+
+    pub fn block_number(&self) -> u64 {
+        self.latest_block().header.number
+    }
+
+    pub fn latest_block(&self) -> &Block {
+        // genesis block is sealed on creation.
+        self.blocks.last().unwrap()
+    }
+
+    pub fn block(&self, number: u64) -> Option<&Block> {
+        self.blocks.get(usize::try_from(number).ok()?)
+    }
+
+    /// Number of the block the tag points to.
+    pub fn resolve(&self, tag: BlockTag) -> Result<u64, NodeError> {
+        match tag {
+            BlockTag::Latest | BlockTag::Pending => Ok(self.block_number()),
+            BlockTag::Earliest => Ok(0),
+            BlockTag::Number(number) if number <= self.block_number() => Ok(number),
+            BlockTag::Number(number) => Err(NodeError::UnknownBlock(number)),
+        }
+    }
+
+    /// Fails if the tag does not point to the latest block, as older states are not kept.
+    pub fn ensure_latest(&self, tag: BlockTag) -> Result<(), NodeError> {
+        match self.resolve(tag)? {
+            number if number == self.block_number() => Ok(()),
+            number => Err(NodeError::HistoricalState(number)),
+        }
+    }
+
+    fn account(&self, address: Address) -> Option<&DbAccount> {
+        self.db.accounts.get(&address)
+    }
+
+    pub fn balance(&self, address: Address) -> U256 {
+        self.account(address)
+            .map(|account| account.info.balance)
+            .unwrap_or_default()
+    }
+
+    pub fn nonce(&self, address: Address) -> u64 {
+        self.account(address)
+            .map(|account| account.info.nonce)
+            .unwrap_or_default()
+    }
+
+    pub fn code(&self, address: Address) -> Bytes {
+        self.account(address)
+            .and_then(|account| self.db.contracts.get(&account.info.code_hash))
+            .map(|code| code.original_bytes())
+            .unwrap_or_default()
+    }
+
+    pub fn storage(&self, address: Address, index: U256) -> U256 {
+        self.account(address)
+            .and_then(|account| account.storage.get(&index))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn receipt(&self, hash: &B256) -> Option<&TransactionReceipt> {
+        self.receipts.get(hash)
+    }
+
+    fn env(&self, block: BlockEnv, tx: TxEnv) -> Env {
+        let mut env = Env::default();
+        env.cfg.network_id = self.config.network_id;
+        env.block = block;
+        env.tx = tx;
+        env
+    }
+
+    fn block_env(&self, number: u64, timestamp: u64) -> BlockEnv {
+        BlockEnv {
+            number: U256::from(number),
+            coinbase: self.config.coinbase,
+            timestamp: U256::from(timestamp),
+            energy_limit: U256::from(self.config.energy_limit),
+            basefee: self.config.basefee,
+            ..Default::default()
+        }
+    }
+
+    /// Adds the transaction to the pending transactions, mining it right away if auto mining
+    /// is enabled.
+    ///
+    /// Nonce and balance of the sender are checked against the latest state so a transaction
+    /// that can't be included is rejected without mining a block.
+    pub fn send_transaction(&mut self, tx: SignedTransaction) -> Result<B256, NodeError> {
+        if tx.network_id != self.config.network_id {
+            return Err(NodeError::InvalidTransaction(format!(
+                "network id {} does not match {}",
+                tx.network_id, self.config.network_id
+            )));
+        }
+        if tx.energy_limit > self.config.energy_limit {
+            return Err(NodeError::InvalidTransaction(
+                "energy limit exceeds the block energy limit".into(),
+            ));
+        }
+        if tx.energy_price < self.config.basefee {
+            return Err(NodeError::InvalidTransaction(
+                "energy price is less than the basefee".into(),
+            ));
+        }
+        let sender = tx
+            .recover_sender()
+            .and_then(|sender| tx.tx_env(sender).map(|_| sender))
+            .map_err(NodeError::InvalidTransaction)?;
+        // pending transactions are only kept when mining on an interval.
+        let nonce = self.nonce(sender);
+        if tx.nonce < nonce || (self.config.auto_mine && tx.nonce != nonce) {
+            return Err(NodeError::InvalidTransaction(format!(
+                "nonce {} does not match the account nonce {nonce}",
+                tx.nonce
+            )));
+        }
+        let cost = U256::from(tx.energy_limit)
+            .saturating_mul(tx.energy_price)
+            .saturating_add(tx.value);
+        if self.balance(sender) < cost {
+            return Err(NodeError::InvalidTransaction(
+                "insufficient funds for energy * price + value".into(),
+            ));
+        }
+
+        let hash = tx.hash();
+        self.pending.push(tx);
+        if self.config.auto_mine {
+            if let Some((_, error)) = self.mine().into_iter().find(|(h, _)| *h == hash) {
+                return Err(error);
+            }
+        }
+        Ok(hash)
+    }
+
+    /// Mines the pending transactions into a new block.
+    ///
+    /// Transactions that don't fit in the block are kept for the next one. Returns the hashes
+    /// of the transactions that were dropped and the reason.
+    pub fn mine(&mut self) -> Vec<(B256, NodeError)> {
+        let parent = &self.latest_block().header;
+        let number = parent.number + 1;
+        let timestamp = now().max(parent.timestamp + 1);
+        let parent_hash = self.latest_block().hash;
+        let block_env = self.block_env(number, timestamp);
+
+        let network_id = self.config.network_id;
+        let env = self.env(block_env, TxEnv::default());
+        let mut executor = BlockExecutor::new(&mut self.db, env, self.config.spec_id);
+        let mut included = Vec::new();
+        let mut receipts = Vec::new();
+        let mut rejected = Vec::new();
+        let mut log_index = 0u64;
+        for tx in mem::take(&mut self.pending) {
+            let hash = tx.hash();
+            if executor.cumulative_energy_used() + tx.energy_limit > self.config.energy_limit {
+                self.pending.push(tx);
+                continue;
+            }
+            let (sender, tx_env) = match tx
+                .recover_sender()
+                .and_then(|sender| Ok((sender, tx.tx_env(sender)?)))
+            {
+                Ok(tx_env) => tx_env,
+                Err(error) => {
+                    rejected.push((hash, NodeError::InvalidTransaction(error)));
+                    continue;
+                }
+            };
+            let executed = match executor.execute(tx_env, 0) {
+                Ok(executed) => executed,
+                Err(error) => {
+                    rejected.push((hash, NodeError::InvalidTransaction(error.to_string())));
+                    continue;
+                }
+            };
+
+            let transaction_index = U256::from(included.len());
+            let rpc_logs = executed
+                .receipt
+                .logs
+                .iter()
+                .map(|log| {
+                    log_index += 1;
+                    RpcLog {
+                        address: log.address.to_ican(network_id),
+                        topics: log.topics().to_vec(),
+                        data: log.data.data.clone(),
+                        // block hash is set once the block is sealed.
+                        block_hash: B256::ZERO,
+                        block_number: U256::from(number),
+                        transaction_hash: hash,
+                        transaction_index,
+                        log_index: U256::from(log_index - 1),
+                        removed: false,
+                    }
+                })
+                .collect();
+            receipts.push(TransactionReceipt {
+                transaction_hash: hash,
+                transaction_index,
+                block_hash: B256::ZERO,
+                block_number: U256::from(number),
+                from: sender.to_ican(network_id),
+                to: tx
+                    .receiver()
+                    .ok()
+                    .flatten()
+                    .map(|to| to.to_ican(network_id)),
+                contract_address: executed
+                    .contract_address
+                    .map(|address| address.to_ican(network_id)),
+                cumulative_energy_used: U256::from(executed.receipt.cumulative_energy_used),
+                energy_used: U256::from(executed.energy_used),
+                effective_energy_price: tx.energy_price,
+                status: U256::from(executed.receipt.success as u8),
+                logs: rpc_logs,
+                logs_bloom: logs_bloom(&executed.receipt.logs),
+            });
+            included.push(tx);
+        }
+
+        let header = Header {
+            parent_hash,
+            coinbase: self.config.coinbase,
+            transactions_root: ordered_trie_root(included.iter().map(alloy_rlp::encode)),
+            receipts_root: executor.receipts_root(),
+            logs_bloom: executor.logs_bloom(),
+            number,
+            energy_limit: self.config.energy_limit,
+            energy_used: executor.cumulative_energy_used(),
+            timestamp,
+        };
+        drop(executor);
+        let hash = self.seal(header, included.iter().map(|tx| tx.hash()).collect());
+        for mut receipt in receipts {
+            receipt.block_hash = hash;
+            for log in &mut receipt.logs {
+                log.block_hash = hash;
+            }
+            self.receipts.insert(receipt.transaction_hash, receipt);
+        }
+        rejected
+    }
+
+    /// Appends the block to the chain and returns its hash.
+    fn seal(&mut self, header: Header, transactions: Vec<B256>) -> B256 {
+        let hash = header.hash();
+        self.db.block_hashes.insert(U256::from(header.number), hash);
+        self.blocks.push(Block {
+            header,
+            hash,
+            transactions,
+        });
+        hash
+    }
+
+    /// Executes the call on top of the latest block without committing it.
+    ///
+    /// Returns the output of the call, revert and halt are errors.
+    pub fn call(&self, request: &CallRequest) -> Result<Bytes, NodeError> {
+        let energy_limit = request
+            .energy
+            .map_or(self.config.energy_limit, |energy| energy.saturating_to());
+        match self.execute(request, energy_limit)? {
+            ExecutionResult::Success { output, .. } => Ok(output.into_data()),
+            ExecutionResult::Revert { output, .. } => Err(NodeError::Reverted(output)),
+            ExecutionResult::Halt { reason, .. } => Err(NodeError::Halted(format!("{reason:?}"))),
+        }
+    }
+
+    /// Lowest energy limit the call succeeds with.
+    pub fn estimate_energy(&self, request: &CallRequest) -> Result<u64, NodeError> {
+        let mut high = request
+            .energy
+            .map_or(self.config.energy_limit, |energy| energy.saturating_to());
+        let energy_used = match self.execute(request, high)? {
+            ExecutionResult::Success { energy_used, .. } => energy_used,
+            ExecutionResult::Revert { output, .. } => return Err(NodeError::Reverted(output)),
+            ExecutionResult::Halt { reason, .. } => {
+                return Err(NodeError::Halted(format!("{reason:?}")))
+            }
+        };
+        // call may need more than it uses, as only 63/64 of the energy is passed to subcalls.
+        let mut low = energy_used - 1;
+        while low + 1 < high {
+            let mid = low + (high - low) / 2;
+            match self.execute(request, mid) {
+                Ok(result) if result.is_success() => high = mid,
+                _ => low = mid,
+            }
+        }
+        Ok(high)
+    }
+
+    fn execute(
+        &self,
+        request: &CallRequest,
+        energy_limit: u64,
+    ) -> Result<ExecutionResult, NodeError> {
+        let latest = &self.latest_block().header;
+        let mut block = self.block_env(latest.number + 1, latest.timestamp + 1);
+        // calls are free, so a caller without balance can make them.
+        block.basefee = U256::ZERO;
+        let tx = TxEnv {
+            caller: request.from.unwrap_or_default(),
+            energy_limit,
+            energy_price: request.energy_price.unwrap_or_default(),
+            transact_to: match request.to {
+                Some(address) => TransactTo::Call(address),
+                None => TransactTo::Create(CreateScheme::Create),
+            },
+            value: request.value.unwrap_or_default(),
+            data: request.data.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+        let env = self.env(block, tx);
+        let mut evm = Evm::builder()
+            .with_ref_db(&self.db)
+            .modify_env(|e| **e = env)
+            .with_spec_id(self.config.spec_id)
+            .build();
+        evm.transact()
+            .map(|out| out.result)
+            .map_err(|e| NodeError::InvalidTransaction(e.to_string()))
+    }
+
+    /// Logs of the mined blocks that match the filter.
+    pub fn logs(&self, filter: &LogFilter) -> Result<Vec<RpcLog>, NodeError> {
+        let blocks: Vec<&Block> = match filter.block_hash {
+            Some(hash) => self.blocks.iter().filter(|b| b.hash == hash).collect(),
+            None => {
+                let from = self.resolve(filter.from_block.unwrap_or_default())? as usize;
+                let to = self.resolve(filter.to_block.unwrap_or_default())? as usize;
+                self.blocks
+                    .get(from..=to)
+                    .unwrap_or_default()
+                    .iter()
+                    .collect()
+            }
+        };
+        Ok(blocks
+            .into_iter()
+            .flat_map(|block| &block.transactions)
+            .filter_map(|hash| self.receipts.get(hash))
+            .flat_map(|receipt| &receipt.logs)
+            .filter(|log| filter.matches(log))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Current unix timestamp in seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::DevAccount;
+    use revm::primitives::AccountInfo;
+
+    #[test]
+    fn mine_transfer() {
+        let account = DevAccount::derive(0);
+        let receiver = Address::with_last_byte(0xaa);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            account.address,
+            AccountInfo::from_balance(U256::from(1_000_000)),
+        );
+        let mut node = Node::new(NodeConfig::default(), db, 0);
+        let tx = TxEnv {
+            transact_to: TransactTo::Call(receiver),
+            energy_limit: 21_000,
+            energy_price: U256::from(1),
+            value: U256::from(10),
+            ..Default::default()
+        };
+        let request = CallRequest {
+            from: Some(account.address),
+            to: Some(receiver),
+            value: Some(U256::from(10)),
+            ..Default::default()
+        };
+        assert_eq!(node.estimate_energy(&request).unwrap(), 21_000);
+
+        let transfer = SignedTransaction::unsigned(&tx, 0, 1)
+            .sign(&account.secret_key)
+            .unwrap();
+        let hash = node.send_transaction(transfer.clone()).unwrap();
+        assert_eq!(node.block_number(), 1);
+        assert_eq!(node.balance(receiver), U256::from(10));
+        assert_eq!(node.nonce(account.address), 1);
+        let receipt = node.receipt(&hash).unwrap();
+        assert_eq!(receipt.status, U256::from(1));
+        assert_eq!(receipt.from, account.address.to_ican(1));
+        assert_eq!(receipt.to, Some(receiver.to_ican(1)));
+        assert_eq!(receipt.block_hash, node.latest_block().hash);
+
+        // nonce is reused, no block is mined for the rejected transaction.
+        assert!(node.send_transaction(transfer).is_err());
+        assert_eq!(node.block_number(), 1);
+
+        let expensive = TxEnv {
+            value: U256::from(1_000_000),
+            ..tx
+        };
+        let expensive = SignedTransaction::unsigned(&expensive, 1, 1)
+            .sign(&account.secret_key)
+            .unwrap();
+        assert!(node.send_transaction(expensive).is_err());
+        assert_eq!(node.block_number(), 1);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::node::{Node, NodeError};
+use crate::types::{BlockTag, CallRequest, LogFilter};
+use alloy_rlp::Decodable;
+use revm::{
+    db::parse_address,
+    primitives::{Address, Bytes, B256, U256},
+};
+use revm_chain::SignedTransaction;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::string::String;
+use std::sync::Mutex;
+
+/// JSON-RPC request.
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// JSON-RPC error object.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl fmt::Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+
+    pub fn parse_error(error: impl fmt::Display) -> Self {
+        Self::new(-32700, error)
+    }
+
+    pub fn invalid_request(error: impl fmt::Display) -> Self {
+        Self::new(-32600, error)
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(-32601, format!("method {method} does not exist"))
+    }
+
+    pub fn invalid_params(index: usize, error: impl fmt::Display) -> Self {
+        Self::new(-32602, format!("invalid parameter {index}: {error}"))
+    }
+}
+
+impl From<NodeError> for RpcError {
+    fn from(error: NodeError) -> Self {
+        match error {
+            NodeError::Reverted(output) => Self {
+                code: 3,
+                message: "execution reverted".into(),
+                data: Some(json!(output)),
+            },
+            error => Self::new(-32000, error),
+        }
+    }
+}
+
+/// Handles the JSON-RPC request or batch of requests.
+pub fn handle(node: &Mutex<Node>, body: &[u8]) -> Value {
+    match serde_json::from_slice(body) {
+        Ok(Value::Array(batch)) => batch
+            .into_iter()
+            .map(|request| handle_request(node, request))
+            .collect(),
+        Ok(request) => handle_request(node, request),
+        Err(e) => response(Value::Null, Err(RpcError::parse_error(e))),
+    }
+}
+
+fn handle_request(node: &Mutex<Node>, request: Value) -> Value {
+    let request: Request = match serde_json::from_value(request) {
+        Ok(request) => request,
+        Err(e) => return response(Value::Null, Err(RpcError::invalid_request(e))),
+    };
+    let result = dispatch(&mut node.lock().unwrap(), &request.method, &request.params);
+    response(request.id, result)
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    }
+}
+
+/// Calls the method of the node with the positional parameters.
+pub fn dispatch(node: &mut Node, method: &str, params: &Value) -> Result<Value, RpcError> {
+    let params = Params(params);
+    let result = match method {
+        "xcb_blockNumber" => json!(U256::from(node.block_number())),
+        "xcb_getBalance" => {
+            node.ensure_latest(params.block(1)?)?;
+            json!(node.balance(params.address(0)?))
+        }
+        "xcb_getCode" => {
+            node.ensure_latest(params.block(1)?)?;
+            json!(node.code(params.address(0)?))
+        }
+        "xcb_getStorageAt" => {
+            node.ensure_latest(params.block(2)?)?;
+            let value = node.storage(params.address(0)?, params.get(1)?);
+            json!(B256::from(value))
+        }
+        "xcb_getTransactionReceipt" => json!(node.receipt(&params.get::<B256>(0)?)),
+        "xcb_getLogs" => json!(node.logs(&params.get::<LogFilter>(0)?)?),
+        "xcb_call" => {
+            node.ensure_latest(params.block(1)?)?;
+            json!(node.call(&params.get::<CallRequest>(0)?)?)
+        }
+        "xcb_estimateEnergy" => {
+            json!(U256::from(
+                node.estimate_energy(&params.get::<CallRequest>(0)?)?
+            ))
+        }
+        "xcb_sendRawTransaction" => {
+            let raw: Bytes = params.get(0)?;
+            let tx = SignedTransaction::decode(&mut raw.as_ref()).map_err(NodeError::from)?;
+            json!(node.send_transaction(tx)?)
+        }
+        _ => return Err(RpcError::method_not_found(method)),
+    };
+    Ok(result)
+}
+
+struct Params<'a>(&'a Value);
+
+impl Params<'_> {
+    /// Parameter at the index, missing parameters are `null`.
+    fn get<T: DeserializeOwned>(&self, index: usize) -> Result<T, RpcError> {
+        let value = self.0.get(index).cloned().unwrap_or_default();
+        serde_json::from_value(value).map_err(|e| RpcError::invalid_params(index, e))
+    }
+
+    /// Plain or ICAN address.
+    fn address(&self, index: usize) -> Result<Address, RpcError> {
+        parse_address(&self.get::<String>(index)?).map_err(|e| RpcError::invalid_params(index, e))
+    }
+
+    /// Optional block parameter, latest block if missing.
+    fn block(&self, index: usize) -> Result<BlockTag, RpcError> {
+        Ok(self.get::<Option<BlockTag>>(index)?.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::NodeConfig;
+    use revm::{db::InMemoryDB, primitives::AccountInfo};
+
+    #[test]
+    fn dispatch_methods() {
+        let address = Address::with_last_byte(1);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(address, AccountInfo::from_balance(U256::from(0x100)));
+        let node = Mutex::new(Node::new(NodeConfig::default(), db, 0));
+
+        let body = json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "xcb_blockNumber" },
+            { "jsonrpc": "2.0", "id": 2, "method": "xcb_getBalance", "params": [address, "latest"] },
+            { "jsonrpc": "2.0", "id": 3, "method": "xcb_getBalance", "params": [address, "0x5"] },
+            { "jsonrpc": "2.0", "id": 4, "method": "xcb_unknown" },
+        ]);
+        let response = handle(&node, body.to_string().as_bytes());
+        assert_eq!(response[0]["result"], "0x0");
+        assert_eq!(response[1]["result"], "0x100");
+        assert_eq!(response[2]["error"]["code"], -32000);
+        assert_eq!(response[3]["error"]["code"], -32601);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{node::Node, rpc};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::string::String;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::vec;
+
+/// Largest request body that is accepted.
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// Serves JSON-RPC requests sent over HTTP, every connection is handled on its own thread.
+pub fn serve(listener: TcpListener, node: Arc<Mutex<Node>>) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let node = node.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(stream, &node) {
+                eprintln!("Connection error: {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Handles a single HTTP request and closes the connection.
+fn handle_connection(mut stream: TcpStream, node: &Mutex<Node>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let method = request_line.split_whitespace().next().unwrap_or_default();
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid length"))?;
+            }
+        }
+    }
+
+    match method {
+        "POST" if content_length > MAX_BODY_SIZE => {
+            write_response(&mut stream, "413 Payload Too Large", &[])
+        }
+        "POST" => {
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            let response = serde_json::to_vec(&rpc::handle(node, &body))?;
+            write_response(&mut stream, "200 OK", &response)
+        }
+        "OPTIONS" => write_response(&mut stream, "204 No Content", &[]),
+        _ => write_response(&mut stream, "405 Method Not Allowed", &[]),
+    }
+}
+
+fn write_response(stream: &mut impl Write, status: &str, body: &[u8]) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Headers: *\r\n\
+         Access-Control-Allow-Methods: POST, OPTIONS\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
@@ -0,0 +1,204 @@
+use alloy_rlp::RlpEncodable;
+use revm::{
+    db::parse_address,
+    primitives::{sha3, Address, Bytes, IcanAddress, B256, U256},
+};
+use revm_chain::Bloom;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::str::FromStr;
+use std::string::String;
+use std::vec::Vec;
+
+/// Header of the mined block.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable)]
+pub struct Header {
+    pub parent_hash: B256,
+    pub coinbase: Address,
+    pub transactions_root: B256,
+    pub receipts_root: B256,
+    pub logs_bloom: Bloom,
+    pub number: u64,
+    pub energy_limit: u64,
+    pub energy_used: u64,
+    pub timestamp: u64,
+}
+
+impl Header {
+    /// Hash of the rlp encoded header.
+    pub fn hash(&self) -> B256 {
+        sha3(alloy_rlp::encode(self))
+    }
+}
+
+/// Block of the chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub header: Header,
+    pub hash: B256,
+    /// Hashes of the included transactions.
+    pub transactions: Vec<B256>,
+}
+
+/// Block parameter of the JSON-RPC methods.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockTag {
+    #[default]
+    Latest,
+    Pending,
+    Earliest,
+    Number(u64),
+}
+
+impl FromStr for BlockTag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latest" => Ok(Self::Latest),
+            "pending" => Ok(Self::Pending),
+            "earliest" => Ok(Self::Earliest),
+            _ => s
+                .strip_prefix("0x")
+                .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+                .map(Self::Number)
+                .ok_or_else(|| format!("invalid block {s}")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Transaction executed by `xcb_call` and `xcb_estimateEnergy`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallRequest {
+    #[serde(default, deserialize_with = "deserialize_option_address")]
+    pub from: Option<Address>,
+    /// Called address, contract is created if not set.
+    #[serde(default, deserialize_with = "deserialize_option_address")]
+    pub to: Option<Address>,
+    #[serde(default, alias = "energyLimit")]
+    pub energy: Option<U256>,
+    #[serde(default)]
+    pub energy_price: Option<U256>,
+    #[serde(default)]
+    pub value: Option<U256>,
+    #[serde(default, alias = "input")]
+    pub data: Option<Bytes>,
+}
+
+/// Single value or an array of values, matched if any of them is equal.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum ValueOrArray<T> {
+    Value(T),
+    Array(Vec<T>),
+}
+
+impl<T: PartialEq> ValueOrArray<T> {
+    pub fn contains(&self, value: &T) -> bool {
+        match self {
+            Self::Value(v) => v == value,
+            Self::Array(values) => values.contains(value),
+        }
+    }
+}
+
+/// Filter of `xcb_getLogs`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilter {
+    #[serde(default)]
+    pub from_block: Option<BlockTag>,
+    #[serde(default)]
+    pub to_block: Option<BlockTag>,
+    /// Block the logs are returned for, overrides the block range.
+    #[serde(default)]
+    pub block_hash: Option<B256>,
+    /// Addresses of the emitters, logs of any address are matched if empty.
+    #[serde(default, deserialize_with = "deserialize_addresses")]
+    pub address: Vec<Address>,
+    /// Topics by position, `null` matches any topic.
+    #[serde(default)]
+    pub topics: Option<Vec<Option<ValueOrArray<B256>>>>,
+}
+
+impl LogFilter {
+    /// Whether the log matches the addresses and topics of the filter.
+    pub fn matches(&self, log: &RpcLog) -> bool {
+        if !self.address.is_empty() && !self.address.contains(&log.address.to_address()) {
+            return false;
+        }
+        self.topics.iter().flatten().enumerate().all(|(i, topics)| {
+            topics.as_ref().map_or(true, |topics| {
+                log.topics
+                    .get(i)
+                    .is_some_and(|topic| topics.contains(topic))
+            })
+        })
+    }
+}
+
+/// Receipt returned by `xcb_getTransactionReceipt`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReceipt {
+    pub transaction_hash: B256,
+    pub transaction_index: U256,
+    pub block_hash: B256,
+    pub block_number: U256,
+    pub from: IcanAddress,
+    pub to: Option<IcanAddress>,
+    pub contract_address: Option<IcanAddress>,
+    pub cumulative_energy_used: U256,
+    pub energy_used: U256,
+    pub effective_energy_price: U256,
+    /// `0x1` if transaction succeeded, `0x0` otherwise.
+    pub status: U256,
+    pub logs: Vec<RpcLog>,
+    pub logs_bloom: Bloom,
+}
+
+/// Log together with the location it was emitted at.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLog {
+    pub address: IcanAddress,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    pub block_hash: B256,
+    pub block_number: U256,
+    pub transaction_hash: B256,
+    pub transaction_index: U256,
+    /// Index of the log in the block.
+    pub log_index: U256,
+    pub removed: bool,
+}
+
+fn deserialize_option_address<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Address>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| parse_address(&s).map_err(de::Error::custom))
+        .transpose()
+}
+
+fn deserialize_addresses<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Address>, D::Error> {
+    let addresses = match Option::<ValueOrArray<String>>::deserialize(deserializer)? {
+        None => Vec::new(),
+        Some(ValueOrArray::Value(address)) => vec![address],
+        Some(ValueOrArray::Array(addresses)) => addresses,
+    };
+    addresses
+        .iter()
+        .map(|s| parse_address(s).map_err(de::Error::custom))
+        .collect()
+}
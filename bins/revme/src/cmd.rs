@@ -1,6 +1,11 @@
+pub mod blocktest;
+pub mod debug;
 pub mod evmrunner;
+pub mod fill;
 pub mod format_kzg_setup;
 pub mod statetest;
+pub mod t8n;
+pub mod tracediff;
 
 use structopt::{clap::AppSettings, StructOpt};
 
@@ -10,6 +15,8 @@ use structopt::{clap::AppSettings, StructOpt};
 pub enum MainCmd {
     #[structopt(about = "Launch Ethereum state tests")]
     Statetest(statetest::Cmd),
+    #[structopt(about = "Launch blockchain tests")]
+    Blocktest(blocktest::Cmd),
     #[structopt(
         about = "Format kzg settings from a trusted setup file (.txt) into binary format (.bin)"
     )]
@@ -18,6 +25,16 @@ pub enum MainCmd {
         about = "Evm runner command allows running arbitrary evm bytecode.\nBytecode can be provided from cli or from file with --path option."
     )]
     Evm(evmrunner::Cmd),
+    #[structopt(
+        about = "Fill state tests from fillers, writing expected roots and signed transactions"
+    )]
+    Fill(fill::Cmd),
+    #[structopt(about = "Run the state transition of a block, compatible with the evm t8n tool")]
+    T8n(t8n::Cmd),
+    #[structopt(about = "Interactive step debugger of bytecode or signed transaction")]
+    Debug(debug::Cmd),
+    #[structopt(about = "Find the first step two EIP-3155 traces diverge at")]
+    Tracediff(tracediff::Cmd),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -25,17 +42,32 @@ pub enum Error {
     #[error(transparent)]
     Statetest(#[from] statetest::Error),
     #[error(transparent)]
+    Blocktest(#[from] blocktest::Error),
+    #[error(transparent)]
     KzgErrors(#[from] format_kzg_setup::KzgErrors),
     #[error(transparent)]
     EvmRunnerErrors(#[from] evmrunner::Errors),
+    #[error(transparent)]
+    Fill(#[from] fill::FillError),
+    #[error(transparent)]
+    T8n(#[from] t8n::Error),
+    #[error(transparent)]
+    Debug(#[from] debug::Error),
+    #[error(transparent)]
+    Tracediff(#[from] tracediff::Error),
 }
 
 impl MainCmd {
     pub fn run(&self) -> Result<(), Error> {
         match self {
             Self::Statetest(cmd) => cmd.run().map_err(Into::into),
+            Self::Blocktest(cmd) => cmd.run().map_err(Into::into),
             Self::FormatKzgSetup(cmd) => cmd.run().map_err(Into::into),
             Self::Evm(cmd) => cmd.run().map_err(Into::into),
+            Self::Fill(cmd) => cmd.run().map_err(Into::into),
+            Self::T8n(cmd) => cmd.run().map_err(Into::into),
+            Self::Debug(cmd) => cmd.run().map_err(Into::into),
+            Self::Tracediff(cmd) => cmd.run().map_err(Into::into),
         }
     }
 }
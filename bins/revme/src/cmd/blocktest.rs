@@ -0,0 +1,42 @@
+pub mod models;
+mod runner;
+
+pub use runner::BlockTestError as Error;
+pub use runner::{
+    execute_test_file, execute_unit, BlockTestError, BlockTestErrorKind, InvalidBlock,
+};
+
+use super::statetest::find_all_json_tests;
+use std::path::PathBuf;
+use std::vec::Vec;
+use structopt::StructOpt;
+
+/// Blocktest command
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// Path to folder or file containing the blockchain tests. If multiple paths are specified
+    /// they will be run in sequence.
+    ///
+    /// Folders will be searched recursively for files with the extension `.json`.
+    #[structopt(required = true)]
+    path: Vec<PathBuf>,
+    /// Network id the blocks are executed with.
+    #[structopt(long, default_value = "1")]
+    network_id: u64,
+}
+
+impl Cmd {
+    /// Run blocktest command.
+    pub fn run(&self) -> Result<(), Error> {
+        for path in &self.path {
+            println!("\nRunning blockchain tests in {}...", path.display());
+            let test_files = find_all_json_tests(path);
+            let n_files = test_files.len();
+            for test_file in test_files {
+                execute_test_file(&test_file, self.network_id)?;
+            }
+            println!("All {n_files} files passed!");
+        }
+        Ok(())
+    }
+}
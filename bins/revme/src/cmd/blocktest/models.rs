@@ -0,0 +1,104 @@
+use super::super::statetest::models::{
+    deserializer::{deserialize_maybe_empty, deserialize_str_as_u64},
+    AccessList, AccountInfo, SpecName,
+};
+use revm::primitives::{Address, Bytes, HashMap, B256, U256};
+use revm_chain::Bloom;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::vec::Vec;
+
+/// Blockchain tests are not checked for unknown fields as blocks carry fields like rlp
+/// encoding and signatures that the runner does not use.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct BlockchainTestSuite(pub BTreeMap<String, BlockchainTestUnit>);
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainTestUnit {
+    pub network: SpecName,
+    pub genesis_block_header: BlockHeader,
+    pub pre: HashMap<Address, AccountInfo>,
+    pub blocks: Vec<Block>,
+    #[serde(default)]
+    pub lastblockhash: Option<B256>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeader {
+    pub hash: B256,
+    pub parent_hash: B256,
+    pub number: U256,
+    pub timestamp: U256,
+    pub coinbase: Address,
+    pub difficulty: U256,
+    pub energy_limit: U256,
+    pub energy_used: U256,
+    pub mix_hash: B256,
+    pub state_root: B256,
+    pub receipt_trie: B256,
+    pub bloom: Bloom,
+    pub base_fee_per_energy: Option<U256>,
+    pub withdrawals_root: Option<B256>,
+    pub blob_energy_used: Option<U256>,
+    pub excess_blob_energy: Option<U256>,
+    pub parent_beacon_block_root: Option<B256>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+    /// Header is not present if block is expected to fail decoding.
+    pub block_header: Option<BlockHeader>,
+    #[serde(default)]
+    pub transactions: Vec<Transaction>,
+    #[serde(default)]
+    pub uncle_headers: Vec<BlockHeader>,
+    #[serde(default)]
+    pub withdrawals: Vec<Withdrawal>,
+    pub expect_exception: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    #[serde(default, rename = "type")]
+    pub tx_type: Option<U256>,
+    pub sender: Address,
+    #[serde(deserialize_with = "deserialize_maybe_empty")]
+    pub to: Option<Address>,
+    pub nonce: U256,
+    pub value: U256,
+    pub data: Bytes,
+    pub energy_limit: U256,
+    pub energy_price: Option<U256>,
+    pub max_fee_per_energy: Option<U256>,
+    pub max_priority_fee_per_energy: Option<U256>,
+    #[serde(default)]
+    pub access_list: Option<AccessList>,
+    pub max_fee_per_blob_energy: Option<U256>,
+    #[serde(default)]
+    pub blob_versioned_hashes: Vec<B256>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    pub address: Address,
+    /// Amount in Gwei.
+    #[serde(deserialize_with = "deserialize_str_as_u64")]
+    pub amount: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_withdrawal() {
+        let json = r#"{"index":"0x00","validatorIndex":"0x00","address":"0xc94f5374fce5edbc8e2a8697c15331677e6ebf0b","amount":"0x01"}"#;
+        let withdrawal: Withdrawal = serde_json::from_str(json).unwrap();
+        assert_eq!(withdrawal.amount, 1);
+    }
+}
@@ -0,0 +1,428 @@
+use super::models::{Block, BlockHeader, BlockchainTestSuite, BlockchainTestUnit, Transaction};
+use crate::cmd::statetest::{models::SpecName, pre_state};
+use revm::{
+    interpreter::CreateScheme,
+    primitives::{Address, EVMError, Env, HashMap, SpecId, TransactTo, TxEnv, B256, U256},
+    CacheState, State,
+};
+use revm_chain::{trie::state_merkle_trie_root, BlockExecutor, Bloom};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::vec::Vec;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Test {name} failed at block {block}: {kind}")]
+pub struct BlockTestError {
+    pub name: String,
+    pub block: usize,
+    pub kind: BlockTestErrorKind,
+}
+
+#[derive(Debug, Error)]
+pub enum BlockTestErrorKind {
+    #[error("block is valid but test expects exception: {0}")]
+    ExpectedException(String),
+    #[error("block is invalid: {0}")]
+    InvalidBlock(#[from] InvalidBlock),
+    #[error("genesis state root mismatch: expected {expected:?}, got {got:?}")]
+    GenesisStateRootMismatch { got: B256, expected: B256 },
+    #[error("last block hash mismatch: expected {expected:?}, got {got:?}")]
+    LastBlockHashMismatch { got: B256, expected: B256 },
+    /// Execution failed for a reason that does not make the block invalid.
+    #[error("transaction {index} can't be executed: {error}")]
+    Execution { index: usize, error: String },
+    #[error(transparent)]
+    SerdeDeserialize(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Reason block was rejected.
+#[derive(Debug, Error)]
+pub enum InvalidBlock {
+    #[error("block header can't be decoded")]
+    MissingHeader,
+    #[error("transaction {index} is invalid: {error}")]
+    Transaction { index: usize, error: String },
+    #[error("energy used mismatch: expected {expected}, got {got}")]
+    EnergyUsedMismatch { got: u64, expected: U256 },
+    #[error("state root mismatch: expected {expected:?}, got {got:?}")]
+    StateRootMismatch { got: B256, expected: B256 },
+    #[error("receipts root mismatch: expected {expected:?}, got {got:?}")]
+    ReceiptsRootMismatch { got: B256, expected: B256 },
+    #[error("logs bloom mismatch: expected {expected:?}, got {got:?}")]
+    LogsBloomMismatch { got: Bloom, expected: Bloom },
+}
+
+/// Block reward of the spec, `None` after the Merge.
+fn block_reward(spec_id: SpecId) -> Option<u128> {
+    const ETHER: u128 = 1_000_000_000_000_000_000;
+    if SpecId::enabled(spec_id, SpecId::MERGE) {
+        None
+    } else if SpecId::enabled(spec_id, SpecId::PETERSBURG) {
+        Some(2 * ETHER)
+    } else if SpecId::enabled(spec_id, SpecId::BYZANTIUM) {
+        Some(3 * ETHER)
+    } else {
+        Some(5 * ETHER)
+    }
+}
+
+fn block_env(env: &mut Env, header: &BlockHeader, spec_id: SpecId) {
+    env.block.number = header.number;
+    env.block.coinbase = header.coinbase;
+    env.block.timestamp = header.timestamp;
+    env.block.energy_limit = header.energy_limit;
+    env.block.basefee = header.base_fee_per_energy.unwrap_or_default();
+    env.block.difficulty = header.difficulty;
+    env.block.prevrandao = SpecId::enabled(spec_id, SpecId::MERGE).then_some(header.mix_hash);
+    env.block.blob_excess_energy_and_price = None;
+    if let Some(excess_blob_energy) = header.excess_blob_energy {
+        env.block
+            .set_blob_excess_energy_and_price(excess_blob_energy.to());
+    }
+}
+
+fn tx_env(tx: &Transaction) -> TxEnv {
+    TxEnv {
+        caller: tx.sender,
+        energy_limit: tx.energy_limit.saturating_to(),
+        energy_price: tx
+            .energy_price
+            .or(tx.max_fee_per_energy)
+            .unwrap_or_default(),
+        energy_priority_fee: tx.max_priority_fee_per_energy,
+        transact_to: match tx.to {
+            Some(address) => TransactTo::Call(address),
+            None => TransactTo::Create(CreateScheme::Create),
+        },
+        value: tx.value,
+        data: tx.data.clone(),
+        nonce: Some(tx.nonce.saturating_to()),
+        access_list: tx
+            .access_list
+            .iter()
+            .flatten()
+            .map(|item| {
+                (
+                    item.address,
+                    item.storage_keys
+                        .iter()
+                        .map(|key| U256::from_be_bytes(key.0))
+                        .collect(),
+                )
+            })
+            .collect(),
+        blob_hashes: tx.blob_versioned_hashes.clone(),
+        max_fee_per_blob_energy: tx.max_fee_per_blob_energy,
+        ..Default::default()
+    }
+}
+
+/// Executes the block on top of the cache and returns the cache with the changes applied.
+///
+/// Only [BlockTestErrorKind::InvalidBlock] errors mean the block is invalid.
+fn execute_block(
+    cache: &CacheState,
+    block_hashes: &BTreeMap<u64, B256>,
+    block: &Block,
+    env: &mut Env,
+    spec_id: SpecId,
+) -> Result<CacheState, BlockTestErrorKind> {
+    let header = block
+        .block_header
+        .as_ref()
+        .ok_or(InvalidBlock::MissingHeader)?;
+    block_env(env, header, spec_id);
+
+    let mut state = State::builder()
+        .with_cached_prestate(cache.clone())
+        .with_block_hashes(block_hashes.clone())
+        .with_bundle_update()
+        .build();
+    state.set_state_clear_flag(SpecId::enabled(spec_id, SpecId::SPURIOUS_DRAGON));
+
+    let mut executor = BlockExecutor::new(&mut state, env.clone(), spec_id);
+    for (index, tx) in block.transactions.iter().enumerate() {
+        let tx_type = tx.tx_type.map(|t| t.saturating_to()).unwrap_or_default();
+        executor
+            .execute(tx_env(tx), tx_type)
+            .map_err(|error| match error {
+                EVMError::Transaction(_) | EVMError::Header(_) => InvalidBlock::Transaction {
+                    index,
+                    error: error.to_string(),
+                }
+                .into(),
+                error => BlockTestErrorKind::Execution {
+                    index,
+                    error: error.to_string(),
+                },
+            })?;
+    }
+    let cumulative_energy_used = executor.cumulative_energy_used();
+    if U256::from(cumulative_energy_used) != header.energy_used {
+        return Err(InvalidBlock::EnergyUsedMismatch {
+            got: cumulative_energy_used,
+            expected: header.energy_used,
+        }
+        .into());
+    }
+
+    // block and uncle rewards.
+    let mut balances: HashMap<Address, u128> = HashMap::new();
+    if let Some(reward) = block_reward(spec_id) {
+        let number: u64 = header.number.saturating_to();
+        for uncle in &block.uncle_headers {
+            let uncle_number: u64 = uncle.number.saturating_to();
+            let uncle_reward = u128::from((uncle_number + 8).saturating_sub(number)) * reward / 8;
+            *balances.entry(uncle.coinbase).or_default() += uncle_reward;
+        }
+        let uncles = block.uncle_headers.len() as u128;
+        *balances.entry(header.coinbase).or_default() += reward + reward / 32 * uncles;
+    }
+    executor.finalize(
+        balances,
+        block.withdrawals.iter().map(|w| (w.address, w.amount)),
+    );
+
+    let receipts_root = executor.receipts_root();
+    let bloom = executor.logs_bloom();
+    drop(executor);
+    let state_root = state_merkle_trie_root(state.cache.trie_account());
+    if state_root != header.state_root {
+        return Err(InvalidBlock::StateRootMismatch {
+            got: state_root,
+            expected: header.state_root,
+        }
+        .into());
+    }
+    if receipts_root != header.receipt_trie {
+        return Err(InvalidBlock::ReceiptsRootMismatch {
+            got: receipts_root,
+            expected: header.receipt_trie,
+        }
+        .into());
+    }
+    if bloom != header.bloom {
+        return Err(InvalidBlock::LogsBloomMismatch {
+            got: bloom,
+            expected: header.bloom,
+        }
+        .into());
+    }
+    Ok(state.cache)
+}
+
+/// Executes all blocks of the test, expected invalid blocks must be rejected and
+/// leave the state unchanged.
+///
+/// The pre state must match the genesis state root and the last accepted block must
+/// match the expected last block hash of the test.
+pub fn execute_unit(
+    name: &str,
+    unit: BlockchainTestUnit,
+    network_id: u64,
+) -> Result<(), BlockTestError> {
+    if unit.network == SpecName::Unknown {
+        return Ok(());
+    }
+    let spec_id = unit.network.to_spec_id();
+    let error = |block, kind| BlockTestError {
+        name: name.to_string(),
+        block,
+        kind,
+    };
+
+    let mut cache = pre_state(&unit.pre);
+    let genesis = &unit.genesis_block_header;
+    let genesis_state_root = state_merkle_trie_root(cache.trie_account());
+    if genesis_state_root != genesis.state_root {
+        return Err(error(
+            0,
+            BlockTestErrorKind::GenesisStateRootMismatch {
+                got: genesis_state_root,
+                expected: genesis.state_root,
+            },
+        ));
+    }
+    let mut block_hashes = BTreeMap::new();
+    block_hashes.insert(genesis.number.saturating_to(), genesis.hash);
+    let mut last_block_hash = genesis.hash;
+    let mut env = Env::default();
+    env.cfg.network_id = network_id;
+
+    for (index, block) in unit.blocks.iter().enumerate() {
+        let error = |kind| error(index, kind);
+        match (
+            execute_block(&cache, &block_hashes, block, &mut env, spec_id),
+            &block.expect_exception,
+        ) {
+            (Ok(new_cache), None) => {
+                let header = block.block_header.as_ref().expect("block is valid");
+                block_hashes.insert(header.number.saturating_to(), header.hash);
+                last_block_hash = header.hash;
+                cache = new_cache;
+            }
+            (Ok(_), Some(exception)) => {
+                return Err(error(BlockTestErrorKind::ExpectedException(
+                    exception.clone(),
+                )))
+            }
+            // rejected block does not change the state.
+            (Err(BlockTestErrorKind::InvalidBlock(_)), Some(_)) => {}
+            (Err(kind), _) => return Err(error(kind)),
+        }
+    }
+    match unit.lastblockhash {
+        Some(expected) if expected != last_block_hash => Err(error(
+            unit.blocks.len().saturating_sub(1),
+            BlockTestErrorKind::LastBlockHashMismatch {
+                got: last_block_hash,
+                expected,
+            },
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Executes all tests of the blockchain test file on the given network.
+pub fn execute_test_file(path: &Path, network_id: u64) -> Result<(), BlockTestError> {
+    let error = |kind| BlockTestError {
+        name: path.to_string_lossy().into_owned(),
+        block: 0,
+        kind,
+    };
+    let s = std::fs::read_to_string(path).map_err(|e| error(e.into()))?;
+    let suite: BlockchainTestSuite = serde_json::from_str(&s).map_err(|e| error(e.into()))?;
+    for (name, unit) in suite.0 {
+        execute_unit(&name, unit, network_id)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::{blocktest::models::Withdrawal, statetest::models::AccountInfo};
+    use revm::primitives::Bytes;
+    use revm_chain::trie::ordered_trie_root;
+
+    fn header(number: u64, state_root: B256) -> BlockHeader {
+        BlockHeader {
+            hash: B256::with_last_byte(number as u8),
+            parent_hash: B256::with_last_byte(number.saturating_sub(1) as u8),
+            number: U256::from(number),
+            timestamp: U256::from(number * 12),
+            coinbase: Address::with_last_byte(0xcc),
+            difficulty: U256::ZERO,
+            energy_limit: U256::from(30_000_000),
+            energy_used: U256::ZERO,
+            mix_hash: B256::ZERO,
+            state_root,
+            receipt_trie: ordered_trie_root(Vec::<Vec<u8>>::new()),
+            bloom: Bloom::ZERO,
+            base_fee_per_energy: None,
+            withdrawals_root: None,
+            blob_energy_used: None,
+            excess_blob_energy: None,
+            parent_beacon_block_root: None,
+        }
+    }
+
+    /// Unit with a valid block that pays a withdrawal, followed by a block with a
+    /// transaction whose sender can't pay for it.
+    fn unit(invalid_block_exception: Option<String>) -> BlockchainTestUnit {
+        let account = Address::with_last_byte(0xaa);
+        let recipient = Address::with_last_byte(0xbb);
+        let pre = HashMap::from_iter([(
+            account,
+            AccountInfo {
+                balance: U256::from(1),
+                code: Bytes::new(),
+                nonce: 0,
+                storage: HashMap::new(),
+            },
+        )]);
+        let mut cache = pre_state(&pre);
+        cache.insert_account(
+            recipient,
+            revm::primitives::AccountInfo::from_balance(U256::from(1_000_000_000)),
+        );
+        let state_root = state_merkle_trie_root(cache.trie_account());
+
+        let valid = Block {
+            block_header: Some(header(1, state_root)),
+            transactions: Vec::new(),
+            uncle_headers: Vec::new(),
+            withdrawals: vec![Withdrawal {
+                address: recipient,
+                amount: 1,
+            }],
+            expect_exception: None,
+        };
+        let invalid = Block {
+            block_header: Some(header(2, state_root)),
+            transactions: vec![Transaction {
+                tx_type: None,
+                sender: account,
+                to: Some(recipient),
+                nonce: U256::ZERO,
+                value: U256::ZERO,
+                data: Bytes::new(),
+                energy_limit: U256::from(21_000),
+                energy_price: Some(U256::from(1)),
+                max_fee_per_energy: None,
+                max_priority_fee_per_energy: None,
+                access_list: None,
+                max_fee_per_blob_energy: None,
+                blob_versioned_hashes: Vec::new(),
+            }],
+            uncle_headers: Vec::new(),
+            withdrawals: Vec::new(),
+            expect_exception: invalid_block_exception,
+        };
+        BlockchainTestUnit {
+            network: SpecName::Shanghai,
+            genesis_block_header: header(0, state_merkle_trie_root(pre_state(&pre).trie_account())),
+            pre,
+            blocks: vec![valid, invalid],
+            lastblockhash: Some(B256::with_last_byte(1)),
+        }
+    }
+
+    #[test]
+    fn execute_valid_and_invalid_block() {
+        let exception = "TransactionException.INSUFFICIENT_ACCOUNT_FUNDS".to_string();
+        execute_unit("test", unit(Some(exception)), 1).unwrap();
+
+        let error = execute_unit("test", unit(None), 1).unwrap_err();
+        assert_eq!(error.block, 1);
+        assert!(matches!(
+            error.kind,
+            BlockTestErrorKind::InvalidBlock(InvalidBlock::Transaction { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn check_genesis_and_last_block_hash() {
+        let exception = "TransactionException.INSUFFICIENT_ACCOUNT_FUNDS".to_string();
+        let mut wrong_last_block = unit(Some(exception.clone()));
+        // the rejected block must not become the last block.
+        wrong_last_block.lastblockhash = Some(B256::with_last_byte(2));
+        let error = execute_unit("test", wrong_last_block, 1).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            BlockTestErrorKind::LastBlockHashMismatch { got, .. } if got == B256::with_last_byte(1)
+        ));
+
+        let mut wrong_genesis = unit(Some(exception));
+        wrong_genesis.genesis_block_header.state_root = B256::ZERO;
+        let error = execute_unit("test", wrong_genesis, 1).unwrap_err();
+        assert_eq!(error.block, 0);
+        assert!(matches!(
+            error.kind,
+            BlockTestErrorKind::GenesisStateRootMismatch { .. }
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+mod debugger;
+
+pub use debugger::{Breakpoint, Command, Debugger};
+
+use super::{statetest::models::SpecName, t8n::models::parse_alloc};
+use alloy_rlp::Decodable;
+use revm::{
+    db::{Genesis, InMemoryDB},
+    inspector_handle_register,
+    primitives::{sha3, Address, Bytecode, Bytes, Env, TransactTo},
+    Evm,
+};
+use revm_chain::SignedTransaction;
+use std::io::{stdin, stdout, BufReader};
+use std::path::PathBuf;
+use std::vec::Vec;
+use structopt::StructOpt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid bytecode")]
+    InvalidBytecode,
+    #[error("Invalid input")]
+    InvalidInput,
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+    #[error("Invalid prestate: {0}")]
+    InvalidPrestate(serde_json::Error),
+    #[error("EVM Error: {0}")]
+    EVMError(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Interactive debugger of the bytecode or signed transaction.
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// Bytecode that is called at the zero address, or hex of the rlp encoded signed
+    /// transaction if `--tx` is set.
+    #[structopt(default_value = "")]
+    bytecode: String,
+    /// Path to file containing the bytecode or the transaction.
+    /// Overrides the bytecode option.
+    #[structopt(long)]
+    path: Option<PathBuf>,
+    /// Debug the signed transaction instead of the bytecode.
+    #[structopt(long)]
+    tx: bool,
+    /// Input bytes of the bytecode call.
+    #[structopt(long, default_value = "")]
+    input: String,
+    /// Path to JSON file with the accounts of the state before execution.
+    #[structopt(long)]
+    prestate: Option<PathBuf>,
+    #[structopt(long, default_value = "1")]
+    network_id: u64,
+    /// Spec the code is executed with.
+    #[structopt(long, default_value = "Cancun")]
+    spec: SpecName,
+    /// Breakpoint set before execution, as `pc <n>`, `op <name>` or `depth <n>`.
+    #[structopt(short = "b", long = "break", number_of_values = 1)]
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl Cmd {
+    /// Run debug command.
+    pub fn run(&self) -> Result<(), Error> {
+        let hex = match &self.path {
+            Some(path) => std::fs::read_to_string(path)?,
+            None => self.bytecode.clone(),
+        };
+        let hex = hex.trim();
+        let bytes: Bytes = hex::decode(hex.strip_prefix("0x").unwrap_or(hex))
+            .map_err(|_| Error::InvalidBytecode)?
+            .into();
+
+        let mut db = self.prestate()?;
+        let mut env = Env::default();
+        env.cfg.network_id = self.network_id;
+        if self.tx {
+            let tx = SignedTransaction::decode(&mut bytes.as_ref())
+                .map_err(|e| Error::InvalidTransaction(e.to_string()))?;
+            env.tx = tx
+                .recover_sender()
+                .and_then(|sender| tx.tx_env(sender))
+                .map_err(Error::InvalidTransaction)?;
+        } else {
+            // the bytecode is deployed at the zero address, keeping its prestate.
+            let mut info = db
+                .accounts
+                .get(&Address::ZERO)
+                .map(|account| account.info.clone())
+                .unwrap_or_default();
+            info.code_hash = sha3(&bytes);
+            info.code = Some(Bytecode::new_raw(bytes));
+            db.insert_account_info(Address::ZERO, info);
+            env.tx.caller = Address::with_last_byte(1);
+            env.tx.transact_to = TransactTo::Call(Address::ZERO);
+            env.tx.data = hex::decode(self.input.trim())
+                .map_err(|_| Error::InvalidInput)?
+                .into();
+        }
+
+        let mut debugger = Debugger::new(BufReader::new(stdin()), stdout());
+        for breakpoint in &self.breakpoints {
+            debugger.add_breakpoint(*breakpoint);
+        }
+        println!("Type help for commands.");
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_env(|e| **e = env.clone())
+            .with_spec_id(self.spec.to_spec_id())
+            .with_external_context(debugger)
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let out = evm.transact().map_err(|e| Error::EVMError(e.to_string()))?;
+        println!("Result: {:#?}", out.result);
+        Ok(())
+    }
+
+    fn prestate(&self) -> Result<InMemoryDB, Error> {
+        let Some(path) = &self.prestate else {
+            return Ok(InMemoryDB::default());
+        };
+        let alloc = parse_alloc(&std::fs::read_to_string(path)?).map_err(Error::InvalidPrestate)?;
+        Ok(Genesis {
+            alloc,
+            ..Default::default()
+        }
+        .in_memory_db())
+    }
+}
@@ -0,0 +1,520 @@
+use revm::{
+    interpreter::{
+        opcode::OPCODE_JUMPMAP, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+    },
+    Database, EvmContext, Inspector,
+};
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+use std::string::String;
+use std::vec::Vec;
+
+const HELP: &str = "\
+step (s)              execute next instruction, stepping into calls
+next (n)              execute next instruction, stepping over calls
+out (o)               run until the current call frame returns
+continue (c)          run until a breakpoint is hit
+quit (q)              run to the end without stopping
+break (b) pc <n>      stop at program counter
+break (b) op <name>   stop at opcode, by name or hex value
+break (b) depth <n>   stop when a call frame at the depth is entered
+delete (d) <n>        delete breakpoint
+breakpoints (bl)      list breakpoints
+info (i)              print current location
+stack                 print stack, top first
+memory (mem)          print memory of the current frame
+storage               print loaded storage of the current contract
+returndata (ret)      print return data of the last call
+energy                print energy of the current frame
+help (h)              print this help
+empty line            step";
+
+/// Condition the debugger stops at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    Pc(usize),
+    Opcode(u8),
+    Depth(u64),
+}
+
+impl Breakpoint {
+    /// Whether the breakpoint is hit at the instruction, `frame_entered` is set for the first
+    /// instruction of a call frame.
+    pub fn is_hit(&self, pc: usize, opcode: u8, depth: u64, frame_entered: bool) -> bool {
+        match *self {
+            Self::Pc(at) => pc == at,
+            Self::Opcode(op) => opcode == op,
+            Self::Depth(at) => frame_entered && depth == at,
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("invalid breakpoint {s}, expected <pc|op|depth> <value>"))?;
+        let value = value.trim();
+        match kind {
+            "pc" => parse_number(value).map(Self::Pc),
+            "depth" => parse_number(value).map(|depth| Self::Depth(depth as u64)),
+            "op" => parse_opcode(value).map(Self::Opcode),
+            _ => Err(format!("unknown breakpoint kind {kind}")),
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Pc(pc) => write!(f, "pc {pc}"),
+            Self::Opcode(opcode) => write!(f, "op {}", opcode_name(opcode)),
+            Self::Depth(depth) => write!(f, "depth {depth}"),
+        }
+    }
+}
+
+fn parse_number(s: &str) -> Result<usize, String> {
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|e| format!("invalid number {s}: {e}"))
+}
+
+fn parse_opcode(s: &str) -> Result<u8, String> {
+    if let Some(hex) = s.strip_prefix("0x") {
+        return u8::from_str_radix(hex, 16).map_err(|e| format!("invalid opcode {s}: {e}"));
+    }
+    let name = s.to_uppercase();
+    OPCODE_JUMPMAP
+        .iter()
+        .position(|op| *op == Some(name.as_str()))
+        .map(|opcode| opcode as u8)
+        .ok_or_else(|| format!("unknown opcode {s}"))
+}
+
+fn opcode_name(opcode: u8) -> String {
+    match OPCODE_JUMPMAP[opcode as usize] {
+        Some(name) => name.into(),
+        None => format!("{opcode:#04x}"),
+    }
+}
+
+/// Command of the debugger prompt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    Step,
+    Next,
+    Out,
+    Continue,
+    Quit,
+    Break(Breakpoint),
+    Delete(usize),
+    Breakpoints,
+    Info,
+    Stack,
+    Memory,
+    Storage,
+    ReturnData,
+    Energy,
+    Help,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (command, args) = s.split_once(' ').unwrap_or((s, ""));
+        Ok(match command {
+            "" | "s" | "step" => Self::Step,
+            "n" | "next" => Self::Next,
+            "o" | "out" => Self::Out,
+            "c" | "continue" => Self::Continue,
+            "q" | "quit" => Self::Quit,
+            "b" | "break" => Self::Break(args.parse()?),
+            "d" | "delete" => Self::Delete(parse_number(args.trim())?),
+            "bl" | "breakpoints" => Self::Breakpoints,
+            "i" | "info" => Self::Info,
+            "stack" => Self::Stack,
+            "mem" | "memory" => Self::Memory,
+            "storage" => Self::Storage,
+            "ret" | "returndata" => Self::ReturnData,
+            "energy" => Self::Energy,
+            "h" | "help" => Self::Help,
+            _ => return Err(format!("unknown command {command}, type help for commands")),
+        })
+    }
+}
+
+/// How the execution is resumed after the prompt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Resume {
+    /// Stop at the next instruction.
+    Step,
+    /// Stop at the next instruction that is not deeper than the depth.
+    Next(u64),
+    /// Stop at the next instruction that is shallower than the depth.
+    Out(u64),
+    /// Stop only at breakpoints.
+    Continue,
+    /// Never stop.
+    Run,
+}
+
+/// Interactive step debugger.
+///
+/// Execution stops before the first instruction and commands are read from the input
+/// every time it stops, breakpoints are checked in every mode except after quit.
+#[derive(Debug)]
+pub struct Debugger<R, W> {
+    input: R,
+    output: W,
+    breakpoints: Vec<Breakpoint>,
+    resume: Resume,
+    /// Call frame was entered and its first instruction was not executed yet.
+    frame_entered: bool,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            breakpoints: Vec::new(),
+            resume: Resume::Step,
+            frame_entered: false,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    fn should_stop(&self, pc: usize, opcode: u8, depth: u64, frame_entered: bool) -> bool {
+        let stop = match self.resume {
+            Resume::Step => true,
+            Resume::Next(at) => depth <= at,
+            Resume::Out(at) => depth < at,
+            Resume::Continue => false,
+            Resume::Run => return false,
+        };
+        stop || self
+            .breakpoints
+            .iter()
+            .any(|breakpoint| breakpoint.is_hit(pc, opcode, depth, frame_entered))
+    }
+
+    /// Reads commands until one of them resumes the execution.
+    fn prompt<DB: Database>(&mut self, interp: &Interpreter, context: &EvmContext<DB>) {
+        let depth = context.journaled_state.depth();
+        write_location(&mut self.output, interp, depth);
+        loop {
+            let _ = write!(self.output, "(debug) ");
+            let _ = self.output.flush();
+            let mut line = String::new();
+            if self.input.read_line(&mut line).unwrap_or_default() == 0 {
+                // input is closed, run to the end.
+                self.resume = Resume::Run;
+                return;
+            }
+            let command = match line.parse::<Command>() {
+                Ok(command) => command,
+                Err(error) => {
+                    let _ = writeln!(self.output, "{error}");
+                    continue;
+                }
+            };
+            self.resume = match command {
+                Command::Step => Resume::Step,
+                Command::Next => Resume::Next(depth),
+                Command::Out => Resume::Out(depth),
+                Command::Continue => Resume::Continue,
+                Command::Quit => Resume::Run,
+                command => {
+                    self.inspect(command, interp, context);
+                    continue;
+                }
+            };
+            return;
+        }
+    }
+
+    fn inspect<DB: Database>(
+        &mut self,
+        command: Command,
+        interp: &Interpreter,
+        context: &EvmContext<DB>,
+    ) {
+        let out = &mut self.output;
+        let _ = match command {
+            Command::Break(breakpoint) => {
+                self.breakpoints.push(breakpoint);
+                writeln!(
+                    out,
+                    "breakpoint {}: {breakpoint}",
+                    self.breakpoints.len() - 1
+                )
+            }
+            Command::Delete(index) if index < self.breakpoints.len() => {
+                let breakpoint = self.breakpoints.remove(index);
+                writeln!(out, "deleted breakpoint {index}: {breakpoint}")
+            }
+            Command::Delete(index) => writeln!(out, "no breakpoint {index}"),
+            Command::Breakpoints => {
+                for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+                    let _ = writeln!(out, "{index}: {breakpoint}");
+                }
+                Ok(())
+            }
+            Command::Info => {
+                write_location(out, interp, context.journaled_state.depth());
+                Ok(())
+            }
+            Command::Stack => {
+                for (index, value) in interp.stack.data().iter().rev().enumerate() {
+                    let _ = writeln!(out, "{index:>4}: {value:#x}");
+                }
+                Ok(())
+            }
+            Command::Memory => {
+                let memory = interp.shared_memory.context_memory();
+                for (index, word) in memory.chunks(32).enumerate() {
+                    let _ = writeln!(out, "{:#06x}: {}", index * 32, hex::encode(word));
+                }
+                Ok(())
+            }
+            Command::Storage => {
+                let address = interp.contract.address;
+                if let Some(account) = context.journaled_state.state.get(&address) {
+                    for (slot, value) in &account.storage {
+                        let _ = writeln!(
+                            out,
+                            "{slot:#x}: {:#x} (original {:#x})",
+                            value.present_value(),
+                            value.original_value()
+                        );
+                    }
+                }
+                Ok(())
+            }
+            Command::ReturnData => writeln!(out, "{}", interp.return_data_buffer),
+            Command::Energy => {
+                let energy = interp.energy();
+                writeln!(
+                    out,
+                    "limit {}, remaining {}, spent {}, refunded {}",
+                    energy.limit(),
+                    energy.remaining(),
+                    energy.spent(),
+                    energy.refunded()
+                )
+            }
+            Command::Help => writeln!(out, "{HELP}"),
+            Command::Step | Command::Next | Command::Out | Command::Continue | Command::Quit => {
+                Ok(())
+            }
+        };
+    }
+
+    fn is_running(&self) -> bool {
+        self.resume == Resume::Run
+    }
+}
+
+fn write_location(out: &mut impl Write, interp: &Interpreter, depth: u64) {
+    let opcode = interp.current_opcode();
+    let _ = writeln!(
+        out,
+        "depth {depth}, address {}, pc {}, {}, energy {}, stack {}, memory {}",
+        interp.contract.address,
+        interp.program_counter(),
+        opcode_name(opcode),
+        interp.energy.remaining(),
+        interp.stack.len(),
+        interp.shared_memory.context_memory().len(),
+    );
+}
+
+impl<DB: Database, R: BufRead, W: Write> Inspector<DB> for Debugger<R, W> {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let depth = context.journaled_state.depth();
+        let frame_entered = std::mem::take(&mut self.frame_entered);
+        if self.should_stop(
+            interp.program_counter(),
+            interp.current_opcode(),
+            depth,
+            frame_entered,
+        ) {
+            self.prompt(interp, context);
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.frame_entered = true;
+        if !self.is_running() {
+            let _ = writeln!(
+                self.output,
+                "-> call {} at depth {}, input {}",
+                inputs.contract,
+                context.journaled_state.depth() + 1,
+                inputs.input
+            );
+        }
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        // frame without code returns before executing any instruction.
+        self.frame_entered = false;
+        if !self.is_running() {
+            let _ = writeln!(
+                self.output,
+                "<- return {:?} to depth {}, output {}",
+                outcome.result.result,
+                context.journaled_state.depth(),
+                outcome.result.output
+            );
+        }
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.frame_entered = true;
+        if !self.is_running() {
+            let _ = writeln!(
+                self.output,
+                "-> create by {} at depth {}",
+                inputs.caller,
+                context.journaled_state.depth() + 1
+            );
+        }
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.frame_entered = false;
+        if !self.is_running() {
+            let _ = writeln!(
+                self.output,
+                "<- created {:?} with {:?} to depth {}",
+                outcome.address,
+                outcome.result.result,
+                context.journaled_state.depth()
+            );
+        }
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        db::InMemoryDB,
+        inspector_handle_register,
+        primitives::{AccountInfo, Address, Bytecode, Bytes, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!("".parse(), Ok(Command::Step));
+        assert_eq!(
+            "b op sstore".parse(),
+            Ok(Command::Break(Breakpoint::Opcode(0x55)))
+        );
+        assert_eq!(
+            "break pc 0x0a".parse(),
+            Ok(Command::Break(Breakpoint::Pc(10)))
+        );
+        assert_eq!("d 1".parse(), Ok(Command::Delete(1)));
+        assert!("jump".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn stops_at_breakpoint() {
+        // PUSH1 1 PUSH1 0 SSTORE STOP
+        let code = Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55, 0x00]);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            Address::ZERO,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.clone())),
+                code_hash: revm::primitives::sha3(&code),
+                ..Default::default()
+            },
+        );
+
+        let input: &[u8] = b"b op SSTORE\nc\nstack\nq\n";
+        let mut output = Vec::new();
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(Debugger::new(input, &mut output))
+            .append_handler_register(inspector_handle_register)
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(Address::ZERO))
+            .build();
+        evm.transact().unwrap();
+        drop(evm);
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("breakpoint 0: op SSTORE"));
+        assert!(output.contains("pc 4, SSTORE"));
+        assert!(output.contains("   0: 0x0\n   1: 0x1\n"));
+    }
+
+    #[test]
+    fn depth_breakpoint_stops_on_frame_entry() {
+        // PUSH1 1 PUSH1 0 SSTORE STOP
+        let code = Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55, 0x00]);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            Address::ZERO,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.clone())),
+                code_hash: revm::primitives::sha3(&code),
+                ..Default::default()
+            },
+        );
+
+        // continue from the first instruction, frame is not entered again.
+        let input: &[u8] = b"c\n";
+        let mut output = Vec::new();
+        let mut debugger = Debugger::new(input, &mut output);
+        debugger.add_breakpoint(Breakpoint::Depth(1));
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(debugger)
+            .append_handler_register(inspector_handle_register)
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(Address::ZERO))
+            .build();
+        evm.transact().unwrap();
+        drop(evm);
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("(debug) ").count(), 1);
+    }
+}
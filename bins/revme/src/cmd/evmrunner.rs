@@ -1,8 +1,20 @@
+use super::{
+    statetest::models::SpecName,
+    t8n::models::{parse_alloc, LogJson},
+};
 use revm::{
-    db::BenchmarkDB,
-    primitives::{Address, Bytecode, TransactTo},
-    Evm,
+    db::{parse_address, Genesis, InMemoryDB},
+    inspector_handle_register,
+    inspectors::TracerEip3155,
+    interpreter::CreateScheme,
+    primitives::{
+        AccountInfo, Address, Bytecode, Bytes, Env, ExecutionResult, Output, ResultAndState,
+        TransactTo, U256,
+    },
+    DatabaseCommit, Evm,
 };
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
 use std::io::Error as IoError;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -21,6 +33,10 @@ pub enum Errors {
     InvalidInput,
     #[error("EVM Error")]
     EVMError,
+    #[error("Contract creation failed: {0:?}")]
+    CreateFailed(ExecutionResult),
+    #[error("Invalid prestate: {0}")]
+    InvalidPrestate(serde_json::Error),
     #[error(transparent)]
     Io(IoError),
 }
@@ -51,10 +67,50 @@ pub struct Cmd {
     /// Print the state.
     #[structopt(long)]
     state: bool,
+    /// Path to JSON file with the accounts of the state before execution.
+    #[structopt(long)]
+    prestate: Option<PathBuf>,
+    /// Run the bytecode as initcode and call the deployed contract with the input.
+    #[structopt(long)]
+    create: bool,
+    /// Address the bytecode is deployed at, ignored in create mode.
+    #[structopt(long, default_value = "0x0000000000000000000000000000000000000000", parse(try_from_str = parse_address))]
+    to: Address,
+    /// Plain or ICAN address of the caller.
+    #[structopt(long, default_value = "0x0000000000000000000000000000000000000001", parse(try_from_str = parse_address))]
+    caller: Address,
+    /// Value transferred by the transaction. In create mode it is transferred to the deployed
+    /// contract on creation and the call that follows transfers no value.
+    #[structopt(long, default_value = "0")]
+    value: U256,
+    /// Energy limit of the transaction.
+    #[structopt(long)]
+    energy: Option<u64>,
+    #[structopt(long, default_value = "0")]
+    energy_price: U256,
+    #[structopt(long, default_value = "1")]
+    network_id: u64,
+    /// Spec the bytecode is executed with.
+    #[structopt(long, default_value = "Cancun")]
+    spec: SpecName,
+    #[structopt(long, default_value = "0")]
+    block_number: U256,
+    #[structopt(long, default_value = "1")]
+    timestamp: U256,
+    #[structopt(long, default_value = "0x0000000000000000000000000000000000000000", parse(try_from_str = parse_address))]
+    coinbase: Address,
+    #[structopt(long, default_value = "0")]
+    basefee: U256,
+    /// Print the EIP-3155 trace to stderr.
+    #[structopt(long)]
+    trace: bool,
+    /// Print the result as JSON with energy used, output, logs and state diff.
+    #[structopt(long)]
+    json: bool,
 }
 
 impl Cmd {
-    /// Run statetest command.
+    /// Run evm runner command.
     pub fn run(&self) -> Result<(), Errors> {
         let bytecode_str: Cow<'_, str> = if let Some(path) = &self.path {
             // check if path exists.
@@ -66,40 +122,347 @@ impl Cmd {
             self.bytecode.as_str().into()
         };
 
-        let bytecode = hex::decode(bytecode_str.trim()).map_err(|_| Errors::InvalidBytecode)?;
-        let input = hex::decode(self.input.trim())
+        let bytecode: Bytes = hex::decode(bytecode_str.trim())
+            .map_err(|_| Errors::InvalidBytecode)?
+            .into();
+        let input: Bytes = hex::decode(self.input.trim())
             .map_err(|_| Errors::InvalidInput)?
             .into();
-        // BenchmarkDB is dummy state that implements Database trait.
-        // the bytecode is deployed at zero address.
-        let mut evm = Evm::builder()
-            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(
-                bytecode.into(),
-            )))
-            .modify_tx_env(|tx| {
-                // execution globals block hash/energy_limit/coinbase/timestamp..
-                tx.caller = "0x0000000000000000000000000000000000000001"
-                    .parse()
-                    .unwrap();
-                tx.transact_to = TransactTo::Call(Address::ZERO);
-                tx.data = input;
-            })
-            .build();
 
         if self.bench {
+            let mut db = self.db(&bytecode)?;
+            let mut evm = Evm::builder()
+                .with_db(&mut db)
+                .modify_env(|e| **e = self.env(input))
+                .with_spec_id(self.spec.to_spec_id())
+                .build();
             // Microbenchmark
             let bench_options = microbench::Options::default().time(Duration::from_secs(3));
 
             microbench::bench(&bench_options, "Run bytecode", || {
                 let _ = evm.transact().unwrap();
             });
+            return Ok(());
+        }
+
+        let execution = self.execute(bytecode, input)?;
+        if self.json {
+            let result = json_result(
+                &execution.out.result,
+                execution.created,
+                execution.state_diff,
+            );
+            println!("{}", serde_json::to_string_pretty(&result).unwrap());
         } else {
-            let out = evm.transact().map_err(|_| Errors::EVMError)?;
-            println!("Result: {:#?}", out.result);
+            if let Some(created) = execution.created {
+                println!("Created: {created}");
+            }
+            println!("Result: {:#?}", execution.out.result);
             if self.state {
-                println!("State: {:#?}", out.state);
+                println!("State: {:#?}", execution.out.state);
             }
         }
         Ok(())
     }
+
+    /// Executes the bytecode on top of the prestate.
+    fn execute(&self, bytecode: Bytes, input: Bytes) -> Result<Execution, Errors> {
+        let mut db = self.db(&bytecode)?;
+        let pre = db.clone();
+        let env = self.env(input);
+        let initcode = self.create.then_some(bytecode);
+        let (created, out) = if self.trace {
+            let evm = Evm::builder()
+                .with_db(&mut db)
+                .modify_env(|e| **e = env)
+                .with_spec_id(self.spec.to_spec_id())
+                .with_external_context(TracerEip3155::new(Box::new(std::io::stderr())))
+                .append_handler_register(inspector_handle_register)
+                .build();
+            transact(evm, initcode)?
+        } else {
+            let evm = Evm::builder()
+                .with_db(&mut db)
+                .modify_env(|e| **e = env)
+                .with_spec_id(self.spec.to_spec_id())
+                .build();
+            transact(evm, initcode)?
+        };
+        // diff covers the deployment that is already committed in create mode.
+        db.commit(out.state.clone());
+        Ok(Execution {
+            created,
+            out,
+            state_diff: state_diff(&pre, &db),
+        })
+    }
+
+    /// Prestate with the bytecode deployed at the called address if not in create mode.
+    fn db(&self, bytecode: &Bytes) -> Result<InMemoryDB, Errors> {
+        let mut db = self.prestate()?;
+        if !self.create && !bytecode.is_empty() {
+            let mut info = account_info(&db, self.to);
+            let code = Bytecode::new_raw(bytecode.clone());
+            info.code_hash = code.hash_slow();
+            info.code = Some(code);
+            db.insert_account_info(self.to, info);
+        }
+        Ok(db)
+    }
+
+    fn prestate(&self) -> Result<InMemoryDB, Errors> {
+        let Some(path) = &self.prestate else {
+            return Ok(InMemoryDB::default());
+        };
+        let alloc = parse_alloc(&fs::read_to_string(path)?).map_err(Errors::InvalidPrestate)?;
+        Ok(Genesis {
+            alloc,
+            ..Default::default()
+        }
+        .in_memory_db())
+    }
+
+    fn env(&self, input: Bytes) -> Env {
+        let mut env = Env::default();
+        env.cfg.network_id = self.network_id;
+        env.block.number = self.block_number;
+        env.block.timestamp = self.timestamp;
+        env.block.coinbase = self.coinbase;
+        env.block.basefee = self.basefee;
+        env.tx.caller = self.caller;
+        env.tx.transact_to = TransactTo::Call(self.to);
+        env.tx.value = self.value;
+        env.tx.data = input;
+        env.tx.energy_price = self.energy_price;
+        if let Some(energy) = self.energy {
+            env.tx.energy_limit = energy;
+        }
+        env
+    }
+}
+
+/// Outcome of the bytecode execution.
+struct Execution {
+    /// Address of the contract deployed in create mode.
+    created: Option<Address>,
+    /// Outcome of the call, not committed.
+    out: ResultAndState,
+    /// Changes of the deployment and the call against the prestate.
+    state_diff: Value,
+}
+
+/// Calls the contract, deploying it first from the initcode if it is set.
+///
+/// Value of the transaction is transferred by the deployment, the call that follows it
+/// transfers no value. Returns the address of the deployed contract and the outcome of
+/// the call, call is not committed so the state contains the values before it.
+fn transact<EXT>(
+    mut evm: Evm<'_, EXT, &mut InMemoryDB>,
+    initcode: Option<Bytes>,
+) -> Result<(Option<Address>, ResultAndState), Errors> {
+    let mut created = None;
+    if let Some(initcode) = initcode {
+        let call = std::mem::replace(&mut evm.tx_mut().data, initcode);
+        evm.tx_mut().transact_to = TransactTo::Create(CreateScheme::Create);
+        let result = evm.transact_commit().map_err(|_| Errors::EVMError)?;
+        let ExecutionResult::Success {
+            output: Output::Create(_, Some(address)),
+            ..
+        } = result
+        else {
+            return Err(Errors::CreateFailed(result));
+        };
+        created = Some(address);
+        let tx = evm.tx_mut();
+        tx.data = call;
+        tx.transact_to = TransactTo::Call(address);
+        tx.value = U256::ZERO;
+    }
+    let out = evm.transact().map_err(|_| Errors::EVMError)?;
+    Ok((created, out))
+}
+
+fn json_result(result: &ExecutionResult, created: Option<Address>, state_diff: Value) -> Value {
+    let output = result.output().cloned().unwrap_or_default();
+    let mut json = json!({
+        "success": result.is_success(),
+        "energyUsed": result.energy_used(),
+        "output": output,
+        "logs": result.logs().iter().map(LogJson::from).collect::<Vec<_>>(),
+        "stateDiff": state_diff,
+    });
+    if !result.is_success() {
+        json["error"] = format!("{result:?}").into();
+    }
+    if let Some(created) = created {
+        json["createdAddress"] = json!(created);
+    }
+    json
+}
+
+fn account_info(db: &InMemoryDB, address: Address) -> AccountInfo {
+    db.accounts
+        .get(&address)
+        .map(|account| account.info.clone())
+        .unwrap_or_default()
+}
+
+/// Changes of the accounts between the states as `{"from": .., "to": ..}` pairs, only
+/// changed fields are set.
+fn state_diff(pre: &InMemoryDB, post: &InMemoryDB) -> Value {
+    let mut accounts = BTreeMap::new();
+    for (address, account) in &post.accounts {
+        let pre_account = pre.accounts.get(address);
+        let pre_info = account_info(pre, *address);
+        let mut diff = Map::new();
+        if pre_info.balance != account.info.balance {
+            diff.insert(
+                "balance".into(),
+                json!({ "from": pre_info.balance, "to": account.info.balance }),
+            );
+        }
+        if pre_info.nonce != account.info.nonce {
+            diff.insert(
+                "nonce".into(),
+                json!({ "from": pre_info.nonce, "to": account.info.nonce }),
+            );
+        }
+        if pre_info.code_hash != account.info.code_hash {
+            diff.insert(
+                "codeHash".into(),
+                json!({ "from": pre_info.code_hash, "to": account.info.code_hash }),
+            );
+        }
+        let pre_storage = pre_account.map(|account| &account.storage);
+        let keys: BTreeSet<U256> = account
+            .storage
+            .keys()
+            .chain(pre_storage.into_iter().flat_map(|storage| storage.keys()))
+            .copied()
+            .collect();
+        let storage: BTreeMap<U256, Value> = keys
+            .into_iter()
+            .filter_map(|key| {
+                let from = pre_storage
+                    .and_then(|storage| storage.get(&key))
+                    .copied()
+                    .unwrap_or_default();
+                let to = account.storage.get(&key).copied().unwrap_or_default();
+                (from != to).then(|| (key, json!({ "from": from, "to": to })))
+            })
+            .collect();
+        if !storage.is_empty() {
+            diff.insert("storage".into(), json!(storage));
+        }
+        if !diff.is_empty() {
+            accounts.insert(*address, Value::Object(diff));
+        }
+    }
+    json!(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::hex;
+
+    /// Constructor stores `1` at slot `0` and deploys code that returns `42`.
+    const INITCODE: &str = "6001600055600a6011600039600a6000f3602a60005260206000f3";
+
+    fn key<T: serde::Serialize>(value: T) -> String {
+        serde_json::to_value(value)
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// Executes the bytecode with the prestate written to a temporary file.
+    fn execute_with_prestate(
+        name: &str,
+        prestate: &str,
+        args: &[&str],
+        bytecode: &str,
+    ) -> (Cmd, Result<Execution, Errors>) {
+        let path = std::env::temp_dir().join(format!("revme-{name}-{}.json", std::process::id()));
+        fs::write(&path, prestate).unwrap();
+        let cmd = Cmd::from_iter(
+            ["evm", "--prestate", path.to_str().unwrap()]
+                .iter()
+                .chain(args),
+        );
+        let execution = cmd.execute(hex::decode(bytecode).unwrap().into(), Bytes::new());
+        fs::remove_file(&path).unwrap();
+        (cmd, execution)
+    }
+
+    #[test]
+    fn create_and_call() {
+        let prestate = r#"{ "0x0000000000000000000000000000000000000001": { "balance": "0x10" } }"#;
+        let (_, execution) =
+            execute_with_prestate("create", prestate, &["--create", "--value", "7"], INITCODE);
+        let execution = execution.unwrap();
+        let created = execution.created.unwrap();
+        assert_eq!(
+            execution.out.result.output().unwrap(),
+            &Bytes::from(U256::from(42).to_be_bytes::<32>().to_vec())
+        );
+
+        let json = json_result(
+            &execution.out.result,
+            execution.created,
+            execution.state_diff,
+        );
+        assert_eq!(json["success"], json!(true));
+        assert_eq!(json["createdAddress"], json!(created));
+        // deployment is part of the diff.
+        let diff = &json["stateDiff"][key(created)];
+        assert_eq!(
+            diff["storage"][key(U256::ZERO)],
+            json!({ "from": U256::ZERO, "to": U256::from(1) })
+        );
+        assert_eq!(
+            diff["balance"],
+            json!({ "from": U256::ZERO, "to": U256::from(7) })
+        );
+        assert!(diff.get("codeHash").is_some());
+        let caller = &json["stateDiff"][key(Address::with_last_byte(1))];
+        assert_eq!(
+            caller["balance"],
+            json!({ "from": U256::from(16), "to": U256::from(9) })
+        );
+    }
+
+    #[test]
+    fn prestate_is_merged_with_bytecode() {
+        let prestate = r#"{ "0x0000000000000000000000000000000000000000": { "balance": "0x05" } }"#;
+        // returns the balance of the called contract.
+        let (cmd, execution) =
+            execute_with_prestate("prestate", prestate, &[], "4760005260206000f3");
+        let execution = execution.unwrap();
+        assert_eq!(execution.created, None);
+        assert_eq!(
+            execution.out.result.output().unwrap(),
+            &Bytes::from(U256::from(5).to_be_bytes::<32>().to_vec())
+        );
+        // call changes nothing but the caller nonce.
+        let diff = execution.state_diff.as_object().unwrap();
+        assert_eq!(diff.len(), 1);
+        assert!(diff[&key(cmd.caller)].get("nonce").is_some());
+    }
+
+    #[test]
+    fn replaced_code_has_its_hash() {
+        let prestate = r#"{ "0x0000000000000000000000000000000000000000": { "balance": "0x05", "code": "0x00" } }"#;
+        // returns the code hash of the called contract.
+        let bytecode = "303f60005260206000f3";
+        let (cmd, execution) = execute_with_prestate("code-hash", prestate, &[], bytecode);
+        let code = Bytecode::new_raw(hex::decode(bytecode).unwrap().into());
+        assert_eq!(
+            execution.unwrap().out.result.output().unwrap(),
+            &Bytes::copy_from_slice(code.hash_slow().as_slice())
+        );
+        let db = cmd.db(&code.original_bytes()).unwrap();
+        assert_eq!(account_info(&db, cmd.to).code_hash, code.hash_slow());
+    }
 }
@@ -0,0 +1,336 @@
+use super::statetest::{
+    find_all_json_tests,
+    models::{
+        AccountExpectation, Expectation, FillerSuite, FillerUnit, SpecName, Test, TestSuite,
+        TestUnit, TxPartIndices,
+    },
+    pre_state, set_tx_indexes, spec_state, unit_env, TestError, TestFormat,
+};
+use revm::{
+    db::EmptyDB,
+    primitives::{Address, Bytes, EVMError, Env, ExecutionResult, U256},
+    Evm, State,
+};
+use revm_chain::{
+    trie::{log_rlp_hash, state_merkle_trie_root},
+    SignedTransaction,
+};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::io::Error as IoError;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+use structopt::StructOpt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FillError {
+    #[error(
+        "Test {name} ({spec:?}, d{data} g{energy} v{value}) does not match expectation: {message}"
+    )]
+    ExpectationMismatch {
+        name: String,
+        spec: SpecName,
+        data: usize,
+        energy: usize,
+        value: usize,
+        message: String,
+    },
+    #[error("Test {0} secret key is not an Ed448 private key")]
+    InvalidSecretKey(String),
+    #[error(transparent)]
+    Test(#[from] TestError),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+/// Statetest filler command
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// Path to folder or file containing the fillers.
+    ///
+    /// Folders will be searched recursively for files with the extension `.json`.
+    #[structopt(required = true)]
+    path: Vec<PathBuf>,
+    /// Folder the filled tests are written to, using the file name of the filler.
+    #[structopt(short = "o", long, default_value = "filled")]
+    output: PathBuf,
+}
+
+impl Cmd {
+    /// Run fill command.
+    pub fn run(&self) -> Result<(), FillError> {
+        std::fs::create_dir_all(&self.output)?;
+        for path in &self.path {
+            for filler_path in find_all_json_tests(path) {
+                let filled = fill_file(&filler_path)?;
+                let output = self.output.join(filler_path.file_name().unwrap());
+                std::fs::write(&output, serde_json::to_string_pretty(&filled)?)?;
+                println!("Filled {} into {}", filler_path.display(), output.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fill all tests of the filler file.
+pub fn fill_file(path: &Path) -> Result<TestSuite, FillError> {
+    let s = std::fs::read_to_string(path)?;
+    let suite: FillerSuite = serde_json::from_str(&s)?;
+    let mut filled = BTreeMap::new();
+    for (name, unit) in suite.0 {
+        let unit = fill_unit(&name, unit)?;
+        filled.insert(name, unit);
+    }
+    Ok(TestSuite(filled))
+}
+
+/// Executes every combination of the transaction parts for all specs of the expectations
+/// and creates the filled test.
+pub fn fill_unit(name: &str, filler: FillerUnit) -> Result<TestUnit, FillError> {
+    let FillerUnit {
+        info,
+        env,
+        pre,
+        transaction,
+        expect,
+    } = filler;
+    let mut unit = TestUnit {
+        info,
+        env,
+        pre,
+        post: BTreeMap::new(),
+        transaction,
+        out: None,
+    };
+    let cache_state = pre_state(&unit.pre);
+    let mut env = unit_env(name, &unit, TestFormat::Core)?;
+    // filled tests do not need to derive the sender.
+    unit.transaction.sender = Some(env.tx.caller);
+
+    let mut specs: Vec<SpecName> = expect
+        .iter()
+        .flat_map(|expectation| expectation.network.iter().copied())
+        .collect();
+    specs.sort();
+    specs.dedup();
+
+    for spec in specs {
+        let spec_id = spec.to_spec_id();
+        let mut tests = Vec::new();
+        for data in 0..unit.transaction.data.len() {
+            for energy in 0..unit.transaction.energy_limit.len() {
+                for value in 0..unit.transaction.value.len() {
+                    let expectation = expect.iter().find(|expectation| {
+                        expectation.network.contains(&spec)
+                            && expectation.indexes.matches(data, energy, value)
+                    });
+                    let indexes = TxPartIndices {
+                        data,
+                        energy,
+                        value,
+                    };
+                    set_tx_indexes(&mut env, &unit.transaction, &indexes);
+
+                    let mut state = spec_state(&cache_state, spec_id);
+                    let mut evm = Evm::builder()
+                        .with_db(&mut state)
+                        .modify_env(|e| *e = env.clone())
+                        .with_spec_id(spec_id)
+                        .build();
+                    let result = evm.transact_commit();
+                    drop(evm);
+
+                    if let Some(expectation) = expectation {
+                        check_expectation(expectation, &result, &state).map_err(|message| {
+                            FillError::ExpectationMismatch {
+                                name: name.to_string(),
+                                spec,
+                                data,
+                                energy,
+                                value,
+                                message,
+                            }
+                        })?;
+                    }
+
+                    let txbytes =
+                        txbytes(&env, &unit.transaction.secret_key, &unit.transaction.nonce)
+                            .ok_or_else(|| FillError::InvalidSecretKey(name.to_string()))?;
+                    let logs = result.as_ref().map(|r| r.logs()).unwrap_or_default();
+                    tests.push(Test {
+                        expect_exception: result.as_ref().err().map(|e| e.to_string()),
+                        indexes,
+                        hash: state_merkle_trie_root(state.cache.trie_account()),
+                        post_state: Default::default(),
+                        logs: log_rlp_hash(logs),
+                        txbytes: Some(txbytes),
+                    });
+                }
+            }
+        }
+        unit.post.insert(spec, tests);
+    }
+    Ok(unit)
+}
+
+/// Checks the result of the execution against the expectation.
+fn check_expectation(
+    expectation: &Expectation,
+    result: &Result<ExecutionResult, EVMError<Infallible>>,
+    state: &State<EmptyDB>,
+) -> Result<(), String> {
+    match (&expectation.expect_exception, result) {
+        (None, Err(error)) => return Err(format!("unexpected exception {error}")),
+        (Some(exception), Ok(_)) => return Err(format!("expected exception {exception}")),
+        _ => {}
+    }
+    for (address, expected) in &expectation.result {
+        check_account(*address, expected, state)?;
+    }
+    Ok(())
+}
+
+fn check_account(
+    address: Address,
+    expected: &AccountExpectation,
+    state: &State<EmptyDB>,
+) -> Result<(), String> {
+    let account = state
+        .cache
+        .accounts
+        .get(&address)
+        .and_then(|account| account.account.as_ref());
+    let Some(account) = account else {
+        if expected.should_not_exist {
+            return Ok(());
+        }
+        return Err(format!("account {address} does not exist"));
+    };
+    if expected.should_not_exist {
+        return Err(format!("account {address} exists"));
+    }
+    if let Some(balance) = expected.balance {
+        if account.info.balance != balance {
+            return Err(format!(
+                "account {address} balance is {}, expected {balance}",
+                account.info.balance
+            ));
+        }
+    }
+    if let Some(nonce) = expected.nonce {
+        if U256::from(account.info.nonce) != nonce {
+            return Err(format!(
+                "account {address} nonce is {}, expected {nonce}",
+                account.info.nonce
+            ));
+        }
+    }
+    if let Some(code) = &expected.code {
+        let got = account
+            .info
+            .code
+            .as_ref()
+            .map(|code| code.original_bytes())
+            .unwrap_or_default();
+        if &got != code {
+            return Err(format!("account {address} code is {got}, expected {code}"));
+        }
+    }
+    for (slot, value) in &expected.storage {
+        let got = account.storage.get(slot).copied().unwrap_or_default();
+        if got != *value {
+            return Err(format!(
+                "account {address} storage slot {slot} is {got}, expected {value}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Encodes the transaction of the environment, signed with the Ed448 secret key.
+fn txbytes(env: &Env, secret_key: &[u8], nonce: &U256) -> Option<Bytes> {
+    let tx = SignedTransaction::unsigned(&env.tx, nonce.saturating_to(), env.cfg.network_id)
+        .sign(secret_key)?;
+    Some(alloy_rlp::encode(&tx).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::statetest::{
+        execute_test_suite, report::TestStatus, utils::recover_address, RunOptions,
+    };
+    use alloy_rlp::Decodable;
+    use revm::primitives::hex;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[test]
+    fn filled_test_passes() {
+        let key = hex!("445a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d85a915e4d060149eb4365960e6a7a45f334393093061110068");
+        let sender = recover_address(&key).unwrap();
+        let filler = format!(
+            r#"{{
+                "env": {{
+                    "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                    "currentDifficulty": "0x020000",
+                    "currentEnergyLimit": "0x7fffffffffffffff",
+                    "currentNumber": "0x01",
+                    "currentTimestamp": "0x03e8"
+                }},
+                "pre": {{
+                    "0x{sender}": {{ "balance": "0x0de0b6b3a7640000", "code": "0x", "nonce": "0x00", "storage": {{}} }}
+                }},
+                "transaction": {{
+                    "data": ["0x", "0x01"],
+                    "energyLimit": ["0x061a80"],
+                    "energyPrice": "0x0a",
+                    "nonce": "0x00",
+                    "secretKey": "0x{key}",
+                    "to": "0x00000000000000000000000000000000000000aa",
+                    "value": ["0x00", "0x01"]
+                }},
+                "expect": [{{
+                    "indexes": {{ "value": [1] }},
+                    "network": ["Shanghai"],
+                    "result": {{ "0x00000000000000000000000000000000000000aa": {{ "balance": "0x01" }} }}
+                }}]
+            }}"#,
+            sender = hex::encode(sender),
+            key = hex::encode(key),
+        );
+        let unit = fill_unit("fillTest", serde_json::from_str(&filler).unwrap()).unwrap();
+
+        let tests = &unit.post[&SpecName::Shanghai];
+        assert_eq!(tests.len(), 4);
+        for test in tests {
+            let txbytes = test.txbytes.as_ref().unwrap();
+            let tx = SignedTransaction::decode(&mut txbytes.as_ref()).unwrap();
+            assert_eq!(tx.recover_sender(), Ok(sender));
+            assert_eq!(tx.data, unit.transaction.data[test.indexes.data]);
+            assert_eq!(tx.value, unit.transaction.value[test.indexes.value]);
+            assert_eq!(tx.receiver(), Ok(unit.transaction.to));
+        }
+
+        let path = std::env::temp_dir().join(format!("revme-fill-{}.json", std::process::id()));
+        let suite = TestSuite(BTreeMap::from([("fillTest".to_string(), unit)]));
+        std::fs::write(&path, serde_json::to_string(&suite).unwrap()).unwrap();
+        let reports = Mutex::new(Vec::new());
+        let result = execute_test_suite(
+            &path,
+            &Arc::new(Mutex::new(Duration::ZERO)),
+            &RunOptions::default(),
+            &reports,
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        result.unwrap();
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 4);
+        assert!(reports
+            .iter()
+            .all(|report| report.status == TestStatus::Passed));
+    }
+}
@@ -1,14 +1,26 @@
-pub mod merkle_trie;
+pub mod diff;
+pub mod filter;
 pub mod models;
+pub mod report;
 mod runner;
 pub mod utils;
 
 pub use runner::TestError as Error;
+pub use runner::{
+    execute_test_suite, find_all_json_tests, pre_state, set_tx_indexes, spec_state, unit_env,
+    RunOptions, TestError, TestErrorKind, TestFormat,
+};
 
-use runner::{find_all_json_tests, run, TestError};
-use std::path::PathBuf;
+use filter::TestFilter;
+use models::SpecName;
+use regex::Regex;
+use report::{json_report, junit_report, Summary, TestReport};
+use runner::run;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::vec::Vec;
 use structopt::StructOpt;
+use utils::{recover_ed448_address, recover_secp256k1_address, AddressScheme, KeyToAddress};
 
 /// Statetest command
 #[derive(StructOpt, Debug)]
@@ -30,16 +42,129 @@ pub struct Cmd {
     /// It will stop second run of evm on failure.
     #[structopt(short = "o", long)]
     json_outcome: bool,
+    /// Print the EIP-3155 trace of the failing test index.
+    #[structopt(long)]
+    trace_failing: bool,
+    /// Load upstream Ethereum state tests that use `gas` field names and secp256k1 keys.
+    #[structopt(long)]
+    ethereum_compat: bool,
+    /// Primary scheme used to derive the sender from the secret key in ethereum-compat mode,
+    /// secp256k1 is used if it fails. Either `ed448` or `secp256k1`.
+    #[structopt(long, default_value = "ed448", parse(try_from_str = parse_key_scheme))]
+    key_scheme: KeyToAddress,
+    /// Only run tests whose name matches the regular expression.
+    #[structopt(long, parse(try_from_str = Regex::new))]
+    filter: Option<Regex>,
+    /// Only run tests of the spec, can be specified multiple times.
+    #[structopt(long = "spec", number_of_values = 1)]
+    specs: Vec<SpecName>,
+    /// Only run tests at the post index of the spec, can be specified multiple times.
+    #[structopt(long = "index", number_of_values = 1)]
+    indexes: Vec<usize>,
+    /// File with names of the tests or test files to skip, one per line.
+    /// Lines starting with `#` are ignored.
+    #[structopt(long)]
+    skip_list: Option<PathBuf>,
+    /// Write JUnit XML report with the outcome and time of every test.
+    #[structopt(long)]
+    junit: Option<PathBuf>,
+    /// Write JSON summary with the outcome and time of every test.
+    #[structopt(long)]
+    json_report: Option<PathBuf>,
+}
+
+fn parse_key_scheme(s: &str) -> Result<KeyToAddress, String> {
+    match s {
+        "ed448" => Ok(recover_ed448_address),
+        "secp256k1" => Ok(recover_secp256k1_address),
+        _ => Err(format!(
+            "unknown key scheme {s}, expected ed448 or secp256k1"
+        )),
+    }
 }
 
 impl Cmd {
     /// Run statetest command.
     pub fn run(&self) -> Result<(), TestError> {
+        let options = RunOptions {
+            single_thread: self.single_thread,
+            trace: self.json,
+            print_outcome: self.json_outcome,
+            trace_failing: self.trace_failing,
+            format: self.format(),
+            filter: self.filter()?,
+            // reports cover all tests, not only the ones before the first failure.
+            keep_going: self.junit.is_some() || self.json_report.is_some(),
+        };
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let mut result = Ok(());
         for path in &self.path {
             println!("\nRunning tests in {}...", path.display());
             let test_files = find_all_json_tests(path);
-            run(test_files, self.single_thread, self.json, self.json_outcome)?
+            let path_result = run(test_files, options.clone(), reports.clone());
+            if result.is_ok() {
+                result = path_result;
+            }
+            if result.is_err() && !options.keep_going {
+                break;
+            }
+        }
+        // reports are written even if tests failed.
+        self.write_reports(&reports.lock().unwrap())?;
+        result
+    }
+
+    fn filter(&self) -> Result<TestFilter, TestError> {
+        let mut filter = TestFilter {
+            name: self.filter.clone(),
+            specs: self.specs.clone(),
+            indexes: self.indexes.clone(),
+            ..Default::default()
+        };
+        if let Some(path) = &self.skip_list {
+            filter.load_skip_list(path).map_err(|e| io_error(path, e))?;
+        }
+        Ok(filter)
+    }
+
+    fn write_reports(&self, reports: &[TestReport]) -> Result<(), TestError> {
+        if self.junit.is_none() && self.json_report.is_none() {
+            return Ok(());
+        }
+        let summary = Summary::new(reports);
+        println!(
+            "Reported {} tests: {} passed, {} failed, {} skipped",
+            summary.total(),
+            summary.passed,
+            summary.failed,
+            summary.skipped
+        );
+        if let Some(path) = &self.junit {
+            std::fs::write(path, junit_report(reports)).map_err(|e| io_error(path, e))?;
+        }
+        if let Some(path) = &self.json_report {
+            let json =
+                serde_json::to_string_pretty(&json_report(reports)).map_err(|e| TestError {
+                    name: path.display().to_string(),
+                    kind: e.into(),
+                })?;
+            std::fs::write(path, json).map_err(|e| io_error(path, e))?;
         }
         Ok(())
     }
+
+    fn format(&self) -> TestFormat {
+        if self.ethereum_compat {
+            TestFormat::EthereumCompat(AddressScheme::new(self.key_scheme))
+        } else {
+            TestFormat::Core
+        }
+    }
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> TestError {
+    TestError {
+        name: path.display().to_string(),
+        kind: TestErrorKind::Io(error),
+    }
 }
@@ -0,0 +1,183 @@
+use super::models::AccountInfo;
+use revm::{
+    db::PlainAccount,
+    primitives::{sha3, Address, HashMap, B256, U256},
+    CacheState,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::vec::Vec;
+
+/// Differences between the expected post state and the state after execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    pub accounts: BTreeMap<Address, AccountDiff>,
+}
+
+/// Difference of a single account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountDiff {
+    /// Account is expected but it does not exist.
+    Missing,
+    /// Account exists but it is not expected.
+    Unexpected,
+    /// Account exists with different values, only fields that differ are set.
+    Changed {
+        balance: Option<(U256, U256)>,
+        nonce: Option<(u64, u64)>,
+        code_hash: Option<(B256, B256)>,
+        /// Storage slots with expected and actual values.
+        storage: Vec<(U256, U256, U256)>,
+    },
+}
+
+impl StateDiff {
+    /// Compares the expected post state with the accounts of the cache.
+    ///
+    /// Storage slots that are not present are zero.
+    pub fn new(expected: &HashMap<Address, AccountInfo>, cache: &CacheState) -> Self {
+        let actual: HashMap<Address, &PlainAccount> = cache.trie_account().into_iter().collect();
+        let addresses: BTreeSet<Address> = expected.keys().chain(actual.keys()).copied().collect();
+
+        let mut accounts = BTreeMap::new();
+        for address in addresses {
+            let diff = match (expected.get(&address), actual.get(&address)) {
+                (Some(_), None) => Some(AccountDiff::Missing),
+                (None, Some(account)) if !account.info.is_empty() => Some(AccountDiff::Unexpected),
+                (Some(expected), Some(account)) => account_diff(expected, account),
+                _ => None,
+            };
+            if let Some(diff) = diff {
+                accounts.insert(address, diff);
+            }
+        }
+        Self { accounts }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
+
+fn account_diff(expected: &AccountInfo, actual: &PlainAccount) -> Option<AccountDiff> {
+    let balance = differ(expected.balance, actual.info.balance);
+    let nonce = differ(expected.nonce, actual.info.nonce);
+    let code_hash = differ(sha3(&expected.code), actual.info.code_hash);
+
+    let slots: BTreeSet<U256> = expected
+        .storage
+        .keys()
+        .chain(actual.storage.keys())
+        .copied()
+        .collect();
+    let storage: Vec<_> = slots
+        .into_iter()
+        .filter_map(|slot| {
+            let expected = expected.storage.get(&slot).copied().unwrap_or_default();
+            let actual = actual.storage.get(&slot).copied().unwrap_or_default();
+            (expected != actual).then_some((slot, expected, actual))
+        })
+        .collect();
+
+    if balance.is_none() && nonce.is_none() && code_hash.is_none() && storage.is_empty() {
+        return None;
+    }
+    Some(AccountDiff::Changed {
+        balance,
+        nonce,
+        code_hash,
+        storage,
+    })
+}
+
+fn differ<T: PartialEq>(expected: T, actual: T) -> Option<(T, T)> {
+    (expected != actual).then_some((expected, actual))
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (address, diff) in &self.accounts {
+            match diff {
+                AccountDiff::Missing => writeln!(f, "  {address}: missing")?,
+                AccountDiff::Unexpected => writeln!(f, "  {address}: unexpected")?,
+                AccountDiff::Changed {
+                    balance,
+                    nonce,
+                    code_hash,
+                    storage,
+                } => {
+                    writeln!(f, "  {address}:")?;
+                    if let Some((expected, got)) = balance {
+                        writeln!(f, "    balance: expected {expected}, got {got}")?;
+                    }
+                    if let Some((expected, got)) = nonce {
+                        writeln!(f, "    nonce: expected {expected}, got {got}")?;
+                    }
+                    if let Some((expected, got)) = code_hash {
+                        writeln!(f, "    code hash: expected {expected}, got {got}")?;
+                    }
+                    for (slot, expected, got) in storage {
+                        writeln!(
+                            f,
+                            "    storage {slot:#x}: expected {expected:#x}, got {got:#x}"
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{Bytes, SHA3_EMPTY};
+
+    #[test]
+    fn diff_accounts() {
+        let changed = Address::with_last_byte(1);
+        let missing = Address::with_last_byte(2);
+        let unexpected = Address::with_last_byte(3);
+
+        let mut cache = CacheState::new(true);
+        cache.insert_account_with_storage(
+            changed,
+            revm::primitives::AccountInfo::new(U256::from(1), 1, SHA3_EMPTY, Default::default()),
+            [(U256::from(1), U256::from(1))].into_iter().collect(),
+        );
+        cache.insert_account(
+            unexpected,
+            revm::primitives::AccountInfo::from_balance(U256::from(1)),
+        );
+
+        let expected_account = |balance: u64| AccountInfo {
+            balance: U256::from(balance),
+            code: Bytes::new(),
+            nonce: 1,
+            storage: [(U256::from(2), U256::from(2))].into_iter().collect(),
+        };
+        let expected = [
+            (changed, expected_account(2)),
+            (missing, expected_account(1)),
+        ]
+        .into_iter()
+        .collect();
+
+        let diff = StateDiff::new(&expected, &cache);
+        assert_eq!(diff.accounts[&missing], AccountDiff::Missing);
+        assert_eq!(diff.accounts[&unexpected], AccountDiff::Unexpected);
+        assert_eq!(
+            diff.accounts[&changed],
+            AccountDiff::Changed {
+                balance: Some((U256::from(2), U256::from(1))),
+                nonce: None,
+                code_hash: None,
+                storage: vec![
+                    (U256::from(1), U256::ZERO, U256::from(1)),
+                    (U256::from(2), U256::from(2), U256::ZERO),
+                ],
+            }
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use super::models::SpecName;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+
+/// Selection of the tests to run, the default filter selects every test.
+#[derive(Clone, Debug, Default)]
+pub struct TestFilter {
+    /// Only run tests whose name matches.
+    pub name: Option<Regex>,
+    /// Only run tests of these specs, all specs if empty.
+    pub specs: Vec<SpecName>,
+    /// Only run tests at these post indexes, all indexes if empty.
+    pub indexes: Vec<usize>,
+    /// Names of the tests or test files that are skipped.
+    pub skip: HashSet<String>,
+}
+
+impl TestFilter {
+    /// Adds the names from the skip list file to the skipped tests.
+    ///
+    /// File contains one test or file name per line, empty lines and lines starting
+    /// with `#` are ignored.
+    pub fn load_skip_list(&mut self, path: &Path) -> std::io::Result<()> {
+        let list = std::fs::read_to_string(path)?;
+        self.skip.extend(parse_skip_list(&list));
+        Ok(())
+    }
+
+    /// Returns true if the test or the test file is on the skip list.
+    pub fn is_skipped(&self, name: &str) -> bool {
+        self.skip.contains(name)
+    }
+
+    pub fn matches_name(&self, name: &str) -> bool {
+        self.name
+            .as_ref()
+            .map_or(true, |regex| regex.is_match(name))
+    }
+
+    pub fn matches_spec(&self, spec: SpecName) -> bool {
+        self.specs.is_empty() || self.specs.contains(&spec)
+    }
+
+    pub fn matches_index(&self, index: usize) -> bool {
+        self.indexes.is_empty() || self.indexes.contains(&index)
+    }
+}
+
+fn parse_skip_list(list: &str) -> impl Iterator<Item = String> + '_ {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_list_ignores_comments() {
+        let list = "# slow tests\nloopExp.json\n\n  callcodeDynamicCode  \n";
+        let skip: Vec<_> = parse_skip_list(list).collect();
+        assert_eq!(skip, ["loopExp.json", "callcodeDynamicCode"]);
+    }
+
+    #[test]
+    fn filter_matches() {
+        let filter = TestFilter {
+            name: Some(Regex::new("^call").unwrap()),
+            specs: vec![SpecName::Cancun],
+            indexes: vec![1],
+            skip: HashSet::new(),
+        };
+        assert!(filter.matches_name("callcode"));
+        assert!(!filter.matches_name("create"));
+        assert!(filter.matches_spec(SpecName::Cancun));
+        assert!(!filter.matches_spec(SpecName::Shanghai));
+        assert!(filter.matches_index(1));
+        assert!(!filter.matches_index(0));
+        assert!(TestFilter::default().matches_index(0));
+    }
+}
@@ -1,5 +1,5 @@
 use revm::primitives::Address;
-use serde::{de, Deserialize};
+use serde::{de, Deserialize, Serializer};
 
 pub fn deserialize_str_as_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
@@ -26,3 +26,20 @@ where
         string.parse().map_err(de::Error::custom).map(Some)
     }
 }
+
+pub fn serialize_u64_as_str<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format!("0x{value:x}"))
+}
+
+pub fn serialize_maybe_empty<S>(value: &Option<Address>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(address) => serializer.serialize_str(&address.to_string()),
+        None => serializer.serialize_str(""),
+    }
+}
@@ -0,0 +1,295 @@
+//! Loading of upstream Ethereum state tests that use `gas` instead of `energy` names.
+
+use super::{
+    deserializer::deserialize_maybe_empty, AccessList, AccountInfo, Env, SpecName, Test, TestSuite,
+    TestUnit, TransactionParts, TxPartIndices,
+};
+use revm::primitives::{Address, Bytes, HashMap, B256, U256};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+impl TestSuite {
+    /// Deserializes suite of upstream Ethereum state tests.
+    ///
+    /// `gas` fields of the environment, transaction and post state indexes are mapped
+    /// onto their `energy` counterparts before the suite is deserialized. Fields the
+    /// runner does not know, like the fixture `config`, are ignored.
+    pub fn from_ethereum_json(s: &str) -> Result<Self, serde_json::Error> {
+        let mut suite: Value = serde_json::from_str(s)?;
+        alias_gas_fields(&mut suite);
+        let suite: BTreeMap<String, EthereumTestUnit> = serde_json::from_value(suite)?;
+        Ok(Self(
+            suite
+                .into_iter()
+                .map(|(name, unit)| (name, unit.into()))
+                .collect(),
+        ))
+    }
+}
+
+/// Upstream counterpart of [TestUnit].
+///
+/// Upstream fixtures carry fields that are not part of the Core format, like `config` of
+/// the unit or `state` of the post state, so the compat models accept unknown fields.
+#[derive(Deserialize)]
+struct EthereumTestUnit {
+    #[serde(default, rename = "_info")]
+    info: Option<Value>,
+    env: EthereumEnv,
+    pre: HashMap<Address, AccountInfo>,
+    post: BTreeMap<SpecName, Vec<EthereumTest>>,
+    transaction: EthereumTransactionParts,
+    #[serde(default)]
+    out: Option<Bytes>,
+}
+
+impl From<EthereumTestUnit> for TestUnit {
+    fn from(unit: EthereumTestUnit) -> Self {
+        Self {
+            info: unit.info,
+            env: unit.env.into(),
+            pre: unit.pre,
+            post: unit
+                .post
+                .into_iter()
+                .map(|(spec, tests)| (spec, tests.into_iter().map(Into::into).collect()))
+                .collect(),
+            transaction: unit.transaction.into(),
+            out: unit.out,
+        }
+    }
+}
+
+/// Upstream counterpart of [Test].
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthereumTest {
+    #[serde(default)]
+    expect_exception: Option<String>,
+    indexes: TxPartIndices,
+    hash: B256,
+    #[serde(default)]
+    post_state: HashMap<Address, AccountInfo>,
+    logs: B256,
+    #[serde(default)]
+    txbytes: Option<Bytes>,
+}
+
+impl From<EthereumTest> for Test {
+    fn from(test: EthereumTest) -> Self {
+        Self {
+            expect_exception: test.expect_exception,
+            indexes: test.indexes,
+            hash: test.hash,
+            post_state: test.post_state,
+            logs: test.logs,
+            txbytes: test.txbytes,
+        }
+    }
+}
+
+/// Upstream counterpart of [Env].
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthereumEnv {
+    current_coinbase: Address,
+    current_difficulty: U256,
+    current_energy_limit: U256,
+    current_number: U256,
+    current_timestamp: U256,
+    current_base_fee: Option<U256>,
+    previous_hash: Option<B256>,
+    current_random: Option<B256>,
+    current_beacon_root: Option<B256>,
+    current_withdrawals_root: Option<B256>,
+    parent_blob_energy_used: Option<U256>,
+    parent_excess_blob_energy: Option<U256>,
+    current_excess_blob_energy: Option<U256>,
+}
+
+impl From<EthereumEnv> for Env {
+    fn from(env: EthereumEnv) -> Self {
+        Self {
+            current_coinbase: env.current_coinbase,
+            current_difficulty: env.current_difficulty,
+            current_energy_limit: env.current_energy_limit,
+            current_number: env.current_number,
+            current_timestamp: env.current_timestamp,
+            current_base_fee: env.current_base_fee,
+            previous_hash: env.previous_hash,
+            current_random: env.current_random,
+            current_beacon_root: env.current_beacon_root,
+            current_withdrawals_root: env.current_withdrawals_root,
+            parent_blob_energy_used: env.parent_blob_energy_used,
+            parent_excess_blob_energy: env.parent_excess_blob_energy,
+            current_excess_blob_energy: env.current_excess_blob_energy,
+        }
+    }
+}
+
+/// Upstream counterpart of [TransactionParts].
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthereumTransactionParts {
+    data: Vec<Bytes>,
+    energy_limit: Vec<U256>,
+    energy_price: Option<U256>,
+    nonce: U256,
+    secret_key: Bytes,
+    #[serde(default)]
+    sender: Option<Address>,
+    #[serde(deserialize_with = "deserialize_maybe_empty")]
+    to: Option<Address>,
+    value: Vec<U256>,
+    max_fee_per_energy: Option<U256>,
+    max_priority_fee_per_energy: Option<U256>,
+    #[serde(default)]
+    access_lists: Vec<Option<AccessList>>,
+    #[serde(default)]
+    blob_versioned_hashes: Vec<B256>,
+    max_fee_per_blob_energy: Option<U256>,
+}
+
+impl From<EthereumTransactionParts> for TransactionParts {
+    fn from(transaction: EthereumTransactionParts) -> Self {
+        Self {
+            data: transaction.data,
+            energy_limit: transaction.energy_limit,
+            energy_price: transaction.energy_price,
+            nonce: transaction.nonce,
+            secret_key: transaction.secret_key,
+            sender: transaction.sender,
+            to: transaction.to,
+            value: transaction.value,
+            max_fee_per_energy: transaction.max_fee_per_energy,
+            max_priority_fee_per_energy: transaction.max_priority_fee_per_energy,
+            access_lists: transaction.access_lists,
+            blob_versioned_hashes: transaction.blob_versioned_hashes,
+            max_fee_per_blob_energy: transaction.max_fee_per_blob_energy,
+        }
+    }
+}
+
+/// Renames `gas` fields of every test unit of the suite to `energy`.
+pub fn alias_gas_fields(suite: &mut Value) {
+    let Some(units) = suite.as_object_mut() else {
+        return;
+    };
+    for unit in units.values_mut() {
+        let Some(unit) = unit.as_object_mut() else {
+            continue;
+        };
+        for field in ["env", "transaction"] {
+            if let Some(Value::Object(object)) = unit.get_mut(field) {
+                rename_gas_keys(object);
+            }
+        }
+        let Some(Value::Object(post)) = unit.get_mut("post") else {
+            continue;
+        };
+        for tests in post.values_mut().filter_map(Value::as_array_mut) {
+            for test in tests {
+                if let Some(Value::Object(indexes)) = test.get_mut("indexes") {
+                    rename_gas_keys(indexes);
+                }
+            }
+        }
+    }
+}
+
+/// Renames keys like `gas`, `gasLimit` or `maxFeePerBlobGas` to their `energy` names.
+///
+/// Keys whose `energy` name is already present are left untouched.
+fn rename_gas_keys(object: &mut Map<String, Value>) {
+    let keys: Vec<String> = object
+        .keys()
+        .filter(|key| key.starts_with("gas") || key.contains("Gas"))
+        .cloned()
+        .collect();
+    for key in keys {
+        let renamed = match key.strip_prefix("gas") {
+            Some(rest) => format!("energy{rest}"),
+            None => key.clone(),
+        }
+        .replace("Gas", "Energy");
+        if object.contains_key(&renamed) {
+            continue;
+        }
+        if let Some(value) = object.remove(&key) {
+            object.insert(renamed, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rename_gas_fields() {
+        let mut suite = json!({
+            "gasTest": {
+                "env": { "currentGasLimit": "0x01", "currentExcessBlobGas": "0x00" },
+                "transaction": { "gasLimit": ["0x01"], "maxFeePerGas": "0x02" },
+                "post": { "Cancun": [{ "indexes": { "data": 0, "gas": 0, "value": 0 } }] }
+            }
+        });
+        alias_gas_fields(&mut suite);
+        assert_eq!(
+            suite,
+            json!({
+                "gasTest": {
+                    "env": { "currentEnergyLimit": "0x01", "currentExcessBlobEnergy": "0x00" },
+                    "transaction": { "energyLimit": ["0x01"], "maxFeePerEnergy": "0x02" },
+                    "post": { "Cancun": [{ "indexes": { "data": 0, "energy": 0, "value": 0 } }] }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn ignore_unknown_test_fields() {
+        let suite = json!({
+            "test": {
+                "config": { "chainid": "0x01" },
+                "env": {
+                    "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                    "currentDifficulty": "0x00",
+                    "currentGasLimit": "0x01",
+                    "currentNumber": "0x01",
+                    "currentTimestamp": "0x00",
+                    "currentExcessBlobGas": "0x00",
+                    "currentBlobGasUsed": "0x00"
+                },
+                "pre": {},
+                "post": {
+                    "Cancun": [{
+                        "indexes": { "data": 0, "gas": 0, "value": 0 },
+                        "hash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                        "logs": "0x0000000000000000000000000000000000000000000000000000000000000002",
+                        "state": {}
+                    }]
+                },
+                "transaction": {
+                    "data": ["0x"],
+                    "gasLimit": ["0x01"],
+                    "nonce": "0x00",
+                    "secretKey": "0x00",
+                    "to": "",
+                    "value": ["0x00"],
+                    "authorizationList": []
+                }
+            }
+        });
+        let suite = TestSuite::from_ethereum_json(&suite.to_string()).unwrap();
+        let unit = &suite.0["test"];
+        assert_eq!(unit.env.current_energy_limit, U256::from(1));
+        assert_eq!(unit.env.current_excess_blob_energy, Some(U256::ZERO));
+        assert_eq!(unit.transaction.energy_limit, vec![U256::from(1)]);
+        let test = &unit.post[&SpecName::Cancun][0];
+        assert_eq!(test.hash, B256::with_last_byte(1));
+        assert_eq!(test.logs, B256::with_last_byte(2));
+    }
+}
@@ -0,0 +1,126 @@
+use super::{AccountInfo, Env, SpecName, TransactionParts};
+use revm::primitives::{Address, Bytes, HashMap, U256};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::vec::Vec;
+
+/// Suite of state test fillers.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct FillerSuite(pub BTreeMap<String, FillerUnit>);
+
+/// State test filler, the filled test is created by executing every combination of the
+/// transaction parts.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FillerUnit {
+    /// Test info is optional
+    #[serde(default, rename = "_info")]
+    pub info: Option<serde_json::Value>,
+
+    pub env: Env,
+    pub pre: HashMap<Address, AccountInfo>,
+    pub transaction: TransactionParts,
+    pub expect: Vec<Expectation>,
+}
+
+/// Expected outcome of the transactions selected by the indexes, for the given specs.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Expectation {
+    #[serde(default)]
+    pub indexes: IndexFilter,
+    pub network: Vec<SpecName>,
+    #[serde(default)]
+    pub expect_exception: Option<String>,
+    /// Expected state of the accounts after the transaction.
+    #[serde(default)]
+    pub result: BTreeMap<Address, AccountExpectation>,
+}
+
+/// Indexes of the transaction parts, a missing part matches every index.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IndexFilter {
+    #[serde(default)]
+    pub data: Option<Vec<usize>>,
+    #[serde(default)]
+    pub energy: Option<Vec<usize>>,
+    #[serde(default)]
+    pub value: Option<Vec<usize>>,
+}
+
+impl IndexFilter {
+    pub fn matches(&self, data: usize, energy: usize, value: usize) -> bool {
+        let matches = |filter: &Option<Vec<usize>>, index| {
+            filter
+                .as_ref()
+                .map_or(true, |indexes| indexes.contains(&index))
+        };
+        matches(&self.data, data) && matches(&self.energy, energy) && matches(&self.value, value)
+    }
+}
+
+/// Expected account state, fields that are not present are not checked.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AccountExpectation {
+    #[serde(default)]
+    pub balance: Option<U256>,
+    #[serde(default)]
+    pub nonce: Option<U256>,
+    #[serde(default)]
+    pub code: Option<Bytes>,
+    /// Expected values of the storage slots, other slots are not checked.
+    #[serde(default)]
+    pub storage: HashMap<U256, U256>,
+    /// Account must not exist after the transaction.
+    #[serde(default)]
+    pub should_not_exist: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_filler() {
+        let json = r#"{
+            "fillerTest": {
+                "env": {
+                    "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                    "currentDifficulty": "0x020000",
+                    "currentEnergyLimit": "0x7fffffffffffffff",
+                    "currentNumber": "0x01",
+                    "currentTimestamp": "0x03e8"
+                },
+                "pre": {},
+                "transaction": {
+                    "data": ["0x", "0x01"],
+                    "energyLimit": ["0x061a80"],
+                    "energyPrice": "0x0a",
+                    "nonce": "0x00",
+                    "secretKey": "0x00",
+                    "to": "",
+                    "value": ["0x00"]
+                },
+                "expect": [{
+                    "indexes": { "data": [1] },
+                    "network": ["Shanghai", "Cancun"],
+                    "result": {
+                        "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba": {
+                            "storage": { "0x00": "0x01" }
+                        }
+                    }
+                }]
+            }
+        }"#;
+        let suite: FillerSuite = serde_json::from_str(json).unwrap();
+        let unit = &suite.0["fillerTest"];
+        assert_eq!(
+            unit.expect[0].network,
+            vec![SpecName::Shanghai, SpecName::Cancun]
+        );
+        assert!(unit.expect[0].indexes.matches(1, 0, 0));
+        assert!(!unit.expect[0].indexes.matches(0, 0, 0));
+    }
+}
@@ -3,34 +3,41 @@ use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::vec::Vec;
 
-mod deserializer;
+pub mod deserializer;
 use deserializer::*;
 
+mod ethereum;
+pub use ethereum::alias_gas_fields;
+
+mod filler;
+pub use filler::{AccountExpectation, Expectation, FillerSuite, FillerUnit, IndexFilter};
+
 mod spec;
 pub use self::spec::SpecName;
 
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TestSuite(pub BTreeMap<String, TestUnit>);
 
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct TestUnit {
     /// Test info is optional
-    #[serde(default, rename = "_info")]
+    #[serde(default, rename = "_info", skip_serializing_if = "Option::is_none")]
     pub info: Option<serde_json::Value>,
 
     pub env: Env,
     pub pre: HashMap<Address, AccountInfo>,
     pub post: BTreeMap<SpecName, Vec<Test>>,
     pub transaction: TransactionParts,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub out: Option<Bytes>,
 }
 
 /// State test indexed state result deserialization.
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Test {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub expect_exception: Option<String>,
 
     /// Indexes
@@ -45,10 +52,11 @@ pub struct Test {
     pub logs: B256,
 
     /// Tx bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub txbytes: Option<Bytes>,
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct TxPartIndices {
     pub data: usize,
@@ -56,17 +64,20 @@ pub struct TxPartIndices {
     pub value: usize,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct AccountInfo {
     pub balance: U256,
     pub code: Bytes,
-    #[serde(deserialize_with = "deserialize_str_as_u64")]
+    #[serde(
+        deserialize_with = "deserialize_str_as_u64",
+        serialize_with = "serialize_u64_as_str"
+    )]
     pub nonce: u64,
     pub storage: HashMap<U256, U256>,
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Env {
     pub current_coinbase: Address,
@@ -86,18 +97,21 @@ pub struct Env {
     pub current_excess_blob_energy: Option<U256>,
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct TransactionParts {
     pub data: Vec<Bytes>,
     pub energy_limit: Vec<U256>,
     pub energy_price: Option<U256>,
     pub nonce: U256,
-    pub secret_key: B256,
+    pub secret_key: Bytes,
     /// if sender is not present we need to derive it from secret key.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sender: Option<Address>,
-    #[serde(deserialize_with = "deserialize_maybe_empty")]
+    #[serde(
+        deserialize_with = "deserialize_maybe_empty",
+        serialize_with = "serialize_maybe_empty"
+    )]
     pub to: Option<Address>,
     pub value: Vec<U256>,
     pub max_fee_per_energy: Option<U256>,
@@ -1,7 +1,8 @@
 use revm::primitives::SpecId;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash)]
 pub enum SpecName {
     Frontier,
     FrontierToHomesteadAt5,
@@ -51,3 +52,14 @@ impl SpecName {
         }
     }
 }
+
+impl FromStr for SpecName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match serde_json::from_value(serde_json::Value::String(s.to_string())) {
+            Ok(Self::Unknown) | Err(_) => Err(format!("unknown spec {s}")),
+            Ok(spec) => Ok(spec),
+        }
+    }
+}
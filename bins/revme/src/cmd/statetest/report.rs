@@ -0,0 +1,210 @@
+use super::models::SpecName;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::string::String;
+use std::time::Duration;
+use std::vec::Vec;
+
+/// Outcome of a single test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestReport {
+    /// Path of the test file.
+    pub file: String,
+    pub name: String,
+    /// Spec and post index of the test, not set if the whole test or file was skipped.
+    pub spec: Option<SpecName>,
+    pub index: Option<usize>,
+    pub time: Duration,
+    pub status: TestStatus,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestStatus {
+    Passed,
+    Failed(String),
+    Skipped,
+}
+
+impl TestReport {
+    /// Name of the test including the spec and the index if they are set.
+    pub fn full_name(&self) -> String {
+        let mut name = self.name.clone();
+        if let Some(spec) = self.spec {
+            let _ = write!(name, "/{spec:?}");
+        }
+        if let Some(index) = self.index {
+            let _ = write!(name, "/{index}");
+        }
+        name
+    }
+}
+
+/// Number of passed, failed and skipped tests and their total time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub time: Duration,
+}
+
+impl Summary {
+    pub fn new<'a>(reports: impl IntoIterator<Item = &'a TestReport>) -> Self {
+        let mut summary = Self::default();
+        for report in reports {
+            match report.status {
+                TestStatus::Passed => summary.passed += 1,
+                TestStatus::Failed(_) => summary.failed += 1,
+                TestStatus::Skipped => summary.skipped += 1,
+            }
+            summary.time += report.time;
+        }
+        summary
+    }
+
+    pub fn total(&self) -> usize {
+        self.passed + self.failed + self.skipped
+    }
+}
+
+/// JSON summary of the run with the outcome and time in seconds of every test.
+pub fn json_report(reports: &[TestReport]) -> Value {
+    let summary = Summary::new(reports);
+    let tests: Vec<Value> = reports
+        .iter()
+        .map(|report| {
+            let (status, error) = match &report.status {
+                TestStatus::Passed => ("passed", None),
+                TestStatus::Failed(error) => ("failed", Some(error)),
+                TestStatus::Skipped => ("skipped", None),
+            };
+            json!({
+                "file": report.file,
+                "name": report.name,
+                "spec": report.spec,
+                "index": report.index,
+                "time": report.time.as_secs_f64(),
+                "status": status,
+                "error": error,
+            })
+        })
+        .collect();
+    json!({
+        "total": summary.total(),
+        "passed": summary.passed,
+        "failed": summary.failed,
+        "skipped": summary.skipped,
+        "time": summary.time.as_secs_f64(),
+        "tests": tests,
+    })
+}
+
+/// JUnit XML report with a test suite per test file.
+pub fn junit_report(reports: &[TestReport]) -> String {
+    let mut files: BTreeMap<&str, Vec<&TestReport>> = BTreeMap::new();
+    for report in reports {
+        files.entry(&report.file).or_default().push(report);
+    }
+
+    let summary = Summary::new(reports);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"statetest\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.6}\">",
+        summary.total(),
+        summary.failed,
+        summary.skipped,
+        summary.time.as_secs_f64()
+    );
+    for (file, reports) in files {
+        let summary = Summary::new(reports.iter().copied());
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.6}\">",
+            escape(file),
+            summary.total(),
+            summary.failed,
+            summary.skipped,
+            summary.time.as_secs_f64()
+        );
+        for report in reports {
+            let _ = write!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.6}\"",
+                escape(file),
+                escape(&report.full_name()),
+                report.time.as_secs_f64()
+            );
+            match &report.status {
+                TestStatus::Passed => xml.push_str("/>\n"),
+                TestStatus::Skipped => xml.push_str(">\n      <skipped/>\n    </testcase>\n"),
+                TestStatus::Failed(error) => {
+                    let first_line = error.lines().next().unwrap_or_default();
+                    let _ = write!(
+                        xml,
+                        ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                        escape(first_line),
+                        escape(error)
+                    );
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reports() -> Vec<TestReport> {
+        let report = |name: &str, status| TestReport {
+            file: "tests/add.json".into(),
+            name: name.into(),
+            spec: Some(SpecName::Cancun),
+            index: Some(0),
+            time: Duration::from_millis(1),
+            status,
+        };
+        vec![
+            report("add", TestStatus::Passed),
+            report("sub", TestStatus::Failed("got <1> & \"2\"".into())),
+            report("mul", TestStatus::Skipped),
+        ]
+    }
+
+    #[test]
+    fn json_summary() {
+        let report = json_report(&reports());
+        assert_eq!(report["total"], 3);
+        assert_eq!(report["failed"], 1);
+        assert_eq!(report["tests"][1]["status"], "failed");
+        assert_eq!(report["tests"][0]["spec"], "Cancun");
+    }
+
+    #[test]
+    fn junit_escapes_failures() {
+        let xml = junit_report(&reports());
+        assert!(xml.contains("tests=\"3\" failures=\"1\" skipped=\"1\""));
+        assert!(xml.contains("name=\"sub/Cancun/0\""));
+        assert!(xml.contains("got &lt;1&gt; &amp; &quot;2&quot;"));
+    }
+}
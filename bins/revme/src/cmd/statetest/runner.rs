@@ -1,7 +1,9 @@
 use super::{
-    merkle_trie::{log_rlp_hash, state_merkle_trie_root},
-    models::{SpecName, Test, TestSuite},
-    utils::recover_address,
+    diff::StateDiff,
+    filter::TestFilter,
+    models::{AccountInfo, SpecName, Test, TestSuite, TestUnit, TransactionParts, TxPartIndices},
+    report::{TestReport, TestStatus},
+    utils::{recover_address, AddressScheme},
 };
 use indicatif::{ProgressBar, ProgressDrawTarget};
 use revm::{
@@ -10,11 +12,12 @@ use revm::{
     inspectors::TracerEip3155,
     interpreter::CreateScheme,
     primitives::{
-        calc_excess_blob_energy, sha3, Bytecode, Bytes, EVMResultGeneric, Env, ExecutionResult,
-        SpecId, TransactTo, B256, U256,
+        calc_excess_blob_energy, sha3, Address, Bytecode, Bytes, EVMResultGeneric, Env,
+        ExecutionResult, HashMap, SpecId, TransactTo, B256, U256,
     },
-    Evm, State,
+    CacheState, Evm, State,
 };
+use revm_chain::trie::{log_rlp_hash, state_merkle_trie_root};
 use serde_json::json;
 use std::boxed::Box;
 use std::vec::Vec;
@@ -40,10 +43,15 @@ pub struct TestError {
 pub enum TestErrorKind {
     #[error("logs root mismatch: expected {expected:?}, got {got:?}")]
     LogsRootMismatch { got: B256, expected: B256 },
-    #[error("state root mismatch: expected {expected:?}, got {got:?}")]
-    StateRootMismatch { got: B256, expected: B256 },
+    #[error("state root mismatch: expected {expected:?}, got {got:?}\n{diff}")]
+    StateRootMismatch {
+        got: B256,
+        expected: B256,
+        /// Differences against the expected post state, empty if test has no post state.
+        diff: StateDiff,
+    },
     #[error("Unknown private key: {0:?}")]
-    UnknownPrivateKey(B256),
+    UnknownPrivateKey(Bytes),
     #[error("Unexpected exception: {got_exception:?} but test expects:{expected_exception:?}")]
     UnexpectedException {
         expected_exception: Option<String>,
@@ -56,6 +64,36 @@ pub enum TestErrorKind {
     },
     #[error(transparent)]
     SerdeDeserialize(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Format of the test fixtures.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum TestFormat {
+    /// Core state tests using `energy` field names and Ed448 keys.
+    #[default]
+    Core,
+    /// Upstream Ethereum state tests using `gas` field names.
+    ///
+    /// Senders are derived with the given scheme that falls back to secp256k1.
+    EthereumCompat(AddressScheme),
+}
+
+/// Options of the statetest run.
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+    pub single_thread: bool,
+    /// Print the EIP-3155 trace of every test, implies `print_outcome`.
+    pub trace: bool,
+    /// Print the outcome of every test in JSON format, implies `single_thread`.
+    pub print_outcome: bool,
+    /// Print the EIP-3155 trace of the failing test index.
+    pub trace_failing: bool,
+    pub format: TestFormat,
+    pub filter: TestFilter,
+    /// Keep running after a test fails, the first error is returned once all tests ran.
+    pub keep_going: bool,
 }
 
 pub fn find_all_json_tests(path: &Path) -> Vec<PathBuf> {
@@ -197,9 +235,15 @@ fn check_evm_execution<EXT>(
     }
 
     if state_root != test.hash {
+        let diff = if test.post_state.is_empty() {
+            StateDiff::default()
+        } else {
+            StateDiff::new(&test.post_state, &evm.context.evm.db.cache)
+        };
         let kind = TestErrorKind::StateRootMismatch {
             got: state_root,
             expected: test.hash,
+            diff,
         };
         print_json_output(Some(kind.to_string()));
         return Err(TestError {
@@ -213,82 +257,202 @@ fn check_evm_execution<EXT>(
     Ok(())
 }
 
+/// Creates cache state that contains the pre state accounts of the test.
+pub fn pre_state(pre: &HashMap<Address, AccountInfo>) -> CacheState {
+    let mut cache_state = CacheState::new(false);
+    for (address, info) in pre {
+        let acc_info = revm::primitives::AccountInfo {
+            balance: info.balance,
+            code_hash: sha3(&info.code),
+            code: Some(Bytecode::new_raw(info.code.clone())),
+            nonce: info.nonce,
+        };
+        cache_state.insert_account_with_storage(*address, acc_info, info.storage.clone());
+    }
+    cache_state
+}
+
+/// Creates environment of the test unit.
+///
+/// Transaction fields that depend on the post state indexes are set by [`set_tx_indexes`].
+pub fn unit_env(name: &str, unit: &TestUnit, format: TestFormat) -> Result<Box<Env>, TestError> {
+    let mut env = Box::<Env>::default();
+    // for mainnet
+    env.cfg.network_id = 1;
+    // env.cfg.spec_id is set down the road
+
+    // block env
+    env.block.number = unit.env.current_number;
+    env.block.coinbase = unit.env.current_coinbase;
+    env.block.timestamp = unit.env.current_timestamp;
+    env.block.energy_limit = unit.env.current_energy_limit;
+    env.block.basefee = unit.env.current_base_fee.unwrap_or_default();
+    env.block.difficulty = unit.env.current_difficulty;
+    // after the Merge prevrandao replaces mix_hash field in block and replaced difficulty opcode in EVM.
+    env.block.prevrandao = unit.env.current_random;
+    // EIP-4844
+    if let Some(current_excess_blob_energy) = unit.env.current_excess_blob_energy {
+        env.block
+            .set_blob_excess_energy_and_price(current_excess_blob_energy.to());
+    } else if let (Some(parent_blob_energy_used), Some(parent_excess_blob_energy)) = (
+        unit.env.parent_blob_energy_used,
+        unit.env.parent_excess_blob_energy,
+    ) {
+        env.block
+            .set_blob_excess_energy_and_price(calc_excess_blob_energy(
+                parent_blob_energy_used.to(),
+                parent_excess_blob_energy.to(),
+            ));
+    }
+
+    // tx env
+    env.tx.caller = if let Some(address) = unit.transaction.sender {
+        address
+    } else {
+        let secret_key = unit.transaction.secret_key.as_ref();
+        match format {
+            TestFormat::Core => recover_address(secret_key),
+            TestFormat::EthereumCompat(scheme) => scheme.recover_address(secret_key),
+        }
+        .ok_or_else(|| TestError {
+            name: name.to_string(),
+            kind: TestErrorKind::UnknownPrivateKey(unit.transaction.secret_key.clone()),
+        })?
+    };
+    env.tx.energy_price = unit
+        .transaction
+        .energy_price
+        .or(unit.transaction.max_fee_per_energy)
+        .unwrap_or_default();
+    env.tx.energy_priority_fee = unit.transaction.max_priority_fee_per_energy;
+    // EIP-4844
+    env.tx.blob_hashes = unit.transaction.blob_versioned_hashes.clone();
+    env.tx.max_fee_per_blob_energy = unit.transaction.max_fee_per_blob_energy;
+    Ok(env)
+}
+
+/// Sets the transaction data, energy limit, value, access list and destination selected by
+/// the indexes.
+pub fn set_tx_indexes(env: &mut Env, transaction: &TransactionParts, indexes: &TxPartIndices) {
+    env.tx.energy_limit = transaction.energy_limit[indexes.energy].saturating_to();
+
+    env.tx.data = transaction.data.get(indexes.data).unwrap().clone();
+    env.tx.value = transaction.value[indexes.value];
+
+    env.tx.access_list = transaction
+        .access_lists
+        .get(indexes.data)
+        .and_then(Option::as_deref)
+        .unwrap_or_default()
+        .iter()
+        .map(|item| {
+            (
+                item.address,
+                item.storage_keys
+                    .iter()
+                    .map(|key| U256::from_be_bytes(key.0))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    let to = match transaction.to {
+        Some(add) => TransactTo::Call(add),
+        None => TransactTo::Create(CreateScheme::Create),
+    };
+    env.tx.transact_to = to;
+}
+
+/// Creates the state of the pre state accounts for the given spec.
+pub fn spec_state(cache_state: &CacheState, spec_id: SpecId) -> State<EmptyDB> {
+    let mut cache = cache_state.clone();
+    cache.set_state_clear_flag(SpecId::enabled(spec_id, SpecId::SPURIOUS_DRAGON));
+    State::builder()
+        .with_cached_prestate(cache)
+        .with_bundle_update()
+        .build()
+}
+
+/// Executes the tests of the file selected by the filter, the outcome of every test is
+/// added to the reports.
 pub fn execute_test_suite(
     path: &Path,
     elapsed: &Arc<Mutex<Duration>>,
-    trace: bool,
-    print_json_outcome: bool,
+    options: &RunOptions,
+    reports: &Mutex<Vec<TestReport>>,
 ) -> Result<(), TestError> {
-    if skip_test(path) {
+    let RunOptions {
+        trace,
+        print_outcome: print_json_outcome,
+        trace_failing,
+        format,
+        ref filter,
+        keep_going,
+        ..
+    } = *options;
+    let file = path.display().to_string();
+    let skipped = |name: &str| TestReport {
+        file: file.clone(),
+        name: name.to_string(),
+        spec: None,
+        index: None,
+        time: Duration::ZERO,
+        status: TestStatus::Skipped,
+    };
+
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    if skip_test(path) || filter.is_skipped(&file_name) {
+        reports.lock().unwrap().push(skipped(&file_name));
         return Ok(());
     }
 
+    let timer = Instant::now();
     let s = std::fs::read_to_string(path).unwrap();
-    let suite: TestSuite = serde_json::from_str(&s).map_err(|e| TestError {
-        name: path.to_string_lossy().into_owned(),
-        kind: e.into(),
-    })?;
-
-    for (name, unit) in suite.0 {
-        // Create database and insert cache
-        let mut cache_state = revm::CacheState::new(false);
-        for (address, info) in unit.pre {
-            let acc_info = revm::primitives::AccountInfo {
-                balance: info.balance,
-                code_hash: sha3(&info.code),
-                code: Some(Bytecode::new_raw(info.code)),
-                nonce: info.nonce,
+    let suite = match format {
+        TestFormat::Core => serde_json::from_str(&s),
+        TestFormat::EthereumCompat(_) => TestSuite::from_ethereum_json(&s),
+    };
+    let suite: TestSuite = match suite {
+        Ok(suite) => suite,
+        Err(e) => {
+            let e = TestError {
+                name: file.clone(),
+                kind: e.into(),
             };
-            cache_state.insert_account_with_storage(address, acc_info, info.storage);
+            reports.lock().unwrap().push(TestReport {
+                status: TestStatus::Failed(e.to_string()),
+                time: timer.elapsed(),
+                ..skipped(&file_name)
+            });
+            return Err(e);
         }
+    };
 
-        let mut env = Box::<Env>::default();
-        // for mainnet
-        env.cfg.network_id = 1;
-        // env.cfg.spec_id is set down the road
-
-        // block env
-        env.block.number = unit.env.current_number;
-        env.block.coinbase = unit.env.current_coinbase;
-        env.block.timestamp = unit.env.current_timestamp;
-        env.block.energy_limit = unit.env.current_energy_limit;
-        env.block.basefee = unit.env.current_base_fee.unwrap_or_default();
-        env.block.difficulty = unit.env.current_difficulty;
-        // after the Merge prevrandao replaces mix_hash field in block and replaced difficulty opcode in EVM.
-        env.block.prevrandao = unit.env.current_random;
-        // EIP-4844
-        if let Some(current_excess_blob_energy) = unit.env.current_excess_blob_energy {
-            env.block
-                .set_blob_excess_energy_and_price(current_excess_blob_energy.to());
-        } else if let (Some(parent_blob_energy_used), Some(parent_excess_blob_energy)) = (
-            unit.env.parent_blob_energy_used,
-            unit.env.parent_excess_blob_energy,
-        ) {
-            env.block
-                .set_blob_excess_energy_and_price(calc_excess_blob_energy(
-                    parent_blob_energy_used.to(),
-                    parent_excess_blob_energy.to(),
-                ));
+    // first failure, returned once all tests ran if `keep_going` is set.
+    let mut failure = None;
+    for (name, unit) in suite.0 {
+        if filter.is_skipped(&name) {
+            reports.lock().unwrap().push(skipped(&name));
+            continue;
+        }
+        if !filter.matches_name(&name) {
+            continue;
         }
 
-        // tx env
-        env.tx.caller = if let Some(address) = unit.transaction.sender {
-            address
-        } else {
-            recover_address(unit.transaction.secret_key.as_slice()).ok_or_else(|| TestError {
-                name: name.clone(),
-                kind: TestErrorKind::UnknownPrivateKey(unit.transaction.secret_key),
-            })?
+        // Create database and insert cache
+        let cache_state = pre_state(&unit.pre);
+        let mut env = match unit_env(&name, &unit, format) {
+            Ok(env) => env,
+            Err(e) if keep_going => {
+                reports.lock().unwrap().push(TestReport {
+                    status: TestStatus::Failed(e.to_string()),
+                    ..skipped(&name)
+                });
+                failure.get_or_insert(e);
+                continue;
+            }
+            Err(e) => return Err(e),
         };
-        env.tx.energy_price = unit
-            .transaction
-            .energy_price
-            .or(unit.transaction.max_fee_per_energy)
-            .unwrap_or_default();
-        env.tx.energy_priority_fee = unit.transaction.max_priority_fee_per_energy;
-        // EIP-4844
-        env.tx.blob_hashes = unit.transaction.blob_versioned_hashes;
-        env.tx.max_fee_per_blob_energy = unit.transaction.max_fee_per_blob_energy;
 
         // post and execution
         for (spec_name, tests) in unit.post {
@@ -300,54 +464,28 @@ pub fn execute_test_suite(
             ) {
                 continue;
             }
+            if !filter.matches_spec(spec_name) {
+                continue;
+            }
 
             let spec_id = spec_name.to_spec_id();
 
             for (index, test) in tests.into_iter().enumerate() {
-                env.tx.energy_limit =
-                    unit.transaction.energy_limit[test.indexes.energy].saturating_to();
-
-                env.tx.data = unit
-                    .transaction
-                    .data
-                    .get(test.indexes.data)
-                    .unwrap()
-                    .clone();
-                env.tx.value = unit.transaction.value[test.indexes.value];
-
-                env.tx.access_list = unit
-                    .transaction
-                    .access_lists
-                    .get(test.indexes.data)
-                    .and_then(Option::as_deref)
-                    .unwrap_or_default()
-                    .iter()
-                    .map(|item| {
-                        (
-                            item.address,
-                            item.storage_keys
-                                .iter()
-                                .map(|key| U256::from_be_bytes(key.0))
-                                .collect::<Vec<_>>(),
-                        )
-                    })
-                    .collect();
-
-                let to = match unit.transaction.to {
-                    Some(add) => TransactTo::Call(add),
-                    None => TransactTo::Create(CreateScheme::Create),
+                if !filter.matches_index(index) {
+                    continue;
+                }
+                let started = Instant::now();
+                let report = |status| TestReport {
+                    file: file.clone(),
+                    name: name.clone(),
+                    spec: Some(spec_name),
+                    index: Some(index),
+                    time: started.elapsed(),
+                    status,
                 };
-                env.tx.transact_to = to;
-
-                let mut cache = cache_state.clone();
-                cache.set_state_clear_flag(SpecId::enabled(
-                    spec_id,
-                    revm::primitives::SpecId::SPURIOUS_DRAGON,
-                ));
-                let mut state = revm::db::State::builder()
-                    .with_cached_prestate(cache)
-                    .with_bundle_update()
-                    .build();
+                set_tx_indexes(&mut env, &unit.transaction, &test.indexes);
+
+                let mut state = spec_state(&cache_state, spec_id);
                 let mut evm = Evm::builder()
                     .with_db(&mut state)
                     .modify_env(|e| *e = env.clone())
@@ -374,6 +512,7 @@ pub fn execute_test_suite(
                         &evm,
                         print_json_outcome,
                     ) else {
+                        reports.lock().unwrap().push(report(TestStatus::Passed));
                         continue;
                     };
                     // reset external context
@@ -393,68 +532,87 @@ pub fn execute_test_suite(
                         print_json_outcome,
                     );
                     let Err(e) = output else {
+                        reports.lock().unwrap().push(report(TestStatus::Passed));
                         continue;
                     };
                     (e, res)
                 };
 
+                reports
+                    .lock()
+                    .unwrap()
+                    .push(report(TestStatus::Failed(e.to_string())));
+
                 // print only once or
                 // if we are already in trace mode, just return error
                 static FAILED: AtomicBool = AtomicBool::new(false);
                 if FAILED.swap(true, Ordering::SeqCst) {
+                    if keep_going {
+                        failure.get_or_insert(e);
+                        continue;
+                    }
                     return Err(e);
                 }
 
-                // re build to run with tracing
-                let mut cache = cache_state.clone();
-                cache.set_state_clear_flag(SpecId::enabled(
-                    spec_id,
-                    revm::primitives::SpecId::SPURIOUS_DRAGON,
-                ));
-                let state = revm::db::State::builder()
-                    .with_cached_prestate(cache)
-                    .with_bundle_update()
-                    .build();
-
+                // re build to run with tracing of the failing index only
                 let path = path.display();
-                println!("\nTraces:");
-                let mut evm = Evm::builder()
-                    .with_spec_id(spec_id)
-                    .with_db(state)
-                    .with_external_context(TracerEip3155::new(Box::new(stdout())))
-                    .append_handler_register(inspector_handle_register)
-                    .build();
-                let _ = evm.transact_commit();
+                if trace_failing {
+                    println!("\nTraces:");
+                    let mut evm = Evm::builder()
+                        .with_spec_id(spec_id)
+                        .with_db(spec_state(&cache_state, spec_id))
+                        .modify_env(|e| *e = env.clone())
+                        .with_external_context(TracerEip3155::new(Box::new(stdout())))
+                        .append_handler_register(inspector_handle_register)
+                        .build();
+                    let _ = evm.transact_commit();
+                }
 
                 println!("\nExecution result: {exec_result:#?}");
                 println!("\nExpected exception: {:?}", test.expect_exception);
-                println!("\nState before: {cache_state:#?}");
-                println!("\nState after: {:#?}", evm.context.evm.db.cache);
+                // post state diff is part of the error, dump the whole state only if
+                // test has no post state to compare with.
+                if test.post_state.is_empty() {
+                    let mut state = spec_state(&cache_state, spec_id);
+                    let mut evm = Evm::builder()
+                        .with_spec_id(spec_id)
+                        .with_db(&mut state)
+                        .modify_env(|e| *e = env.clone())
+                        .build();
+                    let _ = evm.transact_commit();
+                    drop(evm);
+                    println!("\nState before: {cache_state:#?}");
+                    println!("\nState after: {:#?}", state.cache);
+                }
                 println!("\nSpecification: {spec_id:?}");
                 println!("\nEnvironment: {env:#?}");
                 println!("\nTest name: {name:?} (index: {index}, path: {path}) failed:\n{e}");
 
-                return Err(e);
+                if !keep_going {
+                    return Err(e);
+                }
+                failure.get_or_insert(e);
             }
         }
     }
-    Ok(())
+    failure.map_or(Ok(()), Err)
 }
 
+/// Runs the test files, the outcome of every executed test is added to the reports.
 pub fn run(
     test_files: Vec<PathBuf>,
-    mut single_thread: bool,
-    trace: bool,
-    mut print_outcome: bool,
+    mut options: RunOptions,
+    reports: Arc<Mutex<Vec<TestReport>>>,
 ) -> Result<(), TestError> {
     // trace implies print_outcome
-    if trace {
-        print_outcome = true;
+    if options.trace {
+        options.print_outcome = true;
     }
     // print_outcome or trace implies single_thread
-    if print_outcome {
-        single_thread = true;
+    if options.print_outcome {
+        options.single_thread = true;
     }
+    let options = Arc::new(options);
     let n_files = test_files.len();
 
     let endjob = Arc::new(AtomicBool::new(false));
@@ -465,7 +623,7 @@ pub fn run(
     let queue = Arc::new(Mutex::new((0usize, test_files)));
     let elapsed = Arc::new(Mutex::new(std::time::Duration::ZERO));
 
-    let num_threads = match (single_thread, std::thread::available_parallelism()) {
+    let num_threads = match (options.single_thread, std::thread::available_parallelism()) {
         (true, _) | (false, Err(_)) => 1,
         (false, Ok(n)) => n.get(),
     };
@@ -476,29 +634,40 @@ pub fn run(
         let endjob = endjob.clone();
         let console_bar = console_bar.clone();
         let elapsed = elapsed.clone();
+        let options = options.clone();
+        let reports = reports.clone();
 
         let thread = std::thread::Builder::new().name(format!("runner-{i}"));
 
-        let f = move || loop {
-            if endjob.load(Ordering::SeqCst) {
-                return Ok(());
-            }
+        let f = move || {
+            // first error of the thread if `keep_going` is set.
+            let mut result = Ok(());
+            loop {
+                if endjob.load(Ordering::SeqCst) {
+                    return result;
+                }
 
-            let (_index, test_path) = {
-                let (current_idx, queue) = &mut *queue.lock().unwrap();
-                let prev_idx = *current_idx;
-                let Some(test_path) = queue.get(prev_idx).cloned() else {
-                    return Ok(());
+                let (_index, test_path) = {
+                    let (current_idx, queue) = &mut *queue.lock().unwrap();
+                    let prev_idx = *current_idx;
+                    let Some(test_path) = queue.get(prev_idx).cloned() else {
+                        return result;
+                    };
+                    *current_idx = prev_idx + 1;
+                    (prev_idx, test_path)
                 };
-                *current_idx = prev_idx + 1;
-                (prev_idx, test_path)
-            };
 
-            if let Err(err) = execute_test_suite(&test_path, &elapsed, trace, print_outcome) {
-                endjob.store(true, Ordering::SeqCst);
-                return Err(err);
+                if let Err(err) = execute_test_suite(&test_path, &elapsed, &options, &reports) {
+                    if !options.keep_going {
+                        endjob.store(true, Ordering::SeqCst);
+                        return Err(err);
+                    }
+                    if result.is_ok() {
+                        result = Err(err);
+                    }
+                }
+                console_bar.inc(1);
             }
-            console_bar.inc(1);
         };
         handles.push(thread.spawn(f).unwrap());
     }
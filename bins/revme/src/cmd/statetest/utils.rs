@@ -1,11 +1,51 @@
-use libgoldilocks::SigningKey;
+use k256::ecdsa::SigningKey as Secp256k1SigningKey;
 use revm::primitives::Address;
+pub use revm_chain::ed448::recover_address;
+use sha3::{Digest, Keccak256};
 
-/// Recover the address from a private key (SigningKey).
-pub fn recover_address(private_key: &[u8]) -> Option<Address> {
-    let key = SigningKey::from_slice(private_key);
-    let public_key = key.verifying_key();
-    Some(Address::from_raw_public_key(&public_key.as_bytes()))
+/// Derives the sender address from the transaction secret key.
+pub type KeyToAddress = fn(&[u8]) -> Option<Address>;
+
+/// Recover the address from an Ed448 private key, `None` if key is not 57 bytes long.
+pub fn recover_ed448_address(private_key: &[u8]) -> Option<Address> {
+    if private_key.len() != 57 {
+        return None;
+    }
+    recover_address(private_key)
+}
+
+/// Recover the Ethereum address from a secp256k1 private key.
+pub fn recover_secp256k1_address(private_key: &[u8]) -> Option<Address> {
+    let key = Secp256k1SigningKey::from_slice(private_key).ok()?;
+    let public_key = key.verifying_key().to_encoded_point(false);
+    let hash = Keccak256::digest(&public_key.as_bytes()[1..]);
+    Some(Address::from_slice(&hash[12..]))
+}
+
+/// Address derivation used when loading tests.
+///
+/// Primary derivation is tried first and secp256k1 derivation is used if it fails, so
+/// fixtures of upstream Ethereum tests can be loaded with any primary scheme.
+#[derive(Clone, Copy, Debug)]
+pub struct AddressScheme {
+    pub primary: KeyToAddress,
+}
+
+impl Default for AddressScheme {
+    fn default() -> Self {
+        Self::new(recover_ed448_address)
+    }
+}
+
+impl AddressScheme {
+    pub fn new(primary: KeyToAddress) -> Self {
+        Self { primary }
+    }
+
+    /// Derive the address with the primary scheme, falling back to secp256k1.
+    pub fn recover_address(&self, private_key: &[u8]) -> Option<Address> {
+        (self.primary)(private_key).or_else(|| recover_secp256k1_address(private_key))
+    }
 }
 
 #[cfg(test)]
@@ -14,12 +54,11 @@ mod tests {
     use revm::primitives::{address, hex};
 
     #[test]
-    fn sanity_test() {
-        assert_eq!(
-            Some(address!("1afe4bd57060cb20be3da71729151922bcbf3947")),
-            recover_address(&hex!(
-                "445a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d85a915e4d060149eb4365960e6a7a45f334393093061110068"
-            ))
-        )
+    fn secp256k1_fallback() {
+        // sender of the upstream Ethereum state tests.
+        let key = hex!("45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8");
+        let expected = Some(address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b"));
+        assert_eq!(recover_secp256k1_address(&key), expected);
+        assert_eq!(AddressScheme::default().recover_address(&key), expected);
     }
 }
@@ -0,0 +1,132 @@
+pub mod models;
+mod runner;
+
+pub use runner::T8nError as Error;
+pub use runner::{transition, T8nError, Transition, TransitionOptions};
+
+use super::statetest::models::SpecName;
+use alloy_rlp::Decodable;
+use models::{parse_alloc, T8nEnv, TransactionJson};
+use revm::primitives::Bytes;
+use revm_chain::SignedTransaction;
+use std::path::PathBuf;
+use std::vec::Vec;
+use structopt::StructOpt;
+
+/// State transition command, compatible with the interface of the `evm t8n` tool.
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// Accounts of the state before the transition.
+    #[structopt(long = "input.alloc", default_value = "alloc.json")]
+    input_alloc: PathBuf,
+    /// Block environment.
+    #[structopt(long = "input.env", default_value = "env.json")]
+    input_env: PathBuf,
+    /// Transactions as JSON, or as hex string of the rlp encoded list of signed transactions
+    /// if the file has the `.rlp` extension.
+    #[structopt(long = "input.txs", default_value = "txs.json")]
+    input_txs: PathBuf,
+    /// Folder the outputs and traces are written to.
+    #[structopt(long = "output.basedir", default_value = ".")]
+    output_basedir: PathBuf,
+    /// Accounts of the state after the transition.
+    #[structopt(long = "output.alloc", default_value = "alloc.json")]
+    output_alloc: PathBuf,
+    /// Roots, receipts and rejected transactions of the block.
+    #[structopt(long = "output.result", default_value = "result.json")]
+    output_result: PathBuf,
+    /// Hex string of the rlp encoded list of the included transactions.
+    #[structopt(long = "output.body")]
+    output_body: Option<PathBuf>,
+    /// Spec the transactions are executed with.
+    #[structopt(long = "state.fork", default_value = "Cancun")]
+    fork: SpecName,
+    #[structopt(long = "state.networkid", default_value = "1")]
+    network_id: u64,
+    /// Block reward of the coinbase, coinbase is not rewarded if not set.
+    #[structopt(long = "state.reward")]
+    reward: Option<u128>,
+    /// Write the EIP-3155 trace of every transaction to `trace-<index>-<hash>.jsonl` in the
+    /// output folder.
+    #[structopt(long)]
+    trace: bool,
+}
+
+impl Cmd {
+    /// Run t8n command.
+    pub fn run(&self) -> Result<(), Error> {
+        let alloc = parse_alloc(&std::fs::read_to_string(&self.input_alloc)?)?;
+        let env: T8nEnv = serde_json::from_str(&std::fs::read_to_string(&self.input_env)?)?;
+        let transactions = self.transactions()?;
+
+        std::fs::create_dir_all(&self.output_basedir)?;
+        let options = TransitionOptions {
+            spec_id: self.fork.to_spec_id(),
+            network_id: self.network_id,
+            reward: self.reward,
+            trace_dir: self.trace.then(|| self.output_basedir.clone()),
+        };
+        let transition = transition(&alloc, &env, &transactions, &options)?;
+
+        let output = |path: &PathBuf| self.output_basedir.join(path);
+        std::fs::write(
+            output(&self.output_alloc),
+            serde_json::to_string_pretty(&transition.alloc)?,
+        )?;
+        std::fs::write(
+            output(&self.output_result),
+            serde_json::to_string_pretty(&transition.result)?,
+        )?;
+        if let Some(path) = &self.output_body {
+            let body = Bytes::from(alloy_rlp::encode(&transition.transactions));
+            std::fs::write(output(path), serde_json::to_string(&body)?)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the transactions, signing the JSON transactions that are not signed.
+    fn transactions(&self) -> Result<Vec<SignedTransaction>, Error> {
+        let s = std::fs::read_to_string(&self.input_txs)?;
+        if self.input_txs.extension().is_some_and(|ext| ext == "rlp") {
+            let hex = s.trim().trim_matches('"');
+            let rlp = hex::decode(hex.strip_prefix("0x").unwrap_or(hex))?;
+            return Ok(Vec::<SignedTransaction>::decode(&mut rlp.as_slice())?);
+        }
+        let transactions: Vec<TransactionJson> = serde_json::from_str(&s)?;
+        transactions
+            .into_iter()
+            .enumerate()
+            .map(|(index, tx)| sign_transaction(index, tx, self.network_id))
+            .collect()
+    }
+}
+
+fn sign_transaction(
+    index: usize,
+    tx: TransactionJson,
+    network_id: u64,
+) -> Result<SignedTransaction, Error> {
+    let unsigned = SignedTransaction {
+        nonce: tx.nonce.saturating_to(),
+        energy_price: tx.energy_price,
+        energy_limit: tx.energy.saturating_to(),
+        to: tx
+            .to
+            .map(|address| Bytes::copy_from_slice(address.to_ican(network_id).as_slice()))
+            .unwrap_or_default(),
+        value: tx.value,
+        data: tx.input,
+        network_id,
+        signature: Bytes::new(),
+    };
+    match (tx.signature, tx.secret_key) {
+        (Some(signature), _) => Ok(SignedTransaction {
+            signature,
+            ..unsigned
+        }),
+        (None, Some(secret_key)) => unsigned
+            .sign(&secret_key)
+            .ok_or(Error::InvalidSecretKey(index)),
+        (None, None) => Err(Error::MissingSignature(index)),
+    }
+}
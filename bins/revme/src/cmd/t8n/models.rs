@@ -0,0 +1,154 @@
+use super::super::blocktest::models::Withdrawal;
+use revm::{
+    db::{deserialize_alloc, GenesisAccount},
+    primitives::{Address, Bytes, Log, B256, U256},
+};
+use revm_chain::Bloom;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::vec::Vec;
+
+/// Accounts of the state before or after the transition.
+pub type Alloc = BTreeMap<Address, GenesisAccount>;
+
+/// Parses the accounts of `alloc.json`, keyed by plain or ICAN addresses.
+pub fn parse_alloc(s: &str) -> Result<Alloc, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(s);
+    let alloc = deserialize_alloc(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(alloc)
+}
+
+/// Block environment of the transition, `env.json`.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nEnv {
+    pub current_coinbase: Address,
+    pub current_energy_limit: U256,
+    pub current_number: U256,
+    pub current_timestamp: U256,
+    #[serde(default)]
+    pub current_difficulty: U256,
+    /// Randomness of the block after the Merge.
+    #[serde(default)]
+    pub current_random: Option<B256>,
+    #[serde(default)]
+    pub current_base_fee: Option<U256>,
+    #[serde(default)]
+    pub current_excess_blob_energy: Option<U256>,
+    /// Hashes of the previous blocks available to the `BLOCKHASH` opcode.
+    #[serde(default)]
+    pub block_hashes: BTreeMap<U256, B256>,
+    #[serde(default)]
+    pub withdrawals: Vec<Withdrawal>,
+}
+
+/// Transaction of `txs.json`.
+///
+/// Transaction is signed with the secret key if it has no signature.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionJson {
+    pub nonce: U256,
+    pub energy_price: U256,
+    #[serde(alias = "energyLimit")]
+    pub energy: U256,
+    /// Receiver, contract is created if it is not set.
+    #[serde(default)]
+    pub to: Option<Address>,
+    pub value: U256,
+    #[serde(alias = "data")]
+    pub input: Bytes,
+    /// Signature followed by the public key of the sender.
+    #[serde(default)]
+    pub signature: Option<Bytes>,
+    #[serde(default)]
+    pub secret_key: Option<Bytes>,
+}
+
+/// Outcome of the transition, `result.json`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct T8nResult {
+    pub state_root: B256,
+    pub tx_root: B256,
+    pub receipts_root: B256,
+    /// Hash of the rlp encoded logs of all transactions.
+    pub logs_hash: B256,
+    pub logs_bloom: Bloom,
+    pub receipts: Vec<ReceiptJson>,
+    pub rejected: Vec<RejectedTransaction>,
+    pub energy_used: U256,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptJson {
+    pub transaction_hash: B256,
+    pub transaction_index: U256,
+    /// `0x1` if transaction succeeded, `0x0` otherwise.
+    pub status: U256,
+    pub cumulative_energy_used: U256,
+    pub energy_used: U256,
+    pub logs_bloom: Bloom,
+    pub logs: Vec<LogJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<Address>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct LogJson {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+}
+
+impl From<&Log> for LogJson {
+    fn from(log: &Log) -> Self {
+        Self {
+            address: log.address,
+            topics: log.topics().to_vec(),
+            data: log.data.data.clone(),
+        }
+    }
+}
+
+/// Transaction that was not included in the block.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct RejectedTransaction {
+    pub index: usize,
+    pub error: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_env() {
+        let json = r#"{
+            "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+            "currentEnergyLimit": "0x7fffffffffffffff",
+            "currentNumber": "0x01",
+            "currentTimestamp": "0x03e8",
+            "blockHashes": { "0x00": "0x0000000000000000000000000000000000000000000000000000000000000001" }
+        }"#;
+        let env: T8nEnv = serde_json::from_str(json).unwrap();
+        assert_eq!(env.current_number, U256::from(1));
+        assert_eq!(env.block_hashes.len(), 1);
+        assert!(env.withdrawals.is_empty());
+    }
+
+    #[test]
+    fn parse_ican_alloc_and_sort_storage() {
+        let address = Address::with_last_byte(1);
+        let ican = revm::primitives::hex::encode(address.to_ican(1).as_slice());
+        let json = format!(
+            r#"{{ "{ican}": {{ "balance": "0x01", "storage": {{ "0x02": "0x01", "0x01": "0x02" }} }} }}"#
+        );
+        let alloc = parse_alloc(&json).unwrap();
+        assert_eq!(alloc[&address].balance, U256::from(1));
+        let account = serde_json::to_string(&alloc[&address]).unwrap();
+        assert!(account.contains(r#""storage":{"0x1":"0x2","0x2":"0x1"}"#));
+    }
+}
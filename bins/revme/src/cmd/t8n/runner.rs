@@ -0,0 +1,288 @@
+use super::models::{Alloc, LogJson, ReceiptJson, RejectedTransaction, T8nEnv, T8nResult};
+use revm::{
+    db::GenesisAccount,
+    primitives::{Address, BlockEnv, Env, HashMap, Log, SpecId, U256},
+    CacheState, State,
+};
+use revm_chain::{
+    logs_bloom,
+    trie::{log_rlp_hash, ordered_trie_root, state_merkle_trie_root},
+    BlockExecutor, SignedTransaction,
+};
+use std::boxed::Box;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::vec::Vec;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum T8nError {
+    #[error("transaction {0} has neither signature nor secret key")]
+    MissingSignature(usize),
+    #[error("transaction {0} secret key is not an Ed448 private key")]
+    InvalidSecretKey(usize),
+    #[error("transactions can't be decoded: {0}")]
+    Rlp(#[from] alloy_rlp::Error),
+    #[error(transparent)]
+    Hex(#[from] hex::FromHexError),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Options of the state transition.
+#[derive(Clone, Debug)]
+pub struct TransitionOptions {
+    pub spec_id: SpecId,
+    pub network_id: u64,
+    /// Block reward of the coinbase, not rewarded if `None`.
+    pub reward: Option<u128>,
+    /// Folder the EIP-3155 trace of every transaction is written to.
+    pub trace_dir: Option<PathBuf>,
+}
+
+/// State after the transition and the outcome of the block.
+#[derive(Debug)]
+pub struct Transition {
+    pub alloc: Alloc,
+    pub result: T8nResult,
+    /// Transactions included in the block.
+    pub transactions: Vec<SignedTransaction>,
+}
+
+fn block_env(env: &T8nEnv, spec_id: SpecId) -> BlockEnv {
+    let mut block = BlockEnv {
+        number: env.current_number,
+        coinbase: env.current_coinbase,
+        timestamp: env.current_timestamp,
+        energy_limit: env.current_energy_limit,
+        basefee: env.current_base_fee.unwrap_or_default(),
+        difficulty: env.current_difficulty,
+        prevrandao: SpecId::enabled(spec_id, SpecId::MERGE)
+            .then(|| env.current_random.unwrap_or_default()),
+        blob_excess_energy_and_price: None,
+    };
+    if let Some(excess_blob_energy) = env.current_excess_blob_energy {
+        block.set_blob_excess_energy_and_price(excess_blob_energy.to());
+    }
+    block
+}
+
+fn pre_state(alloc: &Alloc, spec_id: SpecId) -> CacheState {
+    let mut cache = CacheState::new(SpecId::enabled(spec_id, SpecId::SPURIOUS_DRAGON));
+    for (address, account) in alloc {
+        cache.insert_account_with_storage(
+            *address,
+            account.account_info(),
+            account.storage.clone(),
+        );
+    }
+    cache
+}
+
+/// Applies the transactions on top of the allocated accounts.
+///
+/// Transactions that are invalid are rejected and do not change the state. After the
+/// transactions coinbase is rewarded and withdrawals are applied.
+pub fn transition(
+    alloc: &Alloc,
+    env: &T8nEnv,
+    transactions: &[SignedTransaction],
+    options: &TransitionOptions,
+) -> Result<Transition, T8nError> {
+    let spec_id = options.spec_id;
+    let block_hashes = env
+        .block_hashes
+        .iter()
+        .map(|(number, hash)| (number.saturating_to(), *hash))
+        .collect();
+    let mut state = State::builder()
+        .with_cached_prestate(pre_state(alloc, spec_id))
+        .with_block_hashes(block_hashes)
+        .with_bundle_update()
+        .build();
+
+    let mut evm_env = Env::default();
+    evm_env.cfg.network_id = options.network_id;
+    evm_env.block = block_env(env, spec_id);
+    let block_energy_limit: u64 = env.current_energy_limit.saturating_to();
+
+    let mut executor = BlockExecutor::new(&mut state, evm_env, spec_id);
+    let mut included = Vec::new();
+    let mut receipts_json = Vec::new();
+    let mut rejected = Vec::new();
+    for (index, tx) in transactions.iter().enumerate() {
+        let reject = |error| RejectedTransaction { index, error };
+        if tx.network_id != options.network_id {
+            rejected.push(reject(format!(
+                "network id {} does not match {}",
+                tx.network_id, options.network_id
+            )));
+            continue;
+        }
+        let cumulative_energy_used = executor.cumulative_energy_used();
+        if cumulative_energy_used.saturating_add(tx.energy_limit) > block_energy_limit {
+            rejected.push(reject("block energy limit reached".into()));
+            continue;
+        }
+        let tx_env = match tx.recover_sender().and_then(|sender| tx.tx_env(sender)) {
+            Ok(tx_env) => tx_env,
+            Err(error) => {
+                rejected.push(reject(error));
+                continue;
+            }
+        };
+
+        let hash = tx.hash();
+        let executed = match &options.trace_dir {
+            Some(dir) => {
+                let file = File::create(dir.join(format!("trace-{index}-{hash}.jsonl")))?;
+                executor.execute_with_tracer(tx_env, 0, Box::new(BufWriter::new(file)))
+            }
+            None => executor.execute(tx_env, 0),
+        };
+        let executed = match executed {
+            Ok(executed) => executed,
+            Err(error) => {
+                rejected.push(reject(error.to_string()));
+                continue;
+            }
+        };
+
+        let receipt = &executed.receipt;
+        receipts_json.push(ReceiptJson {
+            transaction_hash: hash,
+            transaction_index: U256::from(included.len()),
+            status: U256::from(receipt.success as u8),
+            cumulative_energy_used: U256::from(receipt.cumulative_energy_used),
+            energy_used: U256::from(executed.energy_used),
+            logs_bloom: logs_bloom(&receipt.logs),
+            logs: receipt.logs.iter().map(LogJson::from).collect(),
+            contract_address: executed.contract_address,
+        });
+        included.push(tx.clone());
+    }
+
+    // block reward, withdrawals are credited by the executor.
+    let mut balances: HashMap<Address, u128> = HashMap::new();
+    if let Some(reward) = options.reward {
+        balances.insert(env.current_coinbase, reward);
+    }
+    executor.finalize(
+        balances,
+        env.withdrawals.iter().map(|w| (w.address, w.amount)),
+    );
+    let receipts_root = executor.receipts_root();
+    let bloom = executor.logs_bloom();
+    let energy_used = executor.cumulative_energy_used();
+    let logs: Vec<Log> = executor
+        .receipts()
+        .iter()
+        .flat_map(|receipt| receipt.logs.iter().cloned())
+        .collect();
+    drop(executor);
+
+    let alloc: Alloc = state
+        .cache
+        .trie_account()
+        .into_iter()
+        .map(|(address, account)| {
+            let account = GenesisAccount {
+                balance: account.info.balance,
+                nonce: account.info.nonce,
+                code: account
+                    .info
+                    .code
+                    .as_ref()
+                    .map(|code| code.original_bytes())
+                    .unwrap_or_default(),
+                storage: account
+                    .storage
+                    .iter()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(slot, value)| (*slot, *value))
+                    .collect(),
+            };
+            (address, account)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let result = T8nResult {
+        state_root: state_merkle_trie_root(state.cache.trie_account()),
+        tx_root: ordered_trie_root(included.iter().map(alloy_rlp::encode)),
+        receipts_root,
+        logs_hash: log_rlp_hash(&logs),
+        logs_bloom: bloom,
+        receipts: receipts_json,
+        rejected,
+        energy_used: U256::from(energy_used),
+    };
+    Ok(Transition {
+        alloc,
+        result,
+        transactions: included,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{hex, TransactTo, TxEnv};
+
+    #[test]
+    fn transfer_and_reject() {
+        let key = hex!("445a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d85a915e4d060149eb4365960e6a7a45f334393093061110068");
+        let sender = revm_chain::ed448::recover_address(&key).unwrap();
+        let receiver = Address::with_last_byte(0xaa);
+        let mut alloc = Alloc::new();
+        alloc.insert(
+            sender,
+            GenesisAccount {
+                balance: U256::from(1_000_000_000),
+                ..Default::default()
+            },
+        );
+        let env = T8nEnv {
+            current_coinbase: Address::with_last_byte(0xcc),
+            current_energy_limit: U256::from(1_000_000),
+            current_number: U256::from(1),
+            current_timestamp: U256::from(1000),
+            current_difficulty: U256::ZERO,
+            current_random: None,
+            current_base_fee: None,
+            current_excess_blob_energy: None,
+            block_hashes: BTreeMap::new(),
+            withdrawals: Vec::new(),
+        };
+        let transfer = |nonce| {
+            let tx = TxEnv {
+                transact_to: TransactTo::Call(receiver),
+                energy_limit: 21_000,
+                energy_price: U256::from(1),
+                value: U256::from(10),
+                ..Default::default()
+            };
+            SignedTransaction::unsigned(&tx, nonce, 1)
+                .sign(&key)
+                .unwrap()
+        };
+        let options = TransitionOptions {
+            spec_id: SpecId::SHANGHAI,
+            network_id: 1,
+            reward: None,
+            trace_dir: None,
+        };
+        // second transaction reuses the nonce.
+        let transition = transition(&alloc, &env, &[transfer(0), transfer(0)], &options).unwrap();
+
+        assert_eq!(transition.transactions.len(), 1);
+        assert_eq!(transition.result.rejected.len(), 1);
+        assert_eq!(transition.result.rejected[0].index, 1);
+        assert_eq!(transition.result.energy_used, U256::from(21_000));
+        assert_eq!(transition.alloc[&receiver].balance, U256::from(10));
+        assert_eq!(transition.alloc[&sender].nonce, 1);
+    }
+}
@@ -0,0 +1,339 @@
+use revm::{
+    inspectors::{TraceLine, TraceReadError, TraceReader, TraceStep},
+    primitives::U256,
+};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::vec::Vec;
+use structopt::StructOpt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}: {1}")]
+    Trace(PathBuf, TraceReadError),
+    #[error("traces diverge at step {0}")]
+    Diverged(usize),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Compares two EIP-3155 traces and reports the first step they diverge at.
+#[derive(StructOpt, Debug)]
+pub struct Cmd {
+    /// Trace that is expected.
+    left: PathBuf,
+    /// Trace that is compared with the expected one.
+    right: PathBuf,
+    /// Number of matching steps printed before the divergence.
+    #[structopt(long, default_value = "5")]
+    context: usize,
+    /// Don't compare energy, energy cost and refund of the steps.
+    #[structopt(long)]
+    ignore_energy: bool,
+    /// Don't compare state roots of the summaries.
+    #[structopt(long)]
+    ignore_state_root: bool,
+}
+
+impl Cmd {
+    /// Run trace diff command.
+    pub fn run(&self) -> Result<(), Error> {
+        let left = BufReader::new(File::open(&self.left)?);
+        let right = BufReader::new(File::open(&self.right)?);
+        let options = DiffOptions {
+            context: self.context,
+            ignore_energy: self.ignore_energy,
+            ignore_state_root: self.ignore_state_root,
+        };
+        let divergence = diff(left, right, &options).map_err(|(side, e)| {
+            let path = match side {
+                Side::Left => &self.left,
+                Side::Right => &self.right,
+            };
+            Error::Trace(path.clone(), e)
+        })?;
+        match divergence {
+            Some(divergence) => {
+                println!("{divergence}");
+                Err(Error::Diverged(divergence.index))
+            }
+            None => {
+                println!("Traces are equal");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Trace the error was read from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DiffOptions {
+    /// Number of matching steps kept before the divergence.
+    pub context: usize,
+    /// Don't compare energy, energy cost and refund of the steps.
+    pub ignore_energy: bool,
+    /// Don't compare state roots of the summaries.
+    pub ignore_state_root: bool,
+}
+
+/// What differs between the lines of the traces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// Depth, program counter or opcode differ, the traces are no longer aligned.
+    Location,
+    Stack,
+    Energy,
+    EnergyCost,
+    MemSize,
+    Refund,
+    Error,
+    /// Outcome of the transaction differs.
+    Summary,
+    /// State root after the transaction differs.
+    StateRoot,
+    /// One of the traces ended.
+    Length,
+}
+
+/// First divergence of the traces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the line the traces diverge at, counted from zero.
+    pub index: usize,
+    pub kind: DivergenceKind,
+    /// Line of the left trace, `None` if it ended.
+    pub left: Option<TraceLine>,
+    /// Line of the right trace, `None` if it ended.
+    pub right: Option<TraceLine>,
+    /// Matching steps before the divergence, oldest first.
+    pub context: Vec<TraceStep>,
+}
+
+/// Streams both traces line by line and returns their first divergence.
+///
+/// Steps are aligned by depth, program counter and opcode, aligned steps are then compared by
+/// their stack, energy, memory size and refund. Only the last [DiffOptions::context] steps are
+/// kept in memory.
+pub fn diff<L: BufRead, R: BufRead>(
+    left: L,
+    right: R,
+    options: &DiffOptions,
+) -> Result<Option<Divergence>, (Side, TraceReadError)> {
+    let mut left = TraceReader::new(left);
+    let mut right = TraceReader::new(right);
+    let mut context = VecDeque::with_capacity(options.context);
+    let mut index = 0;
+    loop {
+        let left = left.next().transpose().map_err(|e| (Side::Left, e))?;
+        let right = right.next().transpose().map_err(|e| (Side::Right, e))?;
+        let kind = match (&left, &right) {
+            (None, None) => return Ok(None),
+            (Some(left), Some(right)) => compare(left, right, options),
+            _ => Some(DivergenceKind::Length),
+        };
+        if let Some(kind) = kind {
+            return Ok(Some(Divergence {
+                index,
+                kind,
+                left,
+                right,
+                context: context.into(),
+            }));
+        }
+        if let Some(TraceLine::Step(step)) = left {
+            if context.len() == options.context {
+                context.pop_front();
+            }
+            if options.context != 0 {
+                context.push_back(step);
+            }
+        }
+        index += 1;
+    }
+}
+
+fn compare(left: &TraceLine, right: &TraceLine, options: &DiffOptions) -> Option<DivergenceKind> {
+    let (left, right) = match (left, right) {
+        (TraceLine::Step(left), TraceLine::Step(right)) => (left, right),
+        (TraceLine::Summary(left), TraceLine::Summary(right)) => {
+            let equal = left.pass == right.pass
+                && left.output == right.output
+                && (options.ignore_energy || left.energy_used == right.energy_used);
+            return if !equal {
+                Some(DivergenceKind::Summary)
+            } else if !options.ignore_state_root && left.state_root != right.state_root {
+                Some(DivergenceKind::StateRoot)
+            } else {
+                None
+            };
+        }
+        _ => return Some(DivergenceKind::Location),
+    };
+    if (left.depth, left.pc, left.op) != (right.depth, right.pc, right.op) {
+        Some(DivergenceKind::Location)
+    } else if left.stack != right.stack {
+        Some(DivergenceKind::Stack)
+    } else if !options.ignore_energy && left.energy != right.energy {
+        Some(DivergenceKind::Energy)
+    } else if !options.ignore_energy && left.energy_cost != right.energy_cost {
+        Some(DivergenceKind::EnergyCost)
+    } else if left.mem_size != right.mem_size {
+        Some(DivergenceKind::MemSize)
+    } else if !options.ignore_energy && left.refund != right.refund {
+        Some(DivergenceKind::Refund)
+    } else if left.error != right.error {
+        Some(DivergenceKind::Error)
+    } else {
+        None
+    }
+}
+
+fn write_step(f: &mut fmt::Formatter<'_>, step: &TraceStep) -> fmt::Result {
+    write!(
+        f,
+        "depth {} pc {} {} energy {} cost {} memSize {} refund {}",
+        step.depth,
+        step.pc,
+        step.op_name.as_deref().unwrap_or("UNKNOWN"),
+        step.energy,
+        step.energy_cost,
+        step.mem_size,
+        step.refund,
+    )?;
+    if let Some(error) = &step.error {
+        write!(f, " error {error}")?;
+    }
+    Ok(())
+}
+
+fn write_line(f: &mut fmt::Formatter<'_>, name: &str, line: &Option<TraceLine>) -> fmt::Result {
+    write!(f, "{name}: ")?;
+    match line {
+        Some(TraceLine::Step(step)) => write_step(f, step)?,
+        Some(TraceLine::Summary(summary)) => write!(
+            f,
+            "summary pass {} energyUsed {} output {} stateRoot {:?}",
+            summary.pass, summary.energy_used, summary.output, summary.state_root
+        )?,
+        None => write!(f, "end of trace")?,
+    }
+    writeln!(f)
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Traces diverge at line {} ({:?})",
+            self.index + 1,
+            self.kind
+        )?;
+        if !self.context.is_empty() {
+            writeln!(f, "Preceding steps:")?;
+            for step in &self.context {
+                write!(f, "  ")?;
+                write_step(f, step)?;
+                writeln!(f)?;
+            }
+        }
+        write_line(f, "Left ", &self.left)?;
+        write_line(f, "Right", &self.right)?;
+
+        let (Some(TraceLine::Step(left)), Some(TraceLine::Step(right))) = (&self.left, &self.right)
+        else {
+            return Ok(());
+        };
+        writeln!(f, "Stack (top first):")?;
+        let depth = left.stack.len().max(right.stack.len());
+        for i in 0..depth {
+            // stack is traced with the top as the last item.
+            let item = |stack: &[U256]| stack.len().checked_sub(i + 1).map(|i| stack[i]);
+            let (l, r) = (item(&left.stack), item(&right.stack));
+            let marker = if l == r { ' ' } else { '*' };
+            let show = |v: Option<U256>| v.map(|v| format!("{v:#x}")).unwrap_or_else(|| "-".into());
+            writeln!(f, "{marker} {i:>4}: {} | {}", show(l), show(r))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEPS: [&str; 3] = [
+        r#"{"pc":0,"op":96,"energy":"0x64","energyCost":"0x3","stack":[],"depth":1,"returnData":"0x","refund":"0x0","memSize":"0","opName":"PUSH1"}"#,
+        r#"{"pc":2,"op":96,"energy":"0x61","energyCost":"0x3","stack":["0x1"],"depth":1,"returnData":"0x","refund":"0x0","memSize":"0","opName":"PUSH1"}"#,
+        r#"{"pc":4,"op":1,"energy":"0x5e","energyCost":"0x3","stack":["0x1","0x2"],"depth":1,"returnData":"0x","refund":"0x0","memSize":"0","opName":"ADD"}"#,
+    ];
+
+    fn options() -> DiffOptions {
+        DiffOptions {
+            context: 1,
+            ignore_energy: false,
+            ignore_state_root: false,
+        }
+    }
+
+    #[test]
+    fn equal_traces() {
+        let trace = STEPS.join("\n");
+        let divergence = diff(trace.as_bytes(), trace.as_bytes(), &options()).unwrap();
+        assert_eq!(divergence, None);
+    }
+
+    #[test]
+    fn first_divergence() {
+        let left = STEPS.join("\n");
+        let right = left.replace(r#"["0x1","0x2"]"#, r#"["0x1","0x3"]"#);
+        let divergence = diff(left.as_bytes(), right.as_bytes(), &options())
+            .unwrap()
+            .unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.kind, DivergenceKind::Stack);
+        assert_eq!(divergence.context.len(), 1);
+        assert_eq!(divergence.context[0].pc, 2);
+
+        let shorter = STEPS[..2].join("\n");
+        let divergence = diff(left.as_bytes(), shorter.as_bytes(), &options())
+            .unwrap()
+            .unwrap();
+        assert_eq!(divergence.kind, DivergenceKind::Length);
+        assert_eq!(divergence.right, None);
+    }
+
+    #[test]
+    fn state_root_divergence() {
+        let summary = |root: u8| {
+            format!(
+                r#"{{"stateRoot":"0x{root:064x}","output":"0x","energyUsed":"0x9","pass":true}}"#
+            )
+        };
+        let left = [STEPS[0].to_string(), summary(1)].join("\n");
+        let right = [STEPS[0].to_string(), summary(2)].join("\n");
+        let divergence = diff(left.as_bytes(), right.as_bytes(), &options())
+            .unwrap()
+            .unwrap();
+        assert_eq!(divergence.kind, DivergenceKind::StateRoot);
+        let root = revm::primitives::B256::with_last_byte(2);
+        assert!(divergence
+            .to_string()
+            .contains(&format!("stateRoot {root:?}")));
+
+        let options = DiffOptions {
+            ignore_state_root: true,
+            ..options()
+        };
+        let divergence = diff(left.as_bytes(), right.as_bytes(), &options).unwrap();
+        assert_eq!(divergence, None);
+    }
+}
@@ -0,0 +1,45 @@
+use libgoldilocks::{goldilocks::ed448_sign, SigningKey};
+use revm::primitives::{Address, B256};
+
+/// Recover the address from a private key (SigningKey).
+pub fn recover_address(private_key: &[u8]) -> Option<Address> {
+    let key = SigningKey::from_slice(private_key);
+    let public_key = key.verifying_key();
+    Some(Address::from_raw_public_key(&public_key.as_bytes()))
+}
+
+/// Sign the hash with an Ed448 private key.
+///
+/// Returns the 114 bytes long signature followed by the 57 bytes long public key, `None` if
+/// key is not 57 bytes long.
+pub fn sign_ed448(private_key: &[u8], hash: &B256) -> Option<[u8; 171]> {
+    let private_key: [u8; 57] = private_key.try_into().ok()?;
+    let mut public_key = [0u8; 57];
+    public_key.copy_from_slice(
+        &SigningKey::from_slice(&private_key)
+            .verifying_key()
+            .as_bytes(),
+    );
+    let signature = ed448_sign(&private_key, &public_key, hash.as_slice());
+
+    let mut out = [0u8; 171];
+    out[..114].copy_from_slice(&signature);
+    out[114..].copy_from_slice(&public_key);
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{address, hex};
+
+    #[test]
+    fn sanity_test() {
+        assert_eq!(
+            Some(address!("1afe4bd57060cb20be3da71729151922bcbf3947")),
+            recover_address(&hex!(
+                "445a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d85a915e4d060149eb4365960e6a7a45f334393093061110068"
+            ))
+        )
+    }
+}
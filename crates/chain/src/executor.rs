@@ -0,0 +1,151 @@
+use crate::{logs_bloom, trie::ordered_trie_root, Bloom, Receipt};
+use revm::{
+    db::{states::bundle_state::BundleRetention, EmptyDB},
+    inspector_handle_register,
+    inspectors::TracerEip3155,
+    primitives::{Address, EVMError, Env, ExecutionResult, HashMap, Output, SpecId, TxEnv, B256},
+    Database, DatabaseCommit, Evm, State,
+};
+use std::boxed::Box;
+use std::io::Write;
+use std::vec::Vec;
+
+/// Transaction executed by the [BlockExecutor].
+#[derive(Clone, Debug)]
+pub struct ExecutedTransaction {
+    pub energy_used: u64,
+    /// Address of the contract created by the transaction.
+    pub contract_address: Option<Address>,
+    pub receipt: Receipt,
+}
+
+/// Executes the transactions of a block one after another on top of the database.
+///
+/// Changes of every executed transaction are committed, a transaction that fails validation
+/// leaves the database unchanged.
+#[derive(Debug)]
+pub struct BlockExecutor<DB> {
+    db: DB,
+    env: Env,
+    spec_id: SpecId,
+    cumulative_energy_used: u64,
+    receipts: Vec<Receipt>,
+}
+
+impl<DB: Database + DatabaseCommit> BlockExecutor<DB> {
+    /// Creates the executor, `env` holds the configuration and the block environment.
+    pub fn new(db: DB, env: Env, spec_id: SpecId) -> Self {
+        Self {
+            db,
+            env,
+            spec_id,
+            cumulative_energy_used: 0,
+            receipts: Vec::new(),
+        }
+    }
+
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+
+    /// Energy used by the executed transactions.
+    pub fn cumulative_energy_used(&self) -> u64 {
+        self.cumulative_energy_used
+    }
+
+    /// Receipts of the executed transactions.
+    pub fn receipts(&self) -> &[Receipt] {
+        &self.receipts
+    }
+
+    /// Executes the transaction and commits its changes.
+    pub fn execute(
+        &mut self,
+        tx: TxEnv,
+        tx_type: u8,
+    ) -> Result<ExecutedTransaction, EVMError<DB::Error>> {
+        self.env.tx = tx;
+        let mut evm = Evm::builder()
+            .with_db(&mut self.db)
+            .modify_env(|e| **e = self.env.clone())
+            .with_spec_id(self.spec_id)
+            .build();
+        let result = evm.transact_commit();
+        drop(evm);
+        Ok(self.push_receipt(tx_type, result?))
+    }
+
+    /// Executes the transaction and commits its changes, writing the EIP-3155 trace to the
+    /// output.
+    pub fn execute_with_tracer(
+        &mut self,
+        tx: TxEnv,
+        tx_type: u8,
+        output: Box<dyn Write>,
+    ) -> Result<ExecutedTransaction, EVMError<DB::Error>> {
+        self.env.tx = tx;
+        let mut evm = Evm::builder()
+            .with_db(&mut self.db)
+            .modify_env(|e| **e = self.env.clone())
+            .with_spec_id(self.spec_id)
+            .with_external_context(TracerEip3155::new(output))
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm.transact_commit();
+        drop(evm);
+        Ok(self.push_receipt(tx_type, result?))
+    }
+
+    fn push_receipt(&mut self, tx_type: u8, result: ExecutionResult) -> ExecutedTransaction {
+        let energy_used = result.energy_used();
+        self.cumulative_energy_used += energy_used;
+        let contract_address = match &result {
+            ExecutionResult::Success {
+                output: Output::Create(_, address),
+                ..
+            } => *address,
+            _ => None,
+        };
+        let receipt = Receipt {
+            tx_type,
+            success: result.is_success(),
+            cumulative_energy_used: self.cumulative_energy_used,
+            logs: result.into_logs(),
+        };
+        self.receipts.push(receipt.clone());
+        ExecutedTransaction {
+            energy_used,
+            contract_address,
+            receipt,
+        }
+    }
+
+    /// Root of the trie of the receipts.
+    pub fn receipts_root(&self) -> B256 {
+        ordered_trie_root(self.receipts.iter().map(Receipt::encode))
+    }
+
+    /// Bloom of the logs of all executed transactions.
+    pub fn logs_bloom(&self) -> Bloom {
+        logs_bloom(self.receipts.iter().flat_map(|receipt| &receipt.logs))
+    }
+}
+
+impl BlockExecutor<&mut State<EmptyDB>> {
+    /// Credits the rewards and the withdrawals and merges the transitions of the block.
+    ///
+    /// Withdrawals are pairs of the address and the amount in Gwei.
+    pub fn finalize(
+        &mut self,
+        mut balances: HashMap<Address, u128>,
+        withdrawals: impl IntoIterator<Item = (Address, u64)>,
+    ) {
+        for (address, amount) in withdrawals {
+            *balances.entry(address).or_default() += u128::from(amount) * 1_000_000_000;
+        }
+        balances.retain(|_, amount| *amount != 0);
+        // database is empty so loading the accounts can't fail.
+        self.db.increment_balances(balances).unwrap();
+        self.db.merge_transitions(BundleRetention::PlainState);
+    }
+}
@@ -0,0 +1,16 @@
+//! # revm-chain
+//!
+//! Execution of blocks of Core chains shared by the revm binaries: signed transactions,
+//! receipts and the trie roots of the block header.
+#![warn(rustdoc::all)]
+#![deny(unused_must_use, rust_2018_idioms)]
+
+pub mod ed448;
+mod executor;
+mod receipt;
+mod transaction;
+pub mod trie;
+
+pub use executor::{BlockExecutor, ExecutedTransaction};
+pub use receipt::{logs_bloom, Bloom, Receipt};
+pub use transaction::SignedTransaction;
@@ -0,0 +1,79 @@
+use alloy_rlp::Encodable;
+use revm::primitives::{sha3, FixedBytes, Log};
+use std::vec::Vec;
+
+/// Logs bloom of the block header.
+pub type Bloom = FixedBytes<256>;
+
+/// Outcome of a transaction needed to build its receipt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Receipt {
+    pub tx_type: u8,
+    pub success: bool,
+    pub cumulative_energy_used: u64,
+    pub logs: Vec<Log>,
+}
+
+impl Receipt {
+    /// Encodes the receipt as `rlp([status, cumulative_energy_used, bloom, logs])`,
+    /// prefixed by the transaction type if it is not a legacy transaction.
+    pub fn encode(&self) -> Vec<u8> {
+        let bloom = logs_bloom(&self.logs);
+        let fields: [&dyn Encodable; 4] = [
+            &self.success,
+            &self.cumulative_energy_used,
+            &bloom,
+            &self.logs,
+        ];
+        let mut out = Vec::new();
+        if self.tx_type != 0 {
+            out.push(self.tx_type);
+        }
+        alloy_rlp::encode_list::<_, dyn Encodable>(&fields, &mut out);
+        out
+    }
+}
+
+/// Computes logs bloom, every address and topic sets three bits selected by its hash.
+pub fn logs_bloom<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Bloom {
+    let mut bloom = Bloom::ZERO;
+    let mut accrue = |input: &[u8]| {
+        let hash = sha3(input);
+        for i in [0, 2, 4] {
+            let bit = ((usize::from(hash[i]) << 8) | usize::from(hash[i + 1])) & 2047;
+            bloom[255 - bit / 8] |= 1 << (bit % 8);
+        }
+    };
+    for log in logs {
+        accrue(log.address.as_slice());
+        for topic in log.topics() {
+            accrue(topic.as_slice());
+        }
+    }
+    bloom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{address, b256, Bytes};
+
+    #[test]
+    fn empty_logs_bloom() {
+        assert_eq!(logs_bloom(&[]), Bloom::ZERO);
+    }
+
+    #[test]
+    fn logs_bloom_sets_three_bits_per_entry() {
+        let log = Log::new_unchecked(
+            address!("0000000000000000000000000000000000000001"),
+            vec![b256!(
+                "0000000000000000000000000000000000000000000000000000000000000002"
+            )],
+            Bytes::new(),
+        );
+        let bloom = logs_bloom([&log]);
+        let bits: u32 = bloom.iter().map(|byte| byte.count_ones()).sum();
+        assert!(bits > 0 && bits <= 6);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::ed448::sign_ed448;
+use alloy_rlp::{Encodable, RlpDecodable, RlpEncodable};
+use libgoldilocks::goldilocks::ed448_verify_with_error;
+use revm::{
+    interpreter::CreateScheme,
+    primitives::{sha3, Address, Bytes, IcanAddress, TransactTo, TxEnv, B256, U256},
+};
+use std::string::String;
+use std::vec::Vec;
+
+/// Ed448 signed Core transaction.
+///
+/// Transaction is encoded as `rlp([nonce, energy_price, energy_limit, to, value, data,
+/// network_id, signature])` where `signature` is signature of the hash of the fields before
+/// it, followed by the public key.
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+pub struct SignedTransaction {
+    pub nonce: u64,
+    pub energy_price: U256,
+    pub energy_limit: u64,
+    /// ICAN address of the receiver, empty for contract creation.
+    pub to: Bytes,
+    pub value: U256,
+    pub data: Bytes,
+    pub network_id: u64,
+    pub signature: Bytes,
+}
+
+impl SignedTransaction {
+    /// Creates the transaction with an empty signature.
+    pub fn unsigned(tx: &TxEnv, nonce: u64, network_id: u64) -> Self {
+        let to = match tx.transact_to {
+            TransactTo::Call(address) => {
+                Bytes::copy_from_slice(address.to_ican(network_id).as_slice())
+            }
+            TransactTo::Create(_) => Bytes::new(),
+        };
+        Self {
+            nonce,
+            energy_price: tx.energy_price,
+            energy_limit: tx.energy_limit,
+            to,
+            value: tx.value,
+            data: tx.data.clone(),
+            network_id,
+            signature: Bytes::new(),
+        }
+    }
+
+    /// Hash of the fields without the signature.
+    pub fn signing_hash(&self) -> B256 {
+        let fields: [&dyn Encodable; 7] = [
+            &self.nonce,
+            &self.energy_price,
+            &self.energy_limit,
+            &self.to,
+            &self.value,
+            &self.data,
+            &self.network_id,
+        ];
+        let mut out = Vec::new();
+        alloy_rlp::encode_list::<_, dyn Encodable>(&fields, &mut out);
+        sha3(&out)
+    }
+
+    /// Signs the transaction, `None` if secret key is not an Ed448 private key.
+    pub fn sign(mut self, secret_key: &[u8]) -> Option<Self> {
+        let signature = sign_ed448(secret_key, &self.signing_hash())?;
+        self.signature = Bytes::copy_from_slice(&signature);
+        Some(self)
+    }
+
+    /// Hash of the encoded transaction.
+    pub fn hash(&self) -> B256 {
+        sha3(alloy_rlp::encode(self))
+    }
+
+    /// Verifies the signature and returns the address of the public key that signed it.
+    pub fn recover_sender(&self) -> Result<Address, String> {
+        if self.signature.len() != 171 {
+            return Err(format!(
+                "signature is {} bytes long, expected 171",
+                self.signature.len()
+            ));
+        }
+        let mut signature = [0u8; 114];
+        let mut public_key = [0u8; 57];
+        signature.copy_from_slice(&self.signature[..114]);
+        public_key.copy_from_slice(&self.signature[114..]);
+        ed448_verify_with_error(&public_key, &signature, self.signing_hash().as_slice())
+            .map_err(|e| format!("invalid signature: {e:?}"))?;
+        Ok(Address::from_raw_public_key(&public_key))
+    }
+
+    /// Receiver of the transaction, `None` for contract creation.
+    pub fn receiver(&self) -> Result<Option<Address>, String> {
+        match self.to.len() {
+            0 => Ok(None),
+            len if len == IcanAddress::len_bytes() => {
+                Ok(Some(IcanAddress::from_slice(&self.to).to_address()))
+            }
+            len => Err(format!("receiver is {len} bytes long")),
+        }
+    }
+
+    /// Transaction environment of the transaction sent by the caller.
+    pub fn tx_env(&self, caller: Address) -> Result<TxEnv, String> {
+        let transact_to = match self.receiver()? {
+            Some(address) => TransactTo::Call(address),
+            None => TransactTo::Create(CreateScheme::Create),
+        };
+        Ok(TxEnv {
+            caller,
+            energy_limit: self.energy_limit,
+            energy_price: self.energy_price,
+            transact_to,
+            value: self.value,
+            data: self.data.clone(),
+            nonce: Some(self.nonce),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ed448::recover_address;
+    use alloy_rlp::Decodable;
+    use revm::primitives::hex;
+
+    #[test]
+    fn sign_and_recover() {
+        let key = hex!("445a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d85a915e4d060149eb4365960e6a7a45f334393093061110068");
+        let tx = TxEnv {
+            transact_to: TransactTo::Create(CreateScheme::Create),
+            energy_limit: 21_000,
+            ..Default::default()
+        };
+        let tx = SignedTransaction::unsigned(&tx, 0, 1).sign(&key).unwrap();
+        assert_eq!(tx.recover_sender().ok(), recover_address(&key));
+
+        let encoded = alloy_rlp::encode(&tx);
+        let decoded = SignedTransaction::decode(&mut encoded.as_slice()).unwrap();
+        assert_eq!(decoded, tx);
+        assert_eq!(decoded.receiver(), Ok(None));
+    }
+}
@@ -0,0 +1,143 @@
+use alloy_rlp::{RlpEncodable, RlpMaxEncodedLen};
+use hash_db::Hasher;
+use plain_hasher::PlainHasher;
+use revm::{
+    db::PlainAccount,
+    primitives::{sha3, Address, Log, B256, U256},
+};
+use std::vec::Vec;
+use triehash::{ordered_trie_root as triehash_ordered_trie_root, sec_trie_root};
+
+pub fn log_rlp_hash(logs: &[Log]) -> B256 {
+    let mut out = Vec::with_capacity(alloy_rlp::list_length(logs));
+    alloy_rlp::encode_list(logs, &mut out);
+    sha3(&out)
+}
+
+pub fn state_merkle_trie_root<'a>(
+    accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>,
+) -> B256 {
+    trie_root(accounts.into_iter().map(|(address, acc)| {
+        (
+            address,
+            alloy_rlp::encode_fixed_size(&TrieAccount::new(acc)),
+        )
+    }))
+}
+
+#[derive(RlpEncodable, RlpMaxEncodedLen)]
+struct TrieAccount {
+    nonce: u64,
+    balance: U256,
+    root_hash: B256,
+    code_hash: B256,
+}
+
+impl TrieAccount {
+    fn new(acc: &PlainAccount) -> Self {
+        Self {
+            nonce: acc.info.nonce,
+            balance: acc.info.balance,
+            root_hash: sec_trie_root::<ShaHasher, _, _, _>(
+                acc.storage
+                    .iter()
+                    .filter(|(_k, &v)| v != U256::ZERO)
+                    .map(|(k, v)| (k.to_be_bytes::<32>(), alloy_rlp::encode_fixed_size(v))),
+            ),
+            code_hash: acc.info.code_hash,
+        }
+    }
+}
+
+/// Root of the trie keyed by the rlp encoded index of the item, as used for receipts.
+pub fn ordered_trie_root<I>(input: I) -> B256
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    triehash_ordered_trie_root::<ShaHasher, _>(input)
+}
+
+#[inline]
+pub fn trie_root<I, A, B>(input: I) -> B256
+where
+    I: IntoIterator<Item = (A, B)>,
+    A: AsRef<[u8]>,
+    B: AsRef<[u8]>,
+{
+    sec_trie_root::<ShaHasher, _, _, _>(input)
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShaHasher;
+
+impl Hasher for ShaHasher {
+    type Out = B256;
+    type StdHasher = PlainHasher;
+    const LENGTH: usize = 32;
+
+    #[inline]
+    fn hash(x: &[u8]) -> Self::Out {
+        sha3(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{
+        db::{InMemoryDB, StateDump},
+        primitives::{AccountInfo, Bytecode, Bytes},
+    };
+
+    fn state_root(db: &InMemoryDB) -> B256 {
+        let accounts: Vec<(Address, PlainAccount)> = db
+            .accounts
+            .iter()
+            .map(|(address, account)| {
+                let account = PlainAccount {
+                    info: account.info.clone(),
+                    storage: account.storage.clone(),
+                };
+                (*address, account)
+            })
+            .collect();
+        state_merkle_trie_root(accounts.iter().map(|(address, acc)| (*address, acc)))
+    }
+
+    #[test]
+    fn dump_roundtrip_keeps_state_root() {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            Address::with_last_byte(1),
+            AccountInfo::from_balance(U256::from(10)),
+        );
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55]));
+        db.insert_account_info(
+            Address::with_last_byte(2),
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        db.insert_account_storage(Address::with_last_byte(2), U256::from(1), U256::from(2))
+            .unwrap();
+        // contract whose code was never loaded.
+        db.insert_account_info(
+            Address::with_last_byte(3),
+            AccountInfo {
+                nonce: 1,
+                code_hash: sha3([0x00]),
+                ..Default::default()
+            },
+        );
+        let root = state_root(&db);
+
+        let dump = StateDump::from_cache_db(&db);
+        let from_json: StateDump = dump.to_json().parse().unwrap();
+        assert_eq!(state_root(&from_json.in_memory_db()), root);
+        let from_bytes = StateDump::from_bytes(&dump.to_bytes()).unwrap();
+        assert_eq!(state_root(&from_bytes.in_memory_db()), root);
+        assert_eq!(
+            state_merkle_trie_root(from_bytes.cache_state(true).trie_account()),
+            root
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use super::constants::*;
 use crate::inner_models::SelfDestructResult;
-use crate::primitives::{Address, SpecId, U256};
+use crate::primitives::{Address, IntrinsicEnergy, SpecId, U256};
 use std::vec::Vec;
 
 /// `const` Option `?`.
@@ -380,14 +380,24 @@ pub fn validate_initial_tx_energy(
     is_create: bool,
     access_list: &[(Address, Vec<U256>)],
 ) -> u64 {
-    let mut initial_energy = 0;
+    initial_tx_energy_breakdown(spec_id, input, is_create, access_list).total()
+}
+
+/// Initial energy of the transaction split by its components.
+pub fn initial_tx_energy_breakdown(
+    spec_id: SpecId,
+    input: &[u8],
+    is_create: bool,
+    access_list: &[(Address, Vec<U256>)],
+) -> IntrinsicEnergy {
+    let mut initial_energy = IntrinsicEnergy::default();
     let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
     let non_zero_data_len = input.len() as u64 - zero_data_len;
 
     // initdate stipend
-    initial_energy += zero_data_len * TRANSACTION_ZERO_DATA;
+    initial_energy.calldata += zero_data_len * TRANSACTION_ZERO_DATA;
     // EIP-2028: Transaction data energy cost reduction
-    initial_energy += non_zero_data_len
+    initial_energy.calldata += non_zero_data_len
         * if spec_id.is_enabled_in(SpecId::ISTANBUL) {
             16
         } else {
@@ -399,26 +409,21 @@ pub fn validate_initial_tx_energy(
         let accessed_slots = access_list
             .iter()
             .fold(0, |slot_count, (_, slots)| slot_count + slots.len() as u64);
-        initial_energy += access_list.len() as u64 * ACCESS_LIST_ADDRESS;
-        initial_energy += accessed_slots * ACCESS_LIST_STORAGE_KEY;
+        initial_energy.access_list += access_list.len() as u64 * ACCESS_LIST_ADDRESS;
+        initial_energy.access_list += accessed_slots * ACCESS_LIST_STORAGE_KEY;
     }
 
     // base stipend
-    initial_energy += if is_create {
-        if spec_id.is_enabled_in(SpecId::HOMESTEAD) {
-            // EIP-2: Homestead Hard-fork Changes
-            53000
-        } else {
-            21000
-        }
-    } else {
-        21000
-    };
+    initial_energy.base = 21000;
+    if is_create && spec_id.is_enabled_in(SpecId::HOMESTEAD) {
+        // EIP-2: Homestead Hard-fork Changes
+        initial_energy.create = 32000;
+    }
 
     // EIP-3860: Limit and meter initcode
     // Initcode stipend for bytecode analysis
     if spec_id.is_enabled_in(SpecId::SHANGHAI) && is_create {
-        initial_energy += initcode_cost(input.len() as u64)
+        initial_energy.initcode += initcode_cost(input.len() as u64)
     }
 
     initial_energy
@@ -50,6 +50,15 @@ pub trait Host {
 
     /// Mark `address` to be deleted, with funds transferred to `target`.
     fn selfdestruct(&mut self, address: Address, target: Address) -> Option<SelfDestructResult>;
+
+    /// Returns true if the execution should be stopped.
+    ///
+    /// Checked on every jump, the interrupted instruction halts with
+    /// [`InstructionResult::FatalExternalError`](crate::InstructionResult::FatalExternalError).
+    /// Execution is never interrupted by default.
+    fn is_interrupted(&mut self) -> bool {
+        false
+    }
 }
 
 /// Represents the result of an `sstore` operation.
@@ -44,6 +44,10 @@ pub enum InstructionResult {
     CreateContractStartingWithEF,
     /// EIP-3860: Limit and meter initcode. Initcode size limit exceeded.
     CreateInitCodeSizeLimit,
+    /// Instruction limit of the transaction reached.
+    InstructionLimit,
+    /// Call frame limit of the transaction reached.
+    CallFrameLimit,
 
     /// Fatal external error. Returned by database.
     FatalExternalError,
@@ -82,6 +86,8 @@ impl From<HaltReason> for InstructionResult {
             HaltReason::CreateContractSizeLimit => Self::CreateContractSizeLimit,
             HaltReason::CreateContractStartingWithEF => Self::CreateContractStartingWithEF,
             HaltReason::CreateInitCodeSizeLimit => Self::CreateInitCodeSizeLimit,
+            HaltReason::InstructionLimit => Self::InstructionLimit,
+            HaltReason::CallFrameLimit => Self::CallFrameLimit,
             HaltReason::OverflowPayment => Self::OverflowPayment,
             HaltReason::StateChangeDuringStaticCall => Self::StateChangeDuringStaticCall,
             HaltReason::CallNotAllowedInsideStatic => Self::CallNotAllowedInsideStatic,
@@ -134,6 +140,8 @@ macro_rules! return_error {
             | InstructionResult::CreateContractSizeLimit
             | InstructionResult::CreateContractStartingWithEF
             | InstructionResult::CreateInitCodeSizeLimit
+            | InstructionResult::InstructionLimit
+            | InstructionResult::CallFrameLimit
             | InstructionResult::FatalExternalError
     };
 }
@@ -258,6 +266,8 @@ impl From<InstructionResult> for SuccessOrHalt {
             InstructionResult::CreateInitCodeSizeLimit => {
                 Self::Halt(HaltReason::CreateInitCodeSizeLimit)
             }
+            InstructionResult::InstructionLimit => Self::Halt(HaltReason::InstructionLimit),
+            InstructionResult::CallFrameLimit => Self::Halt(HaltReason::CallFrameLimit),
             InstructionResult::FatalExternalError => Self::FatalExternalError,
         }
     }
@@ -326,6 +336,8 @@ mod tests {
             InstructionResult::CreateContractSizeLimit,
             InstructionResult::CreateContractStartingWithEF,
             InstructionResult::CreateInitCodeSizeLimit,
+            InstructionResult::InstructionLimit,
+            InstructionResult::CallFrameLimit,
             InstructionResult::FatalExternalError,
         ];
 
@@ -4,14 +4,16 @@ use crate::{
     Host, InstructionResult, Interpreter, InterpreterResult,
 };
 
-pub fn jump<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn jump<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     energy!(interpreter, energy::MID);
+    check_interrupt!(interpreter, host);
     pop!(interpreter, dest);
     jump_inner(interpreter, dest);
 }
 
-pub fn jumpi<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
+pub fn jumpi<H: Host + ?Sized>(interpreter: &mut Interpreter, host: &mut H) {
     energy!(interpreter, energy::HIGH);
+    check_interrupt!(interpreter, host);
     pop!(interpreter, dest, value);
     if value != U256::ZERO {
         jump_inner(interpreter, dest);
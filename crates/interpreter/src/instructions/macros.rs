@@ -11,6 +11,17 @@ macro_rules! check_staticcall {
     };
 }
 
+/// Fails the instruction if the host requested to stop the execution.
+#[macro_export]
+macro_rules! check_interrupt {
+    ($interp:expr, $host:expr) => {
+        if $host.is_interrupted() {
+            $interp.instruction_result = $crate::InstructionResult::FatalExternalError;
+            return;
+        }
+    };
+}
+
 /// Fails the instruction if the `min` is not enabled in `SPEC`.
 #[macro_export]
 macro_rules! check {
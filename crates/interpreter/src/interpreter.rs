@@ -14,7 +14,7 @@ use crate::{
     CreateInputs, CreateOutcome, Energy, Host, InstructionResult,
 };
 use core::cmp::min;
-use revm_primitives::U256;
+use revm_primitives::{RevertReason, U256};
 
 /// EVM bytecode interpreter.
 #[derive(Debug)]
@@ -48,6 +48,11 @@ pub struct Interpreter {
     /// Set inside CALL or CREATE instructions and RETURN or REVERT instructions. Additionally those instructions will set
     /// InstructionResult to CallOrCreate/Return/Revert so we know the reason.
     pub next_action: InterpreterAction,
+    /// Number of instructions executed by the last [Interpreter::run_with_instruction_limit].
+    pub instruction_count: u64,
+    /// Maximum number of instructions executed by one [Interpreter::run_with_instruction_limit],
+    /// the interpreter halts with [InstructionResult::InstructionLimit] when it is reached.
+    pub instruction_limit: u64,
 }
 
 /// The result of an interpreter operation.
@@ -125,6 +130,8 @@ impl Interpreter {
             shared_memory: EMPTY_SHARED_MEMORY,
             stack: Stack::new(),
             next_action: InterpreterAction::None,
+            instruction_count: 0,
+            instruction_limit: u64::MAX,
         }
     }
 
@@ -320,7 +327,37 @@ impl Interpreter {
         while self.instruction_result == InstructionResult::Continue {
             self.step(instruction_table, host);
         }
+        self.take_next_action()
+    }
+
+    /// Executes the interpreter like [Interpreter::run], counting the executed instructions
+    /// into [Interpreter::instruction_count] and halting once [Interpreter::instruction_limit]
+    /// is reached.
+    pub fn run_with_instruction_limit<FN, H: Host + ?Sized>(
+        &mut self,
+        shared_memory: SharedMemory,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        self.next_action = InterpreterAction::None;
+        self.shared_memory = shared_memory;
+        self.instruction_count = 0;
+        while self.instruction_result == InstructionResult::Continue {
+            if self.instruction_count == self.instruction_limit {
+                self.instruction_result = InstructionResult::InstructionLimit;
+                break;
+            }
+            self.instruction_count += 1;
+            self.step(instruction_table, host);
+        }
+        self.take_next_action()
+    }
 
+    /// Action the interpreter stopped with.
+    fn take_next_action(&mut self) -> InterpreterAction {
         // Return next action if it is some.
         if self.next_action.is_some() {
             return core::mem::take(&mut self.next_action);
@@ -355,6 +392,12 @@ impl InterpreterResult {
     pub const fn is_error(&self) -> bool {
         self.result.is_error()
     }
+
+    /// Decodes the `Error(string)` or `Panic(uint256)` of the output if the result is a revert.
+    #[inline]
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        self.is_revert().then(|| RevertReason::decode(&self.output))
+    }
 }
 
 #[cfg(test)]
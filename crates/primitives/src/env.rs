@@ -267,6 +267,20 @@ pub struct CfgEnv {
     /// If some it will effects EIP-170: Contract code size limit. Useful to increase this because of tests.
     /// By default it is 0x6000 (~25kb).
     pub limit_contract_code_size: Option<usize>,
+    /// Records the location of the halt or revert with the parent frames into
+    /// [`ResultAndState::failure_location`](crate::ResultAndState::failure_location).
+    /// By default, it is set to `false`.
+    pub debug: bool,
+    /// Maximum number of instructions executed by the transaction in all frames. Interpreter
+    /// halts with [`HaltReason::InstructionLimit`](crate::HaltReason::InstructionLimit) when it
+    /// is reached.
+    /// By default, it is set to `None` (unlimited).
+    pub instruction_limit: Option<u64>,
+    /// Maximum number of call frames created by the transaction, including the first frame and
+    /// the calls of precompiles. Call above the limit halts every frame up to the first one with
+    /// [`HaltReason::CallFrameLimit`](crate::HaltReason::CallFrameLimit).
+    /// By default, it is set to `None` (unlimited).
+    pub frame_limit: Option<u64>,
     /// A hard memory limit in bytes beyond which [crate::result::OutOfEnergyError::Memory] cannot be resized.
     ///
     /// In cases where the energy limit may be extraordinarily high, it is recommended to set this to
@@ -371,6 +385,9 @@ impl Default for CfgEnv {
             network_id: 1,
             perf_analyse_created_bytecodes: AnalysisKind::default(),
             limit_contract_code_size: None,
+            debug: false,
+            instruction_limit: None,
+            frame_limit: None,
             #[cfg(feature = "c-kzg")]
             kzg_settings: crate::kzg::EnvKzgSettings::Default,
             #[cfg(feature = "memory_limit")]
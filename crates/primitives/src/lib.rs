@@ -17,12 +17,13 @@ pub mod env;
 pub mod kzg;
 pub mod precompile;
 pub mod result;
+pub mod revert;
 pub mod specification;
 pub mod state;
 pub mod utilities;
 pub use alloy_primitives::{
-    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, IcanAddress, Bytes,
-    FixedBytes, Log, LogData, B1368, B256, I256, U256,
+    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, Bytes,
+    FixedBytes, IcanAddress, Log, LogData, B1368, B256, I256, U256,
 };
 pub use bitvec;
 pub use bytecode::*;
@@ -42,6 +43,7 @@ cfg_if::cfg_if! {
 pub use kzg::{EnvKzgSettings, KzgSettings};
 pub use precompile::*;
 pub use result::*;
+pub use revert::*;
 pub use specification::*;
 pub use state::*;
 pub use utilities::*;
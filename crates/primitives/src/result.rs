@@ -1,4 +1,4 @@
-use crate::{Address, Bytes, Log, State, U256};
+use crate::{Address, Bytes, FixedBytes, Log, RevertReason, State, U256};
 use core::fmt;
 use std::{boxed::Box, string::String, vec::Vec};
 
@@ -15,6 +15,150 @@ pub struct ResultAndState {
     pub result: ExecutionResult,
     /// State that got updated
     pub state: State,
+    /// Energy used split by its sources, only set if the breakdown was enabled
+    /// with `energy_breakdown_handle_register`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub energy_breakdown: Option<EnergyBreakdown>,
+    /// Location of the halt or revert with the call stack, only set if the
+    /// [`CfgEnv::debug`](crate::CfgEnv::debug) is enabled.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub failure_location: Option<FailureLocation>,
+    /// Instructions and call frames executed by the transaction.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub execution_stats: ExecutionStats,
+}
+
+/// Counters of the executed transaction, limited by the
+/// [`CfgEnv::instruction_limit`](crate::CfgEnv::instruction_limit) and
+/// [`CfgEnv::frame_limit`](crate::CfgEnv::frame_limit).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionStats {
+    /// Instructions executed in all frames, `None` if they were not counted as
+    /// [`CfgEnv::instruction_limit`](crate::CfgEnv::instruction_limit) is not set.
+    pub instructions: Option<u64>,
+    /// Call frames created, including the first frame and the calls of precompiles.
+    pub frames: u64,
+}
+
+/// Energy used by the transaction split by its sources.
+///
+/// `energy_used` of the [ExecutionResult] is the sum of the components without the `refunded`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnergyBreakdown {
+    /// Energy charged before the execution.
+    pub intrinsic: IntrinsicEnergy,
+    /// Instructions and call overhead not included in the other components.
+    pub execution: u64,
+    /// Memory expansion of all frames.
+    pub memory: u64,
+    /// `SLOAD` and `SSTORE` instructions.
+    pub storage: StorageEnergy,
+    /// Calls of the precompiles.
+    pub precompile: u64,
+    /// Deposit of the created contracts code.
+    pub code_deposit: u64,
+    /// Refund returned to the caller.
+    pub refunded: u64,
+    /// Refund above the cap that was not returned.
+    pub refund_capped: u64,
+}
+
+/// Intrinsic energy of the transaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntrinsicEnergy {
+    /// Base cost of every transaction.
+    pub base: u64,
+    /// Zero and non-zero bytes of the input.
+    pub calldata: u64,
+    /// Additional cost of the contract creation.
+    pub create: u64,
+    /// Addresses and storage keys of the access list.
+    pub access_list: u64,
+    /// EIP-3860 words of the initcode.
+    pub initcode: u64,
+}
+
+impl IntrinsicEnergy {
+    /// Total intrinsic energy.
+    pub const fn total(&self) -> u64 {
+        self.base + self.calldata + self.create + self.access_list + self.initcode
+    }
+}
+
+/// Energy of the storage instructions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageEnergy {
+    pub cold_sload: u64,
+    pub warm_sload: u64,
+    pub cold_sstore: u64,
+    pub warm_sstore: u64,
+}
+
+impl StorageEnergy {
+    /// Total energy of the storage instructions.
+    pub const fn total(&self) -> u64 {
+        self.cold_sload + self.warm_sload + self.cold_sstore + self.warm_sstore
+    }
+}
+
+/// Location of the instruction in the call stack.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameLocation {
+    /// Address of the executed contract.
+    pub address: Address,
+    /// Program counter of the instruction.
+    pub pc: usize,
+    /// Opcode of the instruction.
+    pub opcode: u8,
+    /// Depth of the frame, the first frame has depth `0`.
+    pub depth: usize,
+    /// Selector of the called function, `None` if the input is shorter than 4 bytes.
+    pub selector: Option<FixedBytes<4>>,
+}
+
+/// Location of the instruction that halted or reverted the execution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FailureLocation {
+    /// Frame that failed first, a revert forwarded by the callers keeps the innermost frame.
+    pub frame: FrameLocation,
+    /// Parent frames from the outermost, their `pc` points to the call or create instruction.
+    pub call_stack: Vec<FrameLocation>,
+}
+
+impl fmt::Display for FrameLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} pc {} opcode {:#04x} depth {}",
+            self.address, self.pc, self.opcode, self.depth
+        )?;
+        if let Some(selector) = self.selector {
+            write!(f, " selector {selector}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FailureLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.frame)?;
+        for frame in self.call_stack.iter().rev() {
+            write!(f, "\n  called from {frame}")?;
+        }
+        Ok(())
+    }
 }
 
 /// Result of a transaction execution.
@@ -74,6 +218,16 @@ impl ExecutionResult {
         }
     }
 
+    /// Decodes the `Error(string)` or `Panic(uint256)` of the reverted execution.
+    ///
+    /// Returns `None` if the execution was not reverted.
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        match self {
+            Self::Revert { output, .. } => Some(RevertReason::decode(output)),
+            _ => None,
+        }
+    }
+
     /// Returns the logs if execution is successful, or an empty list otherwise.
     pub fn logs(&self) -> &[Log] {
         match self {
@@ -144,6 +298,8 @@ pub enum EVMError<DBError> {
     Header(InvalidHeader),
     /// Database error.
     Database(DBError),
+    /// Execution was stopped by the interrupt before it finished.
+    Interrupted,
     /// Custom error.
     ///
     /// Useful for handler registers where custom logic would want to return their own custom error.
@@ -157,7 +313,7 @@ impl<DBError: std::error::Error + 'static> std::error::Error for EVMError<DBErro
             Self::Transaction(e) => Some(e),
             Self::Header(e) => Some(e),
             Self::Database(e) => Some(e),
-            Self::Custom(_) => None,
+            Self::Interrupted | Self::Custom(_) => None,
         }
     }
 }
@@ -168,6 +324,7 @@ impl<DBError: fmt::Display> fmt::Display for EVMError<DBError> {
             Self::Transaction(e) => write!(f, "transaction validation error: {e}"),
             Self::Header(e) => write!(f, "header validation error: {e}"),
             Self::Database(e) => write!(f, "database error: {e}"),
+            Self::Interrupted => f.write_str("execution interrupted"),
             Self::Custom(e) => f.write_str(e),
         }
     }
@@ -407,6 +564,10 @@ pub enum HaltReason {
     CreateContractStartingWithEF,
     /// EIP-3860: Limit and meter initcode. Initcode size limit exceeded.
     CreateInitCodeSizeLimit,
+    /// [`CfgEnv::instruction_limit`](crate::CfgEnv::instruction_limit) reached.
+    InstructionLimit,
+    /// [`CfgEnv::frame_limit`](crate::CfgEnv::frame_limit) reached.
+    CallFrameLimit,
 
     /* Internal Halts that can be only found inside Inspector */
     OverflowPayment,
@@ -0,0 +1,139 @@
+//! Decoding of the revert data of the `Error(string)` and `Panic(uint256)` errors.
+
+use crate::{sha3, Bytes, U256};
+use core::fmt;
+use std::string::String;
+
+/// Signature of the error used by `revert("message")` and `require(condition, "message")`.
+pub const ERROR_SIGNATURE: &str = "Error(string)";
+
+/// Signature of the error raised by the compiler inserted checks, e.g. arithmetic overflow.
+pub const PANIC_SIGNATURE: &str = "Panic(uint256)";
+
+/// Selector of the error with the signature.
+#[inline]
+pub fn error_selector(signature: &str) -> [u8; 4] {
+    let hash = sha3(signature);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Description of the ylem panic code.
+pub fn panic_description(code: U256) -> Option<&'static str> {
+    let description = match u8::try_from(code).ok()? {
+        0x00 => "generic compiler inserted panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic underflow or overflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "conversion into non-existent enum variant",
+        0x22 => "access to incorrectly encoded storage byte array",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "memory allocation overflow",
+        0x51 => "call to zero-initialized function",
+        _ => return None,
+    };
+    Some(description)
+}
+
+/// Decoded revert data.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RevertReason {
+    /// Reverted without data.
+    Empty,
+    /// `Error(string)` with the message.
+    Error(String),
+    /// `Panic(uint256)` with the panic code.
+    Panic(U256),
+    /// Custom error or other data, decodable only with the ABI of the contract.
+    Custom(Bytes),
+}
+
+impl RevertReason {
+    /// Decodes the standard errors of the revert data.
+    pub fn decode(output: &[u8]) -> Self {
+        if output.is_empty() {
+            return Self::Empty;
+        }
+        if let Some(data) = output.strip_prefix(&error_selector(ERROR_SIGNATURE)) {
+            if let Some(message) = decode_string(data) {
+                return Self::Error(message);
+            }
+        }
+        if let Some(data) = output.strip_prefix(&error_selector(PANIC_SIGNATURE)) {
+            if data.len() == 32 {
+                return Self::Panic(U256::from_be_slice(data));
+            }
+        }
+        Self::Custom(Bytes::copy_from_slice(output))
+    }
+
+    /// Returns the message of the `Error(string)`.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Self::Error(message) => Some(message),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("reverted without data"),
+            Self::Error(message) => f.write_str(message),
+            Self::Panic(code) => match panic_description(*code) {
+                Some(description) => write!(f, "panic: {description} ({code:#x})"),
+                None => write!(f, "panic: unknown code {code:#x}"),
+            },
+            Self::Custom(data) => write!(f, "custom error {data}"),
+        }
+    }
+}
+
+/// ABI decodes the single `string` parameter.
+fn decode_string(data: &[u8]) -> Option<String> {
+    let word = |index: usize| -> Option<usize> {
+        let word = data.get(index..index.checked_add(32)?)?;
+        usize::try_from(U256::from_be_slice(word)).ok()
+    };
+    let offset = word(0)?;
+    let len = word(offset)?;
+    let start = offset.checked_add(32)?;
+    let bytes = data.get(start..start.checked_add(len)?)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn decode_error_and_panic() {
+        let mut error = error_selector(ERROR_SIGNATURE).to_vec();
+        error.extend(U256::from(32).to_be_bytes::<32>());
+        error.extend(U256::from(5).to_be_bytes::<32>());
+        error.extend(*b"oops!");
+        error.extend([0u8; 27]);
+        assert_eq!(
+            RevertReason::decode(&error),
+            RevertReason::Error("oops!".into())
+        );
+
+        let mut panic: Vec<u8> = error_selector(PANIC_SIGNATURE).to_vec();
+        panic.extend(U256::from(0x11).to_be_bytes::<32>());
+        let reason = RevertReason::decode(&panic);
+        assert_eq!(reason, RevertReason::Panic(U256::from(0x11)));
+        assert_eq!(
+            reason.to_string(),
+            "panic: arithmetic underflow or overflow (0x11)"
+        );
+
+        assert_eq!(RevertReason::decode(&[]), RevertReason::Empty);
+        assert_eq!(
+            RevertReason::decode(&[1, 2, 3, 4]),
+            RevertReason::Custom(Bytes::from_static(&[1, 2, 3, 4]))
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Decoding of the custom ylem errors with the ABI JSON of the contract.
+
+use crate::primitives::{ExecutionResult, RevertReason};
+use alloy_dyn_abi::{DynSolValue, JsonAbiExt};
+use alloy_json_abi::{Error, JsonAbi};
+use core::fmt;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// Custom error decoded with the ABI.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedError {
+    pub name: String,
+    pub args: Vec<DynSolValue>,
+}
+
+impl fmt::Display for DecodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{:?}", self.name, self.args)
+    }
+}
+
+/// Decoder of the revert data, the custom errors of the ABI are tried after the standard
+/// `Error(string)` and `Panic(uint256)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorDecoder {
+    errors: Vec<Error>,
+}
+
+impl ErrorDecoder {
+    /// Decoder of the errors defined in the ABI.
+    pub fn new(abi: &JsonAbi) -> Self {
+        Self {
+            errors: abi.errors().cloned().collect(),
+        }
+    }
+
+    /// Decoder of the errors defined in the ABI JSON.
+    pub fn from_json(abi: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(&serde_json::from_str(abi)?))
+    }
+
+    /// Adds the errors of another ABI, e.g. of the called libraries.
+    pub fn extend(&mut self, abi: &JsonAbi) {
+        self.errors.extend(abi.errors().cloned());
+    }
+
+    /// Decodes the custom error of the revert data.
+    pub fn decode(&self, output: &[u8]) -> Option<DecodedError> {
+        let selector = output.get(..4)?;
+        self.errors.iter().find_map(|error| {
+            if error.selector().as_slice() != selector {
+                return None;
+            }
+            let args = error.abi_decode_input(&output[4..], true).ok()?;
+            Some(DecodedError {
+                name: error.name.clone(),
+                args,
+            })
+        })
+    }
+
+    /// Human readable description of the revert data.
+    pub fn describe(&self, output: &[u8]) -> String {
+        match RevertReason::decode(output) {
+            RevertReason::Custom(data) => self
+                .decode(&data)
+                .map_or_else(|| RevertReason::Custom(data).to_string(), |e| e.to_string()),
+            reason => reason.to_string(),
+        }
+    }
+
+    /// Description of the revert of the execution, `None` if it was not reverted.
+    pub fn describe_result(&self, result: &ExecutionResult) -> Option<String> {
+        match result {
+            ExecutionResult::Revert { output, .. } => Some(self.describe(output)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::U256;
+
+    #[test]
+    fn decode_custom_error() {
+        let decoder = ErrorDecoder::from_json(
+            r#"[{"type":"error","name":"Denied","inputs":[{"name":"x","type":"uint256"}]}]"#,
+        )
+        .unwrap();
+        let mut output = decoder.errors[0].selector().to_vec();
+        output.extend(U256::from(7).to_be_bytes::<32>());
+
+        let error = decoder.decode(&output).unwrap();
+        assert_eq!(error.name, "Denied");
+        assert_eq!(error.args, [DynSolValue::from(U256::from(7))]);
+        assert!(decoder.decode(&[0, 0, 0, 0]).is_none());
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     primitives::{
         BlockEnv, CfgEnv, CfgEnvWithHandlerCfg, Env, EnvWithHandlerCfg, HandlerCfg, SpecId, TxEnv,
     },
-    Context, ContextWithHandlerCfg, Evm, Handler,
+    Context, ContextWithHandlerCfg, Evm, Handler, Interrupt,
 };
 use core::marker::PhantomData;
 use std::boxed::Box;
@@ -412,6 +412,12 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         self
     }
 
+    /// Sets the interrupt that stops the execution when triggered.
+    pub fn with_interrupt(mut self, interrupt: Interrupt) -> Self {
+        self.context.evm.interrupt = Some(interrupt);
+        self
+    }
+
     /// Clears Environment of EVM.
     pub fn with_clear_env(mut self) -> Self {
         self.context.evm.env.clear();
@@ -0,0 +1,676 @@
+//! Foundry style cheatcodes for contract tests.
+//!
+//! Cheatcodes are called at [CHEATCODE_ADDRESS] with the ABI encoded input and are enabled by
+//! registering [cheatcode_handle_register] when the [Evm](crate::Evm) is built. Supported
+//! cheatcodes are:
+//!
+//! * `warp(uint256)` sets the block timestamp.
+//! * `roll(uint256)` sets the block number.
+//! * `deal(address,uint256)` sets the balance of the account.
+//! * `prank(address)` sets the caller of the next call made by the calling contract.
+//! * `store(address,bytes32,bytes32)` and `load(address,bytes32)` write and read the storage.
+//! * `etch(address,bytes)` sets the code of the account.
+//! * `expectRevert()` and `expectRevert(bytes)` expect the next call made by the calling
+//!   contract to revert, optionally with the given data or `Error(string)` message.
+//! * `recordLogs()` starts recording the logs and `getRecordedLogs()` returns the logs
+//!   recorded since then as `(bytes32[] topics, bytes data, address emitter)[]`.
+
+use crate::{
+    handler::register::EvmHandler,
+    interpreter::{CallInputs, CallOutcome, InstructionResult},
+    precompile::{PrecompileError, PrecompileResult},
+    primitives::{
+        address, db::Database, error_selector, sha3, Address, Bytecode, Bytes, EVMError, Log, B256,
+        ERROR_SIGNATURE, U256,
+    },
+    ContextPrecompile, ContextStatefulPrecompileMut, FrameOrResult, InnerEvmContext,
+};
+use std::boxed::Box;
+use std::string::{String, ToString};
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+
+/// Address the cheatcodes are called at, same as in Foundry.
+pub const CHEATCODE_ADDRESS: Address = address!("7109709ecfa91a80626ff3989d68f67f5b1dd12d");
+
+/// Caller that is set for the next call.
+#[derive(Clone, Debug)]
+struct Prank {
+    sender: Address,
+    /// Contract that called the cheatcode.
+    caller: Address,
+    depth: u64,
+}
+
+/// Revert expected from the next call.
+#[derive(Clone, Debug)]
+struct ExpectedRevert {
+    reason: Option<Bytes>,
+    depth: u64,
+    /// Whether the expected call was made.
+    armed: bool,
+}
+
+#[derive(Debug, Default)]
+struct CheatcodeState {
+    /// Caller of the cheatcode that is being executed.
+    caller: Address,
+    /// Depth the cheatcode was called at.
+    depth: u64,
+    prank: Option<Prank>,
+    expected_revert: Option<ExpectedRevert>,
+    /// Number of logs before the recording started.
+    recorded_logs: Option<usize>,
+}
+
+impl CheatcodeState {
+    /// Applies the prank and arms the expected revert.
+    fn before_call(&mut self, inputs: &mut CallInputs, depth: u64) {
+        if self
+            .prank
+            .as_ref()
+            .is_some_and(|prank| prank.depth == depth && prank.caller == inputs.context.caller)
+        {
+            let prank = self.prank.take().unwrap();
+            inputs.context.caller = prank.sender;
+            if inputs.transfer.source == prank.caller {
+                inputs.transfer.source = prank.sender;
+            }
+        }
+        if let Some(expected) = &mut self.expected_revert {
+            if !expected.armed && expected.depth == depth {
+                expected.armed = true;
+            }
+        }
+    }
+
+    /// Checks the outcome of the call the revert is expected from.
+    ///
+    /// Expected revert is turned into a success with empty output, anything else is turned
+    /// into a revert.
+    fn after_call(&mut self, outcome: &mut CallOutcome, depth: u64) {
+        if !self
+            .expected_revert
+            .as_ref()
+            .is_some_and(|expected| expected.armed && expected.depth == depth)
+        {
+            return;
+        }
+        let expected = self.expected_revert.take().unwrap();
+        let result = &mut outcome.result;
+        let error = if !result.result.is_revert() {
+            Some("call did not revert as expected".to_string())
+        } else {
+            match expected.reason {
+                Some(reason)
+                    if reason != result.output
+                        && revert_string(&result.output).as_ref() != Some(&reason) =>
+                {
+                    Some(format!(
+                        "call reverted with unexpected data {}",
+                        result.output
+                    ))
+                }
+                _ => None,
+            }
+        };
+        match error {
+            None => {
+                result.result = InstructionResult::Return;
+                result.output = Bytes::new();
+            }
+            Some(error) => {
+                result.result = InstructionResult::Revert;
+                result.output = encode_error(&error);
+            }
+        }
+    }
+
+    fn apply<DB: Database>(
+        &mut self,
+        input: &Bytes,
+        evmctx: &mut InnerEvmContext<DB>,
+    ) -> Result<Bytes, CheatcodeError<DB::Error>> {
+        if input.len() < 4 {
+            return Err(CheatcodeError::Invalid("missing selector".into()));
+        }
+        let (selector, args) = input.split_at(4);
+        let args = Args(args);
+        let is = |signature: &str| selector == &sha3(signature)[..4];
+
+        let mut output = Bytes::new();
+        if is("warp(uint256)") {
+            evmctx.env.block.timestamp = args.word(0)?;
+        } else if is("roll(uint256)") {
+            evmctx.env.block.number = args.word(0)?;
+        } else if is("deal(address,uint256)") {
+            let address = args.address(0)?;
+            evmctx.load_account(address)?;
+            evmctx.journaled_state.set_balance(address, args.word(1)?);
+        } else if is("prank(address)") {
+            self.prank = Some(Prank {
+                sender: args.address(0)?,
+                caller: self.caller,
+                depth: self.depth,
+            });
+        } else if is("store(address,bytes32,bytes32)") {
+            let address = args.address(0)?;
+            evmctx.load_account(address)?;
+            evmctx.sstore(address, args.word(1)?, args.word(2)?)?;
+        } else if is("load(address,bytes32)") {
+            let address = args.address(0)?;
+            evmctx.load_account(address)?;
+            let (value, _) = evmctx.sload(address, args.word(1)?)?;
+            output = Bytes::copy_from_slice(B256::from(value).as_slice());
+        } else if is("etch(address,bytes)") {
+            let address = args.address(0)?;
+            let code = args.bytes(1)?;
+            evmctx.load_account(address)?;
+            evmctx
+                .journaled_state
+                .set_code(address, Bytecode::new_raw(code));
+        } else if is("expectRevert()") || is("expectRevert(bytes)") {
+            let reason = if args.0.is_empty() {
+                None
+            } else {
+                Some(args.bytes(0)?)
+            };
+            self.expected_revert = Some(ExpectedRevert {
+                reason,
+                depth: self.depth,
+                armed: false,
+            });
+        } else if is("recordLogs()") {
+            self.recorded_logs = Some(evmctx.journaled_state.logs.len());
+        } else if is("getRecordedLogs()") {
+            let logs = &evmctx.journaled_state.logs;
+            let start = self
+                .recorded_logs
+                .map_or(logs.len(), |start| start.min(logs.len()));
+            output = encode_logs(&logs[start..]);
+            if self.recorded_logs.is_some() {
+                self.recorded_logs = Some(logs.len());
+            }
+        } else {
+            return Err(CheatcodeError::Invalid(format!(
+                "unknown cheatcode 0x{}",
+                crate::primitives::hex::encode(selector)
+            )));
+        }
+        Ok(output)
+    }
+}
+
+enum CheatcodeError<E> {
+    Invalid(String),
+    Database(EVMError<E>),
+}
+
+impl<E> From<EVMError<E>> for CheatcodeError<E> {
+    fn from(e: EVMError<E>) -> Self {
+        Self::Database(e)
+    }
+}
+
+/// Cheatcode precompile, shares the state with the handlers.
+#[derive(Clone)]
+struct Cheatcodes {
+    state: Arc<Mutex<CheatcodeState>>,
+}
+
+impl<DB: Database> ContextStatefulPrecompileMut<DB> for Cheatcodes {
+    fn call_mut(
+        &mut self,
+        bytes: &Bytes,
+        _energy_limit: u64,
+        evmctx: &mut InnerEvmContext<DB>,
+    ) -> PrecompileResult {
+        match self.state.lock().unwrap().apply(bytes, evmctx) {
+            Ok(output) => Ok((0, output)),
+            Err(CheatcodeError::Invalid(e)) => Err(PrecompileError::Other(e)),
+            Err(CheatcodeError::Database(e)) => {
+                // error is returned from the transaction once the call outcome is inserted.
+                evmctx.error = Err(e);
+                Err(PrecompileError::Other("database error".into()))
+            }
+        }
+    }
+}
+
+/// Register cheatcode handles.
+///
+/// Cheatcode precompile is added at [CHEATCODE_ADDRESS], and calls are wrapped to apply
+/// `prank` and check `expectRevert`. Every built [Evm](crate::Evm) has its own cheatcode state.
+pub fn cheatcode_handle_register<EXT, DB: Database>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    let state = Arc::new(Mutex::new(CheatcodeState::default()));
+
+    let cheatcodes = Cheatcodes {
+        state: state.clone(),
+    };
+    let old_handle = handler.pre_execution.load_precompiles.clone();
+    handler.pre_execution.load_precompiles = Arc::new(move || {
+        let mut precompiles = old_handle();
+        precompiles.extend([(
+            CHEATCODE_ADDRESS,
+            ContextPrecompile::ContextStatefulMut(Box::new(cheatcodes.clone())),
+        )]);
+        precompiles
+    });
+
+    // contracts check that the called address has code.
+    let old_handle = handler.pre_execution.load_accounts.clone();
+    handler.pre_execution.load_accounts = Arc::new(move |ctx| {
+        old_handle(ctx)?;
+        let (account, _) = ctx.evm.load_account(CHEATCODE_ADDRESS)?;
+        if account
+            .info
+            .code
+            .as_ref()
+            .map_or(true, |code| code.is_empty())
+        {
+            let code = Bytecode::new_raw(Bytes::from_static(&[0]));
+            account.info.code_hash = code.hash_slow();
+            account.info.code = Some(code);
+        }
+        Ok(())
+    });
+
+    let call_state = state.clone();
+    let old_handle = handler.execution.call.clone();
+    handler.execution.call = Arc::new(
+        move |ctx, mut inputs| -> Result<FrameOrResult, EVMError<DB::Error>> {
+            let depth = ctx.evm.journaled_state.depth();
+            {
+                let mut state = call_state.lock().unwrap();
+                if inputs.contract == CHEATCODE_ADDRESS {
+                    state.caller = inputs.context.caller;
+                    state.depth = depth;
+                } else {
+                    state.before_call(&mut inputs, depth);
+                }
+            }
+            old_handle(ctx, inputs)
+        },
+    );
+
+    let old_handle = handler.execution.insert_call_outcome.clone();
+    handler.execution.insert_call_outcome = Arc::new(
+        move |ctx, frame, shared_memory, mut outcome| -> Result<(), EVMError<DB::Error>> {
+            let depth = ctx.evm.journaled_state.depth();
+            state.lock().unwrap().after_call(&mut outcome, depth);
+            old_handle(ctx, frame, shared_memory, outcome)
+        },
+    );
+}
+
+/// ABI encoded arguments of the cheatcode.
+struct Args<'a>(&'a [u8]);
+
+impl Args<'_> {
+    fn word(&self, index: usize) -> Result<U256, String> {
+        self.word_at(index * 32)
+    }
+
+    fn word_at(&self, offset: usize) -> Result<U256, String> {
+        self.0
+            .get(offset..offset + 32)
+            .map(U256::from_be_slice)
+            .ok_or_else(|| format!("argument at {offset} is missing"))
+    }
+
+    fn address(&self, index: usize) -> Result<Address, String> {
+        Ok(Address::from_word(self.word(index)?.into()))
+    }
+
+    fn bytes(&self, index: usize) -> Result<Bytes, String> {
+        let offset: usize = self.word(index)?.saturating_to();
+        let len: usize = self.word_at(offset)?.saturating_to();
+        let start = offset + 32;
+        self.0
+            .get(start..start.saturating_add(len))
+            .map(Bytes::copy_from_slice)
+            .ok_or_else(|| format!("bytes at {offset} are out of bounds"))
+    }
+}
+
+impl<E> From<String> for CheatcodeError<E> {
+    fn from(e: String) -> Self {
+        Self::Invalid(e)
+    }
+}
+
+fn push_word(out: &mut Vec<u8>, value: usize) {
+    out.extend_from_slice(&U256::from(value).to_be_bytes::<32>());
+}
+
+fn push_bytes(out: &mut Vec<u8>, data: &[u8]) {
+    push_word(out, data.len());
+    out.extend_from_slice(data);
+    out.resize(out.len() + (32 - data.len() % 32) % 32, 0);
+}
+
+/// Message of the `Error(string)` revert data.
+fn revert_string(output: &[u8]) -> Option<Bytes> {
+    let (selector, args) = (output.len() >= 4).then(|| output.split_at(4))?;
+    if selector != error_selector(ERROR_SIGNATURE) {
+        return None;
+    }
+    Args(args).bytes(0).ok()
+}
+
+/// `Error(string)` revert data.
+fn encode_error(message: &str) -> Bytes {
+    let mut out = error_selector(ERROR_SIGNATURE).to_vec();
+    push_word(&mut out, 32);
+    push_bytes(&mut out, message.as_bytes());
+    out.into()
+}
+
+/// Encodes the logs as `(bytes32[] topics, bytes data, address emitter)[]`.
+fn encode_logs(logs: &[Log]) -> Bytes {
+    let encoded: Vec<Vec<u8>> = logs
+        .iter()
+        .map(|log| {
+            let topics = log.topics();
+            let mut out = Vec::new();
+            push_word(&mut out, 3 * 32);
+            push_word(&mut out, 4 * 32 + topics.len() * 32);
+            out.extend_from_slice(log.address.into_word().as_slice());
+            push_word(&mut out, topics.len());
+            for topic in topics {
+                out.extend_from_slice(topic.as_slice());
+            }
+            push_bytes(&mut out, &log.data.data);
+            out
+        })
+        .collect();
+
+    let mut out = Vec::new();
+    push_word(&mut out, 32);
+    push_word(&mut out, encoded.len());
+    let mut offset = encoded.len() * 32;
+    for log in &encoded {
+        push_word(&mut out, offset);
+        offset += log.len();
+    }
+    for log in encoded {
+        out.extend(log);
+    }
+    out.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        primitives::{AccountInfo, ExecutionResult, State, TransactTo},
+        Evm,
+    };
+
+    fn call(signature: &str, args: &[B256]) -> Bytes {
+        let mut input = sha3(signature)[..4].to_vec();
+        for arg in args {
+            input.extend_from_slice(arg.as_slice());
+        }
+        input.into()
+    }
+
+    /// Input of the cheatcode whose last argument is `bytes`.
+    fn call_with_bytes(signature: &str, args: &[B256], data: &[u8]) -> Bytes {
+        let mut input = call(signature, args).to_vec();
+        push_word(&mut input, (args.len() + 1) * 32);
+        push_bytes(&mut input, data);
+        input.into()
+    }
+
+    /// Appends the code that calls the address with the input and stores the success flag at
+    /// the slot.
+    fn push_call(code: &mut Vec<u8>, to: Address, input: &[u8], slot: u8) {
+        for (i, chunk) in input.chunks(32).enumerate() {
+            let mut word = [0u8; 32];
+            word[..chunk.len()].copy_from_slice(chunk);
+            code.push(0x7f);
+            code.extend_from_slice(&word);
+            code.push(0x61);
+            code.extend_from_slice(&(i as u16 * 32).to_be_bytes());
+            code.push(0x52);
+        }
+        // CALL(energy, to, 0, 0, input.len(), 0, 0)
+        code.extend_from_slice(&[0x60, 0x00, 0x60, 0x00, 0x61]);
+        code.extend_from_slice(&(input.len() as u16).to_be_bytes());
+        code.extend_from_slice(&[0x60, 0x00, 0x60, 0x00, 0x73]);
+        code.extend_from_slice(to.as_slice());
+        code.extend_from_slice(&[0x5a, 0xf1, 0x60, slot, 0x55]);
+    }
+
+    /// Evm with the contracts deployed that calls the address with the input.
+    fn build_evm(
+        contracts: Vec<(Address, Vec<u8>)>,
+        to: Address,
+        data: Bytes,
+    ) -> Evm<'static, (), InMemoryDB> {
+        let mut db = InMemoryDB::default();
+        for (address, code) in contracts {
+            let code = Bytecode::new_raw(code.into());
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+        }
+        Evm::builder()
+            .with_db(db)
+            .append_handler_register(cheatcode_handle_register)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(to);
+                tx.data = data;
+            })
+            .build()
+    }
+
+    fn slot(state: &State, address: Address, index: u64) -> U256 {
+        state[&address]
+            .storage
+            .get(&U256::from(index))
+            .map_or(U256::ZERO, |slot| slot.present_value())
+    }
+
+    #[test]
+    fn warp_and_store() {
+        let target = Address::with_last_byte(0xaa);
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .append_handler_register(cheatcode_handle_register)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(CHEATCODE_ADDRESS);
+                tx.data = call("warp(uint256)", &[B256::with_last_byte(100)]);
+            })
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(evm.context.evm.env.block.timestamp, U256::from(100));
+
+        evm.tx_mut().data = call(
+            "store(address,bytes32,bytes32)",
+            &[
+                target.into_word(),
+                B256::with_last_byte(1),
+                B256::with_last_byte(2),
+            ],
+        );
+        let out = evm.transact().unwrap();
+        assert!(out.result.is_success());
+        assert_eq!(
+            out.state[&target].storage[&U256::from(1)].present_value(),
+            U256::from(2)
+        );
+    }
+
+    #[test]
+    fn roll_deal_etch_and_load() {
+        let target = Address::with_last_byte(0xaa);
+        let mut evm = build_evm(
+            Vec::new(),
+            CHEATCODE_ADDRESS,
+            call("roll(uint256)", &[B256::with_last_byte(5)]),
+        );
+        assert!(evm.transact_commit().unwrap().is_success());
+        assert_eq!(evm.context.evm.env.block.number, U256::from(5));
+
+        evm.tx_mut().data = call(
+            "deal(address,uint256)",
+            &[target.into_word(), B256::with_last_byte(100)],
+        );
+        assert!(evm.transact_commit().unwrap().is_success());
+        assert_eq!(
+            evm.context.evm.db.accounts[&target].info.balance,
+            U256::from(100)
+        );
+
+        let code = Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55]);
+        evm.tx_mut().data = call_with_bytes("etch(address,bytes)", &[target.into_word()], &code);
+        assert!(evm.transact_commit().unwrap().is_success());
+        let info = &evm.context.evm.db.accounts[&target].info;
+        assert_eq!(info.code_hash, Bytecode::new_raw(code).hash_slow());
+
+        evm.tx_mut().data = call(
+            "store(address,bytes32,bytes32)",
+            &[
+                target.into_word(),
+                B256::with_last_byte(1),
+                B256::with_last_byte(2),
+            ],
+        );
+        assert!(evm.transact_commit().unwrap().is_success());
+        evm.tx_mut().data = call(
+            "load(address,bytes32)",
+            &[target.into_word(), B256::with_last_byte(1)],
+        );
+        let result = evm.transact().unwrap().result;
+        assert_eq!(
+            result.output(),
+            Some(&Bytes::copy_from_slice(B256::with_last_byte(2).as_slice()))
+        );
+    }
+
+    #[test]
+    fn deal_is_reverted() {
+        let caller = Address::with_last_byte(0xc0);
+        let reverter = Address::with_last_byte(0xbb);
+        let mut code = Vec::new();
+        push_call(
+            &mut code,
+            CHEATCODE_ADDRESS,
+            &call(
+                "deal(address,uint256)",
+                &[caller.into_word(), B256::with_last_byte(100)],
+            ),
+            0,
+        );
+        // REVERT(0, 0)
+        code.extend_from_slice(&[0x60, 0x00, 0x60, 0x00, 0xfd]);
+        let mut evm = build_evm(vec![(reverter, code)], reverter, Bytes::new());
+        evm.tx_mut().caller = caller;
+        let out = evm.transact().unwrap();
+        assert!(matches!(out.result, ExecutionResult::Revert { .. }));
+        assert_eq!(out.state[&caller].info.balance, U256::ZERO);
+    }
+
+    #[test]
+    fn prank_sets_caller_of_next_call() {
+        let tester = Address::with_last_byte(0xaa);
+        let target = Address::with_last_byte(0xbb);
+        let sender = Address::with_last_byte(0xcc);
+        let mut code = Vec::new();
+        push_call(
+            &mut code,
+            CHEATCODE_ADDRESS,
+            &call("prank(address)", &[sender.into_word()]),
+            0,
+        );
+        push_call(&mut code, target, &[], 1);
+        push_call(&mut code, target, &[0], 2);
+        // target stores the caller at the slot of the calldata size.
+        let target_code = vec![0x33, 0x36, 0x55];
+        let mut evm = build_evm(
+            vec![(tester, code), (target, target_code)],
+            tester,
+            Bytes::new(),
+        );
+        let out = evm.transact().unwrap();
+        assert!(out.result.is_success());
+        assert_eq!(slot(&out.state, tester, 0), U256::from(1));
+        assert_eq!(
+            Address::from_word(slot(&out.state, target, 0).into()),
+            sender
+        );
+        // prank is only applied to the next call.
+        assert_eq!(
+            Address::from_word(slot(&out.state, target, 1).into()),
+            tester
+        );
+    }
+
+    #[test]
+    fn expect_revert_checks_reason() {
+        let reverter = Address::with_last_byte(0xbb);
+        // REVERT with "boom".
+        let reverter_code = vec![
+            0x63, b'b', b'o', b'o', b'm', 0x60, 0x00, 0x52, 0x60, 0x04, 0x60, 0x1c, 0xfd,
+        ];
+        for (reason, success) in [(&b"boom"[..], 1), (&b"nope"[..], 0)] {
+            let tester = Address::with_last_byte(0xaa);
+            let mut code = Vec::new();
+            push_call(
+                &mut code,
+                CHEATCODE_ADDRESS,
+                &call_with_bytes("expectRevert(bytes)", &[], reason),
+                0,
+            );
+            push_call(&mut code, reverter, &[], 1);
+            let mut evm = build_evm(
+                vec![(tester, code), (reverter, reverter_code.clone())],
+                tester,
+                Bytes::new(),
+            );
+            let out = evm.transact().unwrap();
+            assert!(out.result.is_success());
+            assert_eq!(slot(&out.state, tester, 1), U256::from(success));
+        }
+    }
+
+    #[test]
+    fn record_logs() {
+        let tester = Address::with_last_byte(0xaa);
+        // LOG1(0, 0, 6)
+        let mut code = vec![0x60, 0x06, 0x60, 0x00, 0x60, 0x00, 0xa1];
+        push_call(&mut code, CHEATCODE_ADDRESS, &call("recordLogs()", &[]), 0);
+        // LOG1(0, 0, 7)
+        code.extend_from_slice(&[0x60, 0x07, 0x60, 0x00, 0x60, 0x00, 0xa1]);
+        push_call(
+            &mut code,
+            CHEATCODE_ADDRESS,
+            &call("getRecordedLogs()", &[]),
+            1,
+        );
+        // RETURNDATACOPY(0, 0, RETURNDATASIZE) RETURN(0, RETURNDATASIZE)
+        code.extend_from_slice(&[0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, 0x3d, 0x60, 0x00, 0xf3]);
+        let mut evm = build_evm(vec![(tester, code)], tester, Bytes::new());
+        let out = evm.transact().unwrap();
+        let log = Log::new_unchecked(tester, vec![B256::with_last_byte(7)], Bytes::new());
+        assert_eq!(out.result.output(), Some(&encode_logs(&[log])));
+    }
+
+    #[test]
+    fn unknown_cheatcode_fails() {
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .append_handler_register(cheatcode_handle_register)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TransactTo::Call(CHEATCODE_ADDRESS);
+                tx.data = call("unknown()", &[]);
+            })
+            .build();
+        let result = evm.transact().unwrap().result;
+        assert!(matches!(result, ExecutionResult::Halt { .. }));
+    }
+}
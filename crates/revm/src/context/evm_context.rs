@@ -160,6 +160,11 @@ impl<DB: Database> EvmContext<DB> {
             return return_result(InstructionResult::CallTooDeep);
         }
 
+        // Check frame limit
+        if !self.count_frame() {
+            return return_result(InstructionResult::CallFrameLimit);
+        }
+
         let (account, _) = self
             .inner
             .journaled_state
@@ -224,7 +229,7 @@ pub(crate) mod test_utils {
     use crate::{
         db::{CacheDB, EmptyDB},
         journaled_state::JournaledState,
-        primitives::{address, Address, Bytes, Env, HashSet, SpecId, B256, U256},
+        primitives::{address, Address, Bytes, Env, ExecutionStats, HashSet, SpecId, B256, U256},
         InnerEvmContext,
     };
 
@@ -285,6 +290,8 @@ pub(crate) mod test_utils {
                 journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::new()),
                 db,
                 error: Ok(()),
+                interrupt: None,
+                execution_stats: ExecutionStats::default(),
                 #[cfg(feature = "optimism")]
                 l1_block_info: None,
             },
@@ -300,6 +307,8 @@ pub(crate) mod test_utils {
                 journaled_state: JournaledState::new(SpecId::CANCUN, HashSet::new()),
                 db,
                 error: Ok(()),
+                interrupt: None,
+                execution_stats: ExecutionStats::default(),
                 #[cfg(feature = "optimism")]
                 l1_block_info: None,
             },
@@ -7,11 +7,11 @@ use crate::{
     journaled_state::JournaledState,
     primitives::{
         sha3, Account, Address, AnalysisKind, Bytecode, Bytes, CreateScheme, EVMError, Env,
-        HashSet, Spec,
+        ExecutionStats, HashSet, Spec,
         SpecId::{self, *},
         B256, U256,
     },
-    FrameOrResult, JournalCheckpoint, CALL_STACK_LIMIT,
+    FrameOrResult, Interrupt, JournalCheckpoint, CALL_STACK_LIMIT,
 };
 use revm_interpreter::{SStoreResult, SelfDestructResult};
 use std::boxed::Box;
@@ -28,6 +28,10 @@ pub struct InnerEvmContext<DB: Database> {
     pub db: DB,
     /// Error that happened during execution.
     pub error: Result<(), EVMError<DB::Error>>,
+    /// Interrupt that stops the execution with [EVMError::Interrupted] when triggered.
+    pub interrupt: Option<Interrupt>,
+    /// Instructions and call frames executed by the current transaction.
+    pub execution_stats: ExecutionStats,
     /// Used as temporary value holder to store L1 block info.
     #[cfg(feature = "optimism")]
     pub l1_block_info: Option<crate::optimism::L1BlockInfo>,
@@ -43,6 +47,8 @@ where
             journaled_state: self.journaled_state.clone(),
             db: self.db.clone(),
             error: self.error.clone(),
+            interrupt: self.interrupt.clone(),
+            execution_stats: self.execution_stats,
             #[cfg(feature = "optimism")]
             l1_block_info: self.l1_block_info.clone(),
         }
@@ -56,6 +62,8 @@ impl<DB: Database> InnerEvmContext<DB> {
             journaled_state: JournaledState::new(SpecId::LATEST, HashSet::new()),
             db,
             error: Ok(()),
+            interrupt: None,
+            execution_stats: ExecutionStats::default(),
             #[cfg(feature = "optimism")]
             l1_block_info: None,
         }
@@ -69,6 +77,8 @@ impl<DB: Database> InnerEvmContext<DB> {
             journaled_state: JournaledState::new(SpecId::LATEST, HashSet::new()),
             db,
             error: Ok(()),
+            interrupt: None,
+            execution_stats: ExecutionStats::default(),
             #[cfg(feature = "optimism")]
             l1_block_info: None,
         }
@@ -84,11 +94,25 @@ impl<DB: Database> InnerEvmContext<DB> {
             journaled_state: self.journaled_state,
             db,
             error: Ok(()),
+            interrupt: self.interrupt,
+            execution_stats: self.execution_stats,
             #[cfg(feature = "optimism")]
             l1_block_info: self.l1_block_info,
         }
     }
 
+    /// Counts the new call frame, returns false if the frame limit is reached.
+    #[inline]
+    pub(crate) fn count_frame(&mut self) -> bool {
+        if let Some(limit) = self.env.cfg.frame_limit {
+            if self.execution_stats.frames >= limit {
+                return false;
+            }
+        }
+        self.execution_stats.frames += 1;
+        true
+    }
+
     /// Returns the configured EVM spec ID.
     #[inline]
     pub const fn spec_id(&self) -> SpecId {
@@ -244,6 +268,11 @@ impl<DB: Database> InnerEvmContext<DB> {
             return return_error(InstructionResult::CallTooDeep);
         }
 
+        // Check frame limit
+        if !self.count_frame() {
+            return return_error(InstructionResult::CallFrameLimit);
+        }
+
         // Fetch balance of caller.
         let (caller_balance, _) = self.balance(inputs.caller)?;
 
@@ -1,16 +1,34 @@
 //! [Database] implementations.
 
+#[cfg(all(feature = "std", feature = "serde-json"))]
+pub mod dump;
 pub mod emptydb;
 #[cfg(feature = "ethersdb")]
 pub mod ethersdb;
+#[cfg(all(feature = "std", feature = "serde-json"))]
+pub mod genesis;
 pub mod in_memory_db;
+pub mod overrides;
+#[cfg(all(feature = "std", feature = "serde-json"))]
+pub mod recording;
+pub mod snapshot;
 pub mod states;
 
 pub use crate::primitives::db::*;
+#[cfg(all(feature = "std", feature = "serde-json"))]
+pub use dump::{DumpAccount, DumpDecodeError, StateDump};
 pub use emptydb::{EmptyDB, EmptyDBTyped};
 #[cfg(feature = "ethersdb")]
 pub use ethersdb::EthersDB;
+#[cfg(all(feature = "std", feature = "serde-json"))]
+pub use genesis::{
+    deserialize_alloc, parse_address, parse_network_address, Genesis, GenesisAccount, GenesisConfig,
+};
 pub use in_memory_db::*;
+pub use overrides::{AccountOverride, BlockOverrides, OverrideDB, StateOverride};
+#[cfg(all(feature = "std", feature = "serde-json"))]
+pub use recording::{RecordedAccount, Recording, RecordingDB, ReplayDB, UnrecordedRead};
+pub use snapshot::{SnapshotDB, SnapshotId};
 pub use states::{
     AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
     OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
@@ -0,0 +1,461 @@
+//! Export and import of the full state in go-core `dump` format.
+
+use super::{
+    genesis::{deserialize_alloc, deserialize_u256},
+    AccountState, BundleState, CacheDB, CacheState, EmptyDB, InMemoryDB, State,
+};
+use crate::primitives::{AccountInfo, Address, Bytecode, Bytes, B256, SHA3_EMPTY, U256};
+use core::{fmt, str::FromStr};
+use serde::{Deserialize, Serialize, Serializer};
+use std::{collections::BTreeMap, string::String, vec::Vec};
+
+/// Magic bytes that start the binary encoding of the [StateDump].
+const MAGIC: &[u8; 4] = b"CVMD";
+
+/// Version of the binary encoding of the [StateDump].
+const VERSION: u8 = 2;
+
+/// Full state of the accounts.
+///
+/// The JSON representation follows the go-core `dump` output. Accounts and storage are
+/// kept sorted so the same state always produces the same output.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateDump {
+    /// State root the dump was taken at, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<B256>,
+    /// Accounts by their address.
+    #[serde(deserialize_with = "deserialize_alloc")]
+    pub accounts: BTreeMap<Address, DumpAccount>,
+}
+
+/// Account of the [StateDump].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpAccount {
+    /// Balance of the account, written as a decimal string.
+    #[serde(
+        serialize_with = "serialize_decimal",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub balance: U256,
+    /// Nonce of the account.
+    pub nonce: u64,
+    /// Storage root of the account, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<B256>,
+    /// Hash of the account code.
+    pub code_hash: B256,
+    /// Code of the account.
+    #[serde(default, skip_serializing_if = "Bytes::is_empty")]
+    pub code: Bytes,
+    /// Non zero storage slots of the account.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<B256, U256>,
+}
+
+impl DumpAccount {
+    fn new(info: &AccountInfo, code: Option<&Bytecode>) -> Self {
+        Self {
+            balance: info.balance,
+            nonce: info.nonce,
+            root: None,
+            code_hash: info.code_hash,
+            code: code.map(Bytecode::original_bytes).unwrap_or_default(),
+            storage: BTreeMap::new(),
+        }
+    }
+
+    fn insert_storage(&mut self, storage: impl IntoIterator<Item = (U256, U256)>) {
+        self.storage.extend(
+            storage
+                .into_iter()
+                .filter(|(_, value)| *value != U256::ZERO)
+                .map(|(slot, value)| (B256::from(slot), value)),
+        );
+    }
+
+    /// Returns the account info with the dumped code hash.
+    ///
+    /// Code is not set if the account has code hash but its code was not dumped, so it is
+    /// loaded by the hash when needed.
+    pub fn account_info(&self) -> AccountInfo {
+        let code = (!self.code.is_empty() || self.code_hash == SHA3_EMPTY)
+            .then(|| Bytecode::new_raw(self.code.clone()));
+        AccountInfo {
+            balance: self.balance,
+            nonce: self.nonce,
+            code_hash: self.code_hash,
+            code,
+        }
+    }
+
+    /// Returns storage slots of the account.
+    pub fn storage_slots(&self) -> impl Iterator<Item = (U256, U256)> + '_ {
+        self.storage
+            .iter()
+            .map(|(slot, value)| (U256::from_be_bytes(slot.0), *value))
+    }
+}
+
+/// Error returned when decoding [StateDump] from bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DumpDecodeError {
+    /// Bytes do not start with the dump magic.
+    InvalidMagic,
+    /// Encoding version is not supported.
+    UnsupportedVersion(u8),
+    /// Input ended before the dump was decoded.
+    UnexpectedEnd,
+    /// Number is longer than 32 bytes.
+    InvalidNumber,
+    /// Input has bytes left after the dump was decoded.
+    TrailingBytes,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DumpDecodeError {}
+
+impl fmt::Display for DumpDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => write!(f, "invalid state dump magic"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported state dump version {version}")
+            }
+            Self::UnexpectedEnd => write!(f, "unexpected end of state dump"),
+            Self::InvalidNumber => write!(f, "number is longer than 32 bytes"),
+            Self::TrailingBytes => write!(f, "trailing bytes after state dump"),
+        }
+    }
+}
+
+impl FromStr for StateDump {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+impl StateDump {
+    /// Creates dump of all accounts, storage and contracts held by the [CacheDB].
+    ///
+    /// Only the cached state is dumped, values that were never loaded from the underlying
+    /// database are not part of the dump. Accounts marked as not existing are skipped.
+    pub fn from_cache_db<ExtDB>(db: &CacheDB<ExtDB>) -> Self {
+        let accounts = db
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.account_state != AccountState::NotExisting)
+            .map(|(address, account)| {
+                let code = account
+                    .info
+                    .code
+                    .as_ref()
+                    .or_else(|| db.contracts.get(&account.info.code_hash));
+                let mut dump = DumpAccount::new(&account.info, code);
+                dump.insert_storage(account.storage.iter().map(|(k, v)| (*k, *v)));
+                (*address, dump)
+            })
+            .collect();
+        Self {
+            root: None,
+            accounts,
+        }
+    }
+
+    /// Creates dump of the present state of the accounts in the [BundleState].
+    ///
+    /// Destroyed accounts are skipped. Bundle contains only the state that was changed,
+    /// so it is a full state only if the bundle was built on top of an empty state.
+    pub fn from_bundle_state(bundle: &BundleState) -> Self {
+        let accounts = bundle
+            .state
+            .iter()
+            .filter_map(|(address, account)| {
+                let info = account.info.as_ref()?;
+                let code = info
+                    .code
+                    .as_ref()
+                    .or_else(|| bundle.contracts.get(&info.code_hash));
+                let mut dump = DumpAccount::new(info, code);
+                dump.insert_storage(
+                    account
+                        .storage
+                        .iter()
+                        .map(|(slot, value)| (*slot, value.present_value)),
+                );
+                Some((*address, dump))
+            })
+            .collect();
+        Self {
+            root: None,
+            accounts,
+        }
+    }
+
+    /// Sets the state root of the dump.
+    pub fn with_root(mut self, root: B256) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Returns an in memory database that contains the dumped accounts.
+    pub fn in_memory_db(&self) -> InMemoryDB {
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, account) in &self.accounts {
+            db.insert_account_info(*address, account.account_info());
+            db.accounts
+                .get_mut(address)
+                .expect("account is inserted")
+                .storage
+                .extend(account.storage_slots());
+        }
+        db
+    }
+
+    /// Returns the cache state that contains the dumped accounts.
+    pub fn cache_state(&self, has_state_clear: bool) -> CacheState {
+        let mut cache = CacheState::new(has_state_clear);
+        for (address, account) in &self.accounts {
+            cache.insert_account_with_storage(
+                *address,
+                account.account_info(),
+                account.storage_slots().collect(),
+            );
+        }
+        cache
+    }
+
+    /// Returns the [State] with the dumped accounts preloaded and bundle updates enabled.
+    pub fn state(&self) -> State<EmptyDB> {
+        State::builder()
+            .with_cached_prestate(self.cache_state(true))
+            .with_bundle_update()
+            .build()
+    }
+
+    /// Returns the pretty printed JSON of the dump.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("dump is serializable")
+    }
+
+    /// Encodes the dump into the compact binary format.
+    ///
+    /// Numbers are written as big endian without leading zeros, prefixed by their length.
+    /// Code hash is written even if the code is not dumped. Storage roots of the accounts
+    /// are not encoded.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        match self.root {
+            Some(root) => {
+                out.push(1);
+                out.extend_from_slice(root.as_slice());
+            }
+            None => out.push(0),
+        }
+        encode_number(&mut out, U256::from(self.accounts.len()));
+        for (address, account) in &self.accounts {
+            out.extend_from_slice(address.as_slice());
+            encode_number(&mut out, account.balance);
+            encode_number(&mut out, U256::from(account.nonce));
+            out.extend_from_slice(account.code_hash.as_slice());
+            encode_number(&mut out, U256::from(account.code.len()));
+            out.extend_from_slice(&account.code);
+            encode_number(&mut out, U256::from(account.storage.len()));
+            for (slot, value) in account.storage_slots() {
+                encode_number(&mut out, slot);
+                encode_number(&mut out, value);
+            }
+        }
+        out
+    }
+
+    /// Decodes the dump from the compact binary format, see [StateDump::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DumpDecodeError> {
+        let mut decoder = Decoder(bytes);
+        if decoder.take(MAGIC.len())? != MAGIC {
+            return Err(DumpDecodeError::InvalidMagic);
+        }
+        let version = decoder.take(1)?[0];
+        if version != VERSION {
+            return Err(DumpDecodeError::UnsupportedVersion(version));
+        }
+        let root = match decoder.take(1)?[0] {
+            0 => None,
+            _ => Some(B256::from_slice(decoder.take(32)?)),
+        };
+        let mut accounts = BTreeMap::new();
+        for _ in 0..decoder.len()? {
+            let address = Address::from_slice(decoder.take(Address::len_bytes())?);
+            let balance = decoder.number()?;
+            let nonce = decoder.u64()?;
+            let code_hash = B256::from_slice(decoder.take(32)?);
+            let code_len = decoder.len()?;
+            let code = Bytes::copy_from_slice(decoder.take(code_len)?);
+            let mut account = DumpAccount {
+                balance,
+                nonce,
+                root: None,
+                code_hash,
+                code,
+                storage: BTreeMap::new(),
+            };
+            for _ in 0..decoder.len()? {
+                let slot = decoder.number()?;
+                let value = decoder.number()?;
+                account.insert_storage([(slot, value)]);
+            }
+            accounts.insert(address, account);
+        }
+        if !decoder.0.is_empty() {
+            return Err(DumpDecodeError::TrailingBytes);
+        }
+        Ok(Self { root, accounts })
+    }
+}
+
+fn serialize_decimal<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+fn encode_number(out: &mut Vec<u8>, number: U256) {
+    let bytes = number.to_be_bytes_trimmed_vec();
+    out.push(bytes.len() as u8);
+    out.extend_from_slice(&bytes);
+}
+
+/// Reads the binary encoding of the [StateDump].
+struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DumpDecodeError> {
+        if self.0.len() < len {
+            return Err(DumpDecodeError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn number(&mut self) -> Result<U256, DumpDecodeError> {
+        let len = self.take(1)?[0] as usize;
+        if len > 32 {
+            return Err(DumpDecodeError::InvalidNumber);
+        }
+        U256::try_from_be_slice(self.take(len)?).ok_or(DumpDecodeError::InvalidNumber)
+    }
+
+    fn u64(&mut self) -> Result<u64, DumpDecodeError> {
+        self.number()?
+            .try_into()
+            .map_err(|_| DumpDecodeError::InvalidNumber)
+    }
+
+    fn len(&mut self) -> Result<usize, DumpDecodeError> {
+        self.number()?
+            .try_into()
+            .map_err(|_| DumpDecodeError::InvalidNumber)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{states::bundle_state::BundleRetention, DatabaseCommit, DatabaseRef},
+        primitives::{Account, HashMap, StorageSlot},
+    };
+
+    fn cache_db() -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55]));
+        db.insert_account_info(
+            Address::with_last_byte(1),
+            AccountInfo::new(U256::from(10), 1, code.hash_slow(), code),
+        );
+        db.insert_account_storage(Address::with_last_byte(1), U256::from(1), U256::from(2))
+            .unwrap();
+        db.insert_account_storage(Address::with_last_byte(1), U256::from(2), U256::ZERO)
+            .unwrap();
+        db.insert_account_info(
+            Address::with_last_byte(2),
+            AccountInfo::from_balance(U256::MAX),
+        );
+        db
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let dump = StateDump::from_cache_db(&cache_db()).with_root(B256::with_last_byte(1));
+        let json = dump.to_json();
+        assert!(json.contains("\"balance\": \"10\""));
+        // output is deterministic.
+        assert_eq!(
+            json,
+            StateDump::from_cache_db(&cache_db())
+                .with_root(B256::with_last_byte(1))
+                .to_json()
+        );
+
+        let loaded: StateDump = json.parse().unwrap();
+        assert_eq!(loaded, dump);
+        let account = &loaded.accounts[&Address::with_last_byte(1)];
+        assert_eq!(account.storage.len(), 1);
+
+        let db = loaded.in_memory_db();
+        assert_eq!(
+            StateDump::from_cache_db(&db).with_root(B256::with_last_byte(1)),
+            dump
+        );
+        assert_eq!(
+            db.storage_ref(Address::with_last_byte(1), U256::from(1)),
+            Ok(U256::from(2))
+        );
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let dump = StateDump::from_cache_db(&cache_db());
+        let bytes = dump.to_bytes();
+        assert_eq!(StateDump::from_bytes(&bytes), Ok(dump));
+        assert_eq!(
+            StateDump::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DumpDecodeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            StateDump::from_bytes(b"CVMD\x03"),
+            Err(DumpDecodeError::UnsupportedVersion(3))
+        );
+    }
+
+    #[test]
+    fn dump_bundle_state() {
+        let dump = StateDump::from_cache_db(&cache_db());
+        let mut state = dump.state();
+
+        let address = Address::with_last_byte(2);
+        let mut account = Account::from(AccountInfo::from_balance(U256::from(5)));
+        account.mark_touch();
+        account.storage = [(
+            U256::from(7),
+            StorageSlot::new_changed(U256::ZERO, U256::from(8)),
+        )]
+        .into_iter()
+        .collect();
+        state.commit(HashMap::from_iter([(address, account)]));
+        state.merge_transitions(BundleRetention::PlainState);
+        let bundle = state.take_bundle();
+
+        let changed = StateDump::from_bundle_state(&bundle);
+        assert_eq!(changed.accounts.len(), 1);
+        assert_eq!(changed.accounts[&address].balance, U256::from(5));
+        assert_eq!(
+            changed.accounts[&address].storage[&B256::from(U256::from(7))],
+            U256::from(8)
+        );
+    }
+}
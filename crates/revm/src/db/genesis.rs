@@ -0,0 +1,428 @@
+//! Loader of go-core style genesis files.
+
+use super::{CacheDB, CacheState, EmptyDB, InMemoryDB, State};
+use crate::primitives::{
+    AccountInfo, Address, BlockEnv, Bytecode, Bytes, CfgEnv, HashMap, IcanAddress, B256, U256,
+};
+use core::str::FromStr;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::BTreeMap, string::String};
+
+/// Genesis of the chain as found in go-core `genesis.json` files.
+///
+/// ICAN addresses must carry the prefix of the configured network.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "GenesisFile")]
+pub struct Genesis {
+    /// Chain configuration.
+    pub config: GenesisConfig,
+    /// Nonce of the genesis block.
+    pub nonce: u64,
+    /// Timestamp of the genesis block.
+    pub timestamp: U256,
+    /// Extra data of the genesis block.
+    pub extra_data: Bytes,
+    /// Energy limit of the genesis block.
+    pub energy_limit: U256,
+    /// Difficulty of the genesis block.
+    pub difficulty: U256,
+    /// Mix hash of the genesis block.
+    pub mix_hash: B256,
+    /// Coinbase of the genesis block.
+    pub coinbase: Address,
+    /// Base fee of the genesis block, if the chain uses it.
+    pub base_fee_per_energy: Option<U256>,
+    /// Accounts allocated in the genesis block.
+    pub alloc: BTreeMap<Address, GenesisAccount>,
+}
+
+/// Genesis file with addresses kept as strings until the network id is known.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenesisFile {
+    #[serde(default)]
+    config: GenesisConfig,
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    nonce: u64,
+    #[serde(default, deserialize_with = "deserialize_u256")]
+    timestamp: U256,
+    #[serde(default)]
+    extra_data: Bytes,
+    #[serde(deserialize_with = "deserialize_u256")]
+    energy_limit: U256,
+    #[serde(default, deserialize_with = "deserialize_u256")]
+    difficulty: U256,
+    #[serde(default)]
+    mix_hash: B256,
+    #[serde(default)]
+    coinbase: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_u256")]
+    base_fee_per_energy: Option<U256>,
+    #[serde(default)]
+    alloc: BTreeMap<String, GenesisAccount>,
+}
+
+impl TryFrom<GenesisFile> for Genesis {
+    type Error = String;
+
+    fn try_from(file: GenesisFile) -> Result<Self, Self::Error> {
+        let network_id = file.config.network_id;
+        let coinbase = match file.coinbase {
+            Some(coinbase) => parse_network_address(&coinbase, network_id)?,
+            None => Address::ZERO,
+        };
+        let alloc = file
+            .alloc
+            .into_iter()
+            .map(|(address, account)| Ok((parse_network_address(&address, network_id)?, account)))
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            config: file.config,
+            nonce: file.nonce,
+            timestamp: file.timestamp,
+            extra_data: file.extra_data,
+            energy_limit: file.energy_limit,
+            difficulty: file.difficulty,
+            mix_hash: file.mix_hash,
+            coinbase,
+            base_fee_per_energy: file.base_fee_per_energy,
+            alloc,
+        })
+    }
+}
+
+/// Chain configuration of the genesis.
+///
+/// Only the network id is used by the EVM, other fields are kept as they are.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisConfig {
+    /// Network id of the chain.
+    pub network_id: u64,
+    /// Fork blocks and other chain specific configuration.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+/// Account allocated in the genesis.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisAccount {
+    /// Balance of the account.
+    #[serde(deserialize_with = "deserialize_u256")]
+    pub balance: U256,
+    /// Nonce of the account.
+    #[serde(default, deserialize_with = "deserialize_u64")]
+    pub nonce: u64,
+    /// Code of the account.
+    #[serde(default)]
+    pub code: Bytes,
+    /// Storage of the account, serialized sorted by slot.
+    #[serde(default, serialize_with = "serialize_storage")]
+    pub storage: HashMap<U256, U256>,
+}
+
+impl GenesisAccount {
+    /// Returns the account info with the analysed code.
+    pub fn account_info(&self) -> AccountInfo {
+        let code = Bytecode::new_raw(self.code.clone());
+        AccountInfo::new(self.balance, self.nonce, code.hash_slow(), code)
+    }
+}
+
+impl FromStr for Genesis {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+impl Genesis {
+    /// Returns the EVM configuration of the chain.
+    pub fn cfg_env(&self) -> CfgEnv {
+        let mut cfg = CfgEnv::default();
+        cfg.network_id = self.config.network_id;
+        cfg
+    }
+
+    /// Returns the environment of the genesis block.
+    pub fn block_env(&self) -> BlockEnv {
+        BlockEnv {
+            number: U256::ZERO,
+            coinbase: self.coinbase,
+            timestamp: self.timestamp,
+            energy_limit: self.energy_limit,
+            basefee: self.base_fee_per_energy.unwrap_or_default(),
+            difficulty: self.difficulty,
+            prevrandao: Some(self.mix_hash),
+            ..Default::default()
+        }
+    }
+
+    /// Returns an in memory database that contains the allocated accounts.
+    pub fn in_memory_db(&self) -> InMemoryDB {
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, account) in &self.alloc {
+            db.insert_account_info(*address, account.account_info());
+            db.accounts
+                .get_mut(address)
+                .expect("account is inserted")
+                .storage
+                .extend(account.storage.iter().map(|(slot, value)| (*slot, *value)));
+        }
+        db
+    }
+
+    /// Returns the cache state that contains the allocated accounts.
+    pub fn cache_state(&self, has_state_clear: bool) -> CacheState {
+        let mut cache = CacheState::new(has_state_clear);
+        for (address, account) in &self.alloc {
+            cache.insert_account_with_storage(
+                *address,
+                account.account_info(),
+                account.storage.clone(),
+            );
+        }
+        cache
+    }
+
+    /// Returns the [State] with the allocated accounts preloaded and bundle updates enabled.
+    pub fn state(&self) -> State<EmptyDB> {
+        State::builder()
+            .with_cached_prestate(self.cache_state(true))
+            .with_bundle_update()
+            .build()
+    }
+}
+
+/// Parses a plain address or an ICAN address, with or without the `0x` prefix.
+///
+/// The network prefix of ICAN addresses is not checked, see [parse_network_address].
+pub fn parse_address(s: &str) -> Result<Address, String> {
+    parse_any_address(s).map(|(address, _)| address)
+}
+
+/// Parses a plain address or an ICAN address of the given network.
+///
+/// ICAN addresses with the prefix of another network are rejected.
+pub fn parse_network_address(s: &str, network_id: u64) -> Result<Address, String> {
+    let (address, ican) = parse_any_address(s)?;
+    match ican {
+        Some(ican) if ican != address.to_ican(network_id) => Err(format!(
+            "invalid ICAN address {s}: not an address of network {network_id}"
+        )),
+        _ => Ok(address),
+    }
+}
+
+/// Parses the address and returns the ICAN address if it was given in that form.
+fn parse_any_address(s: &str) -> Result<(Address, Option<IcanAddress>), String> {
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    if hex.len() == 2 * Address::len_bytes() {
+        let address = Address::from_str(hex).map_err(|e| format!("invalid address {s}: {e}"))?;
+        Ok((address, None))
+    } else {
+        let ican =
+            IcanAddress::from_str(hex).map_err(|e| format!("invalid ICAN address {s}: {e}"))?;
+        if !is_valid_ican_checksum(hex) {
+            return Err(format!("invalid ICAN address {s}: wrong checksum"));
+        }
+        Ok((ican.to_address(), Some(ican)))
+    }
+}
+
+/// Checks the ISO 7064 mod 97-10 checksum of the ICAN address, as used by IBAN.
+fn is_valid_ican_checksum(ican: &str) -> bool {
+    if ican.len() < 4 || !ican.is_ascii() {
+        return false;
+    }
+    let (head, tail) = ican.split_at(4);
+    let mut remainder = 0u32;
+    for c in tail.chars().chain(head.chars()) {
+        remainder = match c.to_digit(36) {
+            Some(digit @ 0..=9) => (remainder * 10 + digit) % 97,
+            Some(letter) => (remainder * 100 + letter) % 97,
+            None => return false,
+        };
+    }
+    remainder == 1
+}
+
+/// Deserializes map of accounts keyed by plain or ICAN addresses.
+pub fn deserialize_alloc<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<Address, T>, D::Error> {
+    BTreeMap::<String, T>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(address, account)| Ok((parse_address(&address)?, account)))
+        .collect::<Result<_, String>>()
+        .map_err(de::Error::custom)
+}
+
+fn serialize_storage<S: Serializer>(
+    storage: &HashMap<U256, U256>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    storage
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .serialize(serializer)
+}
+
+/// Numbers in genesis files are either JSON numbers, or hex or decimal strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HexOrDecimal {
+    Number(u64),
+    String(String),
+}
+
+impl HexOrDecimal {
+    fn into_u256(self) -> Result<U256, String> {
+        match self {
+            Self::Number(number) => Ok(U256::from(number)),
+            Self::String(string) => match string.strip_prefix("0x") {
+                Some(hex) => U256::from_str_radix(hex, 16),
+                None => U256::from_str_radix(&string, 10),
+            }
+            .map_err(|e| format!("invalid number {string}: {e}")),
+        }
+    }
+}
+
+pub(super) fn deserialize_u256<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<U256, D::Error> {
+    HexOrDecimal::deserialize(deserializer)?
+        .into_u256()
+        .map_err(de::Error::custom)
+}
+
+fn deserialize_option_u256<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<U256>, D::Error> {
+    Option::<HexOrDecimal>::deserialize(deserializer)?
+        .map(HexOrDecimal::into_u256)
+        .transpose()
+        .map_err(de::Error::custom)
+}
+
+pub(super) fn deserialize_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let number = deserialize_u256(deserializer)?;
+    number
+        .try_into()
+        .map_err(|_| de::Error::custom(format!("number {number} does not fit into u64")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{address, db::DatabaseRef, hex};
+
+    const GENESIS: &str = r#"{
+        "config": { "networkId": 3, "homesteadBlock": 0 },
+        "nonce": "0x42",
+        "timestamp": "0x10",
+        "energyLimit": "0x47b760",
+        "difficulty": "1",
+        "alloc": {
+            "0x1000000000000000000000000000000000000001": {
+                "balance": "1000000000000000000",
+                "code": "0x6000",
+                "nonce": "0x1",
+                "storage": { "0x01": "0x02" }
+            },
+            "2000000000000000000000000000000000000002": { "balance": "0x10" }
+        }
+    }"#;
+
+    #[test]
+    fn load_genesis() {
+        let genesis: Genesis = GENESIS.parse().unwrap();
+        assert_eq!(genesis.config.network_id, 3);
+        assert_eq!(genesis.nonce, 0x42);
+        assert!(genesis.config.other.contains_key("homesteadBlock"));
+
+        let cfg = genesis.cfg_env();
+        assert_eq!(cfg.network_id, 3);
+        let block = genesis.block_env();
+        assert_eq!(block.number, U256::ZERO);
+        assert_eq!(block.timestamp, U256::from(0x10));
+        assert_eq!(block.energy_limit, U256::from(0x47b760));
+
+        let contract = address!("1000000000000000000000000000000000000001");
+        let db = genesis.in_memory_db();
+        let info = db.basic_ref(contract).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(1_000_000_000_000_000_000u64));
+        assert_eq!(info.nonce, 1);
+        assert_eq!(db.storage_ref(contract, U256::from(1)), Ok(U256::from(2)));
+        let eoa = address!("2000000000000000000000000000000000000002");
+        assert_eq!(
+            db.basic_ref(eoa).unwrap().unwrap().balance,
+            U256::from(0x10)
+        );
+
+        let state = genesis.state();
+        let account = state.cache.accounts.get(&contract).unwrap();
+        assert_eq!(account.account_info().unwrap().nonce, 1);
+    }
+
+    #[test]
+    fn load_ican_alloc() {
+        let plain = address!("3000000000000000000000000000000000000003");
+        let ican = hex::encode(plain.to_ican(3).as_slice());
+        let genesis: Genesis = format!(
+            r#"{{
+                "config": {{ "networkId": 3 }},
+                "energyLimit": "0x10",
+                "coinbase": "0x{ican}",
+                "alloc": {{ "{ican}": {{ "balance": "0x20" }} }}
+            }}"#
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(genesis.coinbase, plain);
+        assert_eq!(genesis.alloc[&plain].balance, U256::from(0x20));
+
+        assert_eq!(parse_address(&ican), Ok(plain));
+        assert_eq!(parse_address(&format!("0x{ican}")), Ok(plain));
+        // checksum digits are the 3rd and 4th characters.
+        let checksum: u8 = ican[2..4].parse().unwrap();
+        let wrong = format!("{}{:02}{}", &ican[..2], (checksum + 1) % 100, &ican[4..]);
+        assert!(parse_address(&wrong).is_err());
+        assert!(parse_address("0x1234").is_err());
+        assert!(parse_address("zz00000000000000000000000000000000000000000000").is_err());
+
+        let invalid = format!(
+            r#"{{ "energyLimit": "0x10", "alloc": {{ "{wrong}": {{ "balance": "0x1" }} }} }}"#
+        );
+        assert!(invalid.parse::<Genesis>().is_err());
+    }
+
+    #[test]
+    fn reject_ican_of_other_network() {
+        let plain = address!("3000000000000000000000000000000000000003");
+        let ican = hex::encode(plain.to_ican(1).as_slice());
+        assert_eq!(parse_network_address(&ican, 1), Ok(plain));
+        assert!(parse_network_address(&ican, 3).is_err());
+        // plain addresses have no prefix to check.
+        assert_eq!(
+            parse_network_address("3000000000000000000000000000000000000003", 3),
+            Ok(plain)
+        );
+
+        let genesis = |network_id: u64| {
+            format!(
+                r#"{{
+                    "config": {{ "networkId": {network_id} }},
+                    "energyLimit": "0x10",
+                    "alloc": {{ "{ican}": {{ "balance": "0x1" }} }}
+                }}"#
+            )
+            .parse::<Genesis>()
+        };
+        assert_eq!(genesis(1).unwrap().alloc[&plain].balance, U256::from(1));
+        assert!(genesis(3).is_err());
+    }
+}
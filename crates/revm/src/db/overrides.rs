@@ -0,0 +1,299 @@
+use crate::primitives::{
+    db::{Database, DatabaseRef},
+    AccountInfo, Address, BlockEnv, Bytecode, Bytes, HashMap, B256, U256,
+};
+
+/// Overrides of multiple accounts, applied by [OverrideDB].
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Overrides of a single account that are applied before execution.
+///
+/// Fields that are `None` are loaded from the underlying database.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct AccountOverride {
+    /// Balance of the account.
+    pub balance: Option<U256>,
+    /// Nonce of the account.
+    pub nonce: Option<u64>,
+    /// Code of the account.
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage of the account, slots that are not present are zero.
+    pub state: Option<HashMap<U256, U256>>,
+    /// Replaces only the given storage slots, other slots are loaded from the database.
+    ///
+    /// Ignored if [`AccountOverride::state`] is set.
+    pub state_diff: Option<HashMap<U256, U256>>,
+}
+
+impl AccountOverride {
+    /// Applies the balance, nonce and code overrides to the account info.
+    ///
+    /// `code` is the analysed [`AccountOverride::code`] together with its hash.
+    fn apply(&self, info: &mut AccountInfo, code: Option<(B256, &Bytecode)>) {
+        if let Some(balance) = self.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = self.nonce {
+            info.nonce = nonce;
+        }
+        if let Some((code_hash, code)) = code {
+            info.code_hash = code_hash;
+            info.code = Some(code.clone());
+        }
+    }
+
+    /// Returns overridden value of the storage slot, if any.
+    fn storage(&self, index: U256) -> Option<U256> {
+        if let Some(state) = &self.state {
+            return Some(state.get(&index).copied().unwrap_or_default());
+        }
+        self.state_diff
+            .as_ref()
+            .and_then(|state_diff| state_diff.get(&index).copied())
+    }
+}
+
+/// Overrides of the block environment fields.
+///
+/// Fields that are `None` are left unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct BlockOverrides {
+    /// Block number.
+    pub number: Option<U256>,
+    /// Block timestamp.
+    pub timestamp: Option<U256>,
+    /// Block coinbase.
+    pub coinbase: Option<Address>,
+    /// Block base fee.
+    pub basefee: Option<U256>,
+    /// Block energy limit.
+    pub energy_limit: Option<U256>,
+}
+
+impl BlockOverrides {
+    /// Applies the overrides to the block environment.
+    pub fn apply(&self, block: &mut BlockEnv) {
+        if let Some(number) = self.number {
+            block.number = number;
+        }
+        if let Some(timestamp) = self.timestamp {
+            block.timestamp = timestamp;
+        }
+        if let Some(coinbase) = self.coinbase {
+            block.coinbase = coinbase;
+        }
+        if let Some(basefee) = self.basefee {
+            block.basefee = basefee;
+        }
+        if let Some(energy_limit) = self.energy_limit {
+            block.energy_limit = energy_limit;
+        }
+    }
+}
+
+/// A [DatabaseRef] overlay that applies [StateOverride] on top of the wrapped database.
+///
+/// The wrapped database is never modified. To collect changes of the execution on top of the
+/// overrides wrap it in [CacheDB](crate::db::CacheDB), or use it directly with
+/// [WrapDatabaseRef](crate::db::WrapDatabaseRef).
+#[derive(Clone, Debug, Default)]
+pub struct OverrideDB<DB> {
+    /// Underlying database.
+    pub db: DB,
+    /// Account overrides.
+    overrides: StateOverride,
+    /// Overridden code of the accounts with its hash.
+    codes: HashMap<Address, (B256, Bytecode)>,
+    /// Overridden code by its hash.
+    contracts: HashMap<B256, Bytecode>,
+}
+
+impl<DB> OverrideDB<DB> {
+    /// Creates new overlay over the database with the given account overrides.
+    pub fn new(db: DB, overrides: StateOverride) -> Self {
+        let mut codes = HashMap::new();
+        let mut contracts = HashMap::new();
+        for (address, account) in overrides.iter() {
+            if let Some(code) = &account.code {
+                let bytecode = Bytecode::new_raw(code.clone());
+                let code_hash = bytecode.hash_slow();
+                contracts.insert(code_hash, bytecode.clone());
+                codes.insert(*address, (code_hash, bytecode));
+            }
+        }
+        Self {
+            db,
+            overrides,
+            codes,
+            contracts,
+        }
+    }
+
+    /// Returns the account overrides.
+    pub fn overrides(&self) -> &StateOverride {
+        &self.overrides
+    }
+
+    /// Consumes the overlay and returns the underlying database.
+    pub fn into_inner(self) -> DB {
+        self.db
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for OverrideDB<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_ref(address)?;
+        let Some(account) = self.overrides.get(&address) else {
+            return Ok(info);
+        };
+        // overridden account exists even if it is not present in the database.
+        let mut info = info.unwrap_or_default();
+        let code = self
+            .codes
+            .get(&address)
+            .map(|(code_hash, code)| (*code_hash, code));
+        account.apply(&mut info, code);
+        Ok(Some(info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.contracts.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self
+            .overrides
+            .get(&address)
+            .and_then(|account| account.storage(index))
+        {
+            Some(value) => Ok(value),
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+impl<DB: DatabaseRef> Database for OverrideDB<DB> {
+    type Error = DB::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{CacheDB, EmptyDB};
+
+    #[test]
+    fn override_account_and_storage() {
+        let address = Address::with_last_byte(42);
+        let mut base = CacheDB::new(EmptyDB::default());
+        base.insert_account_info(
+            address,
+            AccountInfo {
+                nonce: 7,
+                ..Default::default()
+            },
+        );
+        base.insert_account_storage(address, U256::from(1), U256::from(1))
+            .unwrap();
+        base.insert_account_storage(address, U256::from(2), U256::from(2))
+            .unwrap();
+
+        let code = Bytes::from_static(&[0x60, 0x00]);
+        let overrides = [(
+            address,
+            AccountOverride {
+                balance: Some(U256::from(100)),
+                code: Some(code.clone()),
+                state_diff: Some([(U256::from(1), U256::from(10))].into()),
+                ..Default::default()
+            },
+        )]
+        .into();
+        let db = OverrideDB::new(&base, overrides);
+
+        let info = db.basic_ref(address).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(100));
+        assert_eq!(info.nonce, 7);
+        assert_eq!(
+            db.code_by_hash_ref(info.code_hash)
+                .unwrap()
+                .original_bytes(),
+            code
+        );
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(10)));
+        assert_eq!(db.storage_ref(address, U256::from(2)), Ok(U256::from(2)));
+
+        // underlying database is untouched.
+        assert_eq!(
+            base.basic_ref(address).unwrap().unwrap().balance,
+            U256::ZERO
+        );
+    }
+
+    #[test]
+    fn override_full_storage() {
+        let address = Address::with_last_byte(42);
+        let mut base = CacheDB::new(EmptyDB::default());
+        base.insert_account_storage(address, U256::from(1), U256::from(1))
+            .unwrap();
+
+        let overrides = [(
+            address,
+            AccountOverride {
+                state: Some([(U256::from(2), U256::from(2))].into()),
+                ..Default::default()
+            },
+        )]
+        .into();
+        let mut db = CacheDB::new(OverrideDB::new(base, overrides));
+
+        assert_eq!(db.storage(address, U256::from(1)), Ok(U256::ZERO));
+        assert_eq!(db.storage(address, U256::from(2)), Ok(U256::from(2)));
+    }
+
+    #[test]
+    fn apply_block_overrides() {
+        let mut block = BlockEnv::default();
+        BlockOverrides {
+            number: Some(U256::from(10)),
+            basefee: Some(U256::from(7)),
+            ..Default::default()
+        }
+        .apply(&mut block);
+        assert_eq!(block.number, U256::from(10));
+        assert_eq!(block.basefee, U256::from(7));
+        assert_eq!(block.timestamp, BlockEnv::default().timestamp);
+    }
+}
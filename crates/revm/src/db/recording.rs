@@ -0,0 +1,362 @@
+//! Recording of database reads and their offline replay.
+
+use crate::primitives::{
+    db::{Database, DatabaseCommit, DatabaseRef},
+    Account, AccountInfo, Address, Bytecode, Bytes, HashMap, B256, U256,
+};
+use core::{cell::RefCell, fmt};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs::File, io, path::Path, vec::Vec};
+
+/// Responses of the database recorded during execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recording {
+    /// Accounts by address, `None` if the account does not exist.
+    pub accounts: BTreeMap<Address, Option<RecordedAccount>>,
+    /// Contract code by code hash.
+    pub contracts: BTreeMap<B256, Bytes>,
+    /// Storage slots by account address.
+    pub storage: BTreeMap<Address, BTreeMap<U256, U256>>,
+    /// Block hashes by block number.
+    pub block_hashes: BTreeMap<U256, B256>,
+}
+
+/// Account as returned by [Database::basic].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedAccount {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code_hash: B256,
+    /// Code if it was returned together with the account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+}
+
+impl From<&AccountInfo> for RecordedAccount {
+    fn from(info: &AccountInfo) -> Self {
+        Self {
+            balance: info.balance,
+            nonce: info.nonce,
+            code_hash: info.code_hash,
+            code: info.code.as_ref().map(|code| code.original_bytes()),
+        }
+    }
+}
+
+impl From<&RecordedAccount> for AccountInfo {
+    fn from(account: &RecordedAccount) -> Self {
+        AccountInfo {
+            balance: account.balance,
+            nonce: account.nonce,
+            code_hash: account.code_hash,
+            code: account.code.clone().map(Bytecode::new_raw),
+        }
+    }
+}
+
+impl Recording {
+    /// Loads the recording from the JSON file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = io::BufReader::new(File::open(path)?);
+        serde_json::from_reader(file).map_err(Into::into)
+    }
+
+    /// Saves the recording to the JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = io::BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, self).map_err(Into::into)
+    }
+
+    fn record_basic(&mut self, address: Address, info: &Option<AccountInfo>) {
+        self.accounts
+            .insert(address, info.as_ref().map(RecordedAccount::from));
+    }
+
+    fn record_code(&mut self, code_hash: B256, code: &Bytecode) {
+        self.contracts.insert(code_hash, code.original_bytes());
+    }
+
+    fn record_storage(&mut self, address: Address, index: U256, value: U256) {
+        self.storage
+            .entry(address)
+            .or_default()
+            .insert(index, value);
+    }
+
+    fn record_block_hash(&mut self, number: U256, hash: B256) {
+        self.block_hashes.insert(number, hash);
+    }
+}
+
+/// Database wrapper that records every response of the inner database.
+///
+/// The recording can be saved and served offline by [ReplayDB], so an execution that needs a
+/// remote database can be reproduced without it. To record only the reads of the remote
+/// database, wrap it before it is wrapped by a cache.
+#[derive(Debug, Default)]
+pub struct RecordingDB<DB> {
+    /// Inner database.
+    pub db: DB,
+    recording: RefCell<Recording>,
+}
+
+impl<DB> RecordingDB<DB> {
+    /// Creates new recording database with an empty recording.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            recording: RefCell::default(),
+        }
+    }
+
+    /// Returns a copy of the responses recorded so far.
+    pub fn recording(&self) -> Recording {
+        self.recording.borrow().clone()
+    }
+
+    /// Returns the inner database and the recording.
+    pub fn into_parts(self) -> (DB, Recording) {
+        (self.db, self.recording.into_inner())
+    }
+
+    /// Saves the responses recorded so far to the JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.recording.borrow().save(path)
+    }
+}
+
+impl<DB: Database> Database for RecordingDB<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        self.recording.get_mut().record_basic(address, &info);
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        self.recording.get_mut().record_code(code_hash, &code);
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.recording
+            .get_mut()
+            .record_storage(address, index, value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.recording.get_mut().record_block_hash(number, hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for RecordingDB<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_ref(address)?;
+        self.recording.borrow_mut().record_basic(address, &info);
+        Ok(info)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash_ref(code_hash)?;
+        self.recording.borrow_mut().record_code(code_hash, &code);
+        Ok(code)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage_ref(address, index)?;
+        self.recording
+            .borrow_mut()
+            .record_storage(address, index, value);
+        Ok(value)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash_ref(number)?;
+        self.recording.borrow_mut().record_block_hash(number, hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for RecordingDB<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes)
+    }
+}
+
+/// Read that was not recorded.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UnrecordedRead {
+    Basic(Address),
+    CodeByHash(B256),
+    Storage(Address, U256),
+    BlockHash(U256),
+}
+
+impl fmt::Display for UnrecordedRead {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic(address) => write!(f, "account {address} was not recorded"),
+            Self::CodeByHash(hash) => write!(f, "code {hash} was not recorded"),
+            Self::Storage(address, index) => {
+                write!(f, "storage {index:#x} of {address} was not recorded")
+            }
+            Self::BlockHash(number) => write!(f, "hash of block {number} was not recorded"),
+        }
+    }
+}
+
+impl std::error::Error for UnrecordedRead {}
+
+/// Database that serves the responses of a [Recording] offline.
+///
+/// Reads that were not recorded fail with [UnrecordedRead] and are collected, so a fixture
+/// that is missing data can be reported in full.
+#[derive(Debug, Default)]
+pub struct ReplayDB {
+    recording: Recording,
+    unrecorded: RefCell<Vec<UnrecordedRead>>,
+}
+
+impl ReplayDB {
+    /// Creates new database serving the recording.
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            unrecorded: RefCell::default(),
+        }
+    }
+
+    /// Loads the recording from the JSON file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Recording::load(path).map(Self::new)
+    }
+
+    /// Returns the reads that were not recorded, in the order they were done.
+    pub fn unrecorded(&self) -> Vec<UnrecordedRead> {
+        self.unrecorded.borrow().clone()
+    }
+
+    fn unrecorded_read(&self, read: UnrecordedRead) -> UnrecordedRead {
+        self.unrecorded.borrow_mut().push(read.clone());
+        read
+    }
+}
+
+impl DatabaseRef for ReplayDB {
+    type Error = UnrecordedRead;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.recording.accounts.get(&address) {
+            Some(account) => Ok(account.as_ref().map(AccountInfo::from)),
+            None => Err(self.unrecorded_read(UnrecordedRead::Basic(address))),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.recording.contracts.get(&code_hash) {
+            Some(code) => Ok(Bytecode::new_raw(code.clone())),
+            None => Err(self.unrecorded_read(UnrecordedRead::CodeByHash(code_hash))),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self
+            .recording
+            .storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index));
+        match value {
+            Some(value) => Ok(*value),
+            None => Err(self.unrecorded_read(UnrecordedRead::Storage(address, index))),
+        }
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        match self.recording.block_hashes.get(&number) {
+            Some(hash) => Ok(*hash),
+            None => Err(self.unrecorded_read(UnrecordedRead::BlockHash(number))),
+        }
+    }
+}
+
+impl Database for ReplayDB {
+    type Error = UnrecordedRead;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{InMemoryDB, WrapDatabaseRef},
+        primitives::{EVMError, TransactTo},
+        Evm,
+    };
+
+    fn transfer<DB: DatabaseRef>(db: DB) -> Result<(), EVMError<DB::Error>> {
+        Evm::builder()
+            .with_db(WrapDatabaseRef(db))
+            .modify_tx_env(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.transact_to = TransactTo::Call(Address::with_last_byte(2));
+                tx.value = U256::from(1);
+            })
+            .build()
+            .transact()
+            .map(|_| ())
+    }
+
+    #[test]
+    fn replay_recorded_execution() {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            Address::with_last_byte(1),
+            AccountInfo::from_balance(U256::from(10)),
+        );
+        let recording_db = RecordingDB::new(db);
+        transfer(&recording_db).unwrap();
+        let recording = recording_db.recording();
+        let caller = recording.accounts[&Address::with_last_byte(1)].as_ref();
+        assert_eq!(caller.map(|account| account.balance), Some(U256::from(10)));
+        assert_eq!(recording.accounts[&Address::with_last_byte(2)], None);
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let replay = ReplayDB::new(serde_json::from_str(&json).unwrap());
+        transfer(&replay).unwrap();
+        assert!(replay.unrecorded().is_empty());
+    }
+
+    #[test]
+    fn report_unrecorded_reads() {
+        let replay = ReplayDB::default();
+        let Err(EVMError::Database(read)) = transfer(&replay) else {
+            panic!("read is not recorded");
+        };
+        assert_eq!(replay.unrecorded(), vec![read]);
+    }
+}
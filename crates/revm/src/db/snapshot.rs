@@ -0,0 +1,340 @@
+use super::{AccountState, CacheDB, DbAccount};
+use crate::primitives::{
+    db::{Database, DatabaseCommit, DatabaseRef},
+    Account, AccountInfo, Address, Bytecode, HashMap, B256, SHA3_EMPTY, U256,
+};
+use std::vec::Vec;
+
+/// Identifier of a snapshot taken with [SnapshotDB::snapshot].
+pub type SnapshotId = u64;
+
+/// A [CacheDB] wrapper that can take snapshots of its state and revert to them.
+///
+/// Every [DatabaseCommit::commit] done while at least one snapshot is active records
+/// the values it overrides, so reverting only undoes the changes instead of keeping
+/// a copy of the whole database. Snapshots can be nested and reverting to a snapshot
+/// discards it together with all snapshots taken after it.
+///
+/// Accounts and storage slots that are only read through the [Database] interface are
+/// not reverted, as they mirror the values of the underlying database.
+#[derive(Debug, Clone)]
+pub struct SnapshotDB<ExtDB> {
+    /// Cache that all changes are committed to.
+    pub cache: CacheDB<ExtDB>,
+    /// Reverts of commits done while snapshots were active, oldest first.
+    journal: Vec<CommitRevert>,
+    /// Active snapshots, oldest first.
+    snapshots: Vec<Snapshot>,
+    /// Id given to the next snapshot.
+    next_id: SnapshotId,
+}
+
+/// Position of the journal and logs when the snapshot was taken.
+#[derive(Debug, Clone, Copy)]
+struct Snapshot {
+    id: SnapshotId,
+    journal_len: usize,
+    logs_len: usize,
+}
+
+/// Values needed to undo a single commit.
+#[derive(Debug, Clone, Default)]
+struct CommitRevert {
+    /// Previous state of the accounts changed by the commit.
+    accounts: Vec<(Address, DbAccountRevert)>,
+    /// Contracts that were inserted by the commit.
+    contracts: Vec<B256>,
+}
+
+/// Previous state of a single cached account.
+#[derive(Debug, Clone)]
+enum DbAccountRevert {
+    /// Account was not cached, it is removed on revert.
+    Remove,
+    /// Commit cleared the account storage so the whole account is restored.
+    Restore(DbAccount),
+    /// Info and state are restored and the changed slots are set to their previous values.
+    /// `None` means that slot was not cached and it is removed.
+    Update {
+        info: AccountInfo,
+        account_state: AccountState,
+        storage: Vec<(U256, Option<U256>)>,
+    },
+}
+
+impl<ExtDB: Default> Default for SnapshotDB<ExtDB> {
+    fn default() -> Self {
+        Self::new(CacheDB::default())
+    }
+}
+
+impl<ExtDB> From<CacheDB<ExtDB>> for SnapshotDB<ExtDB> {
+    fn from(cache: CacheDB<ExtDB>) -> Self {
+        Self::new(cache)
+    }
+}
+
+impl<ExtDB> SnapshotDB<ExtDB> {
+    /// Creates new snapshot database on top of the given cache.
+    pub fn new(cache: CacheDB<ExtDB>) -> Self {
+        Self {
+            cache,
+            journal: Vec::new(),
+            snapshots: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Consumes the database and returns the inner cache with all changes applied.
+    pub fn into_inner(self) -> CacheDB<ExtDB> {
+        self.cache
+    }
+
+    /// Takes a snapshot of the current state and returns its id.
+    pub fn snapshot(&mut self) -> SnapshotId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.snapshots.push(Snapshot {
+            id,
+            journal_len: self.journal.len(),
+            logs_len: self.cache.logs.len(),
+        });
+        id
+    }
+
+    /// Returns `true` if snapshot with the given id can be reverted to.
+    pub fn has_snapshot(&self, id: SnapshotId) -> bool {
+        self.snapshots.iter().any(|snapshot| snapshot.id == id)
+    }
+
+    /// Reverts accounts, storage, contracts and logs to the state they had when the
+    /// snapshot was taken.
+    ///
+    /// The snapshot and all snapshots taken after it are discarded. Returns `false` if
+    /// snapshot is not known, in which case nothing is changed.
+    pub fn revert_to(&mut self, id: SnapshotId) -> bool {
+        let Some(index) = self.snapshots.iter().position(|snapshot| snapshot.id == id) else {
+            return false;
+        };
+        let snapshot = self.snapshots[index];
+        self.snapshots.truncate(index);
+
+        for revert in self.journal.drain(snapshot.journal_len..).rev() {
+            revert.apply(&mut self.cache);
+        }
+        self.cache.logs.truncate(snapshot.logs_len);
+        true
+    }
+
+    /// Creates revert for the given changes against the current cache.
+    fn commit_revert(&self, changes: &HashMap<Address, Account>) -> CommitRevert {
+        let mut revert = CommitRevert::default();
+        for (address, account) in changes {
+            if !account.is_touched() {
+                continue;
+            }
+            let clears_storage = account.is_selfdestructed() || account.is_created();
+            let account_revert = match self.cache.accounts.get(address) {
+                None => DbAccountRevert::Remove,
+                Some(db_account) if clears_storage => DbAccountRevert::Restore(db_account.clone()),
+                Some(db_account) => DbAccountRevert::Update {
+                    info: db_account.info.clone(),
+                    account_state: db_account.account_state.clone(),
+                    storage: account
+                        .storage
+                        .keys()
+                        .map(|slot| (*slot, db_account.storage.get(slot).copied()))
+                        .collect(),
+                },
+            };
+            revert.accounts.push((*address, account_revert));
+
+            if account.is_selfdestructed() {
+                continue;
+            }
+            // same code hash as the one [CacheDB::insert_contract] is going to use.
+            if let Some(code) = account.info.code.as_ref().filter(|code| !code.is_empty()) {
+                let code_hash = if account.info.code_hash == SHA3_EMPTY {
+                    code.hash_slow()
+                } else {
+                    account.info.code_hash
+                };
+                if !self.cache.contracts.contains_key(&code_hash)
+                    && !revert.contracts.contains(&code_hash)
+                {
+                    revert.contracts.push(code_hash);
+                }
+            }
+        }
+        revert
+    }
+}
+
+impl CommitRevert {
+    /// Undoes the commit on the given cache.
+    fn apply<ExtDB>(self, cache: &mut CacheDB<ExtDB>) {
+        for (address, account_revert) in self.accounts {
+            match account_revert {
+                DbAccountRevert::Remove => {
+                    cache.accounts.remove(&address);
+                }
+                DbAccountRevert::Restore(db_account) => {
+                    cache.accounts.insert(address, db_account);
+                }
+                DbAccountRevert::Update {
+                    info,
+                    account_state,
+                    storage,
+                } => {
+                    let db_account = cache.accounts.entry(address).or_default();
+                    db_account.info = info;
+                    db_account.account_state = account_state;
+                    for (slot, value) in storage {
+                        match value {
+                            Some(value) => db_account.storage.insert(slot, value),
+                            None => db_account.storage.remove(&slot),
+                        };
+                    }
+                }
+            }
+        }
+        for code_hash in self.contracts {
+            cache.contracts.remove(&code_hash);
+        }
+    }
+}
+
+impl<ExtDB> DatabaseCommit for SnapshotDB<ExtDB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        if !self.snapshots.is_empty() {
+            let revert = self.commit_revert(&changes);
+            self.journal.push(revert);
+        }
+        self.cache.commit(changes)
+    }
+}
+
+impl<ExtDB: DatabaseRef> Database for SnapshotDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.cache.basic(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.cache.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.cache.storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.cache.block_hash(number)
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for SnapshotDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.cache.basic_ref(address)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.cache.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.cache.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.cache.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotDB;
+    use crate::db::EmptyDB;
+    use crate::primitives::{
+        db::{DatabaseCommit, DatabaseRef},
+        Account, AccountInfo, Address, Bytecode, Bytes, HashMap, StorageSlot, U256,
+    };
+
+    fn changes(
+        address: Address,
+        info: AccountInfo,
+        slots: &[(u64, u64)],
+    ) -> HashMap<Address, Account> {
+        let mut account = Account::from(info);
+        account.mark_touch();
+        account.storage = slots
+            .iter()
+            .map(|(slot, value)| {
+                (
+                    U256::from(*slot),
+                    StorageSlot::new_changed(U256::ZERO, U256::from(*value)),
+                )
+            })
+            .collect();
+        [(address, account)].into_iter().collect()
+    }
+
+    #[test]
+    fn revert_to_nested_snapshots() {
+        let address = Address::with_last_byte(42);
+        let mut db = SnapshotDB::<EmptyDB>::default();
+        db.cache
+            .insert_account_info(address, AccountInfo::from_balance(U256::from(1)));
+
+        let first = db.snapshot();
+        db.commit(changes(
+            address,
+            AccountInfo::from_balance(U256::from(2)),
+            &[(1, 1)],
+        ));
+        let second = db.snapshot();
+        db.commit(changes(
+            address,
+            AccountInfo::from_balance(U256::from(3)),
+            &[(1, 2), (2, 2)],
+        ));
+
+        assert!(db.revert_to(second));
+        let info = db.basic_ref(address).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(2));
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(1)));
+        assert_eq!(db.storage_ref(address, U256::from(2)), Ok(U256::ZERO));
+        assert!(!db.has_snapshot(second));
+
+        assert!(db.revert_to(first));
+        let info = db.basic_ref(address).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(1));
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::ZERO));
+
+        // reverting discards the snapshot.
+        assert!(!db.revert_to(first));
+    }
+
+    #[test]
+    fn revert_removes_new_accounts_and_contracts() {
+        let address = Address::with_last_byte(42);
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        let code_hash = code.hash_slow();
+        let mut db = SnapshotDB::<EmptyDB>::default();
+
+        let snapshot = db.snapshot();
+        db.commit(changes(
+            address,
+            AccountInfo::new(U256::from(1), 1, code_hash, code),
+            &[],
+        ));
+        assert!(db.cache.contracts.contains_key(&code_hash));
+
+        assert!(db.revert_to(snapshot));
+        assert!(!db.cache.accounts.contains_key(&address));
+        assert!(!db.cache.contracts.contains_key(&code_hash));
+    }
+}
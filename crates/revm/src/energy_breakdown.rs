@@ -0,0 +1,196 @@
+//! Handler register that splits the used energy of the transaction by its sources.
+//!
+//! The breakdown is collected only when [energy_breakdown_handle_register] is appended to the
+//! handler, the default handler is not affected by it.
+
+use crate::{
+    handler::register::EvmHandler,
+    interpreter::{
+        energy::initial_tx_energy_breakdown,
+        opcode::{self, InstructionTables},
+        Interpreter,
+    },
+    primitives::{db::Database, EVMError, EnergyBreakdown, ResultAndState},
+    Evm, FrameOrResult, FrameResult,
+};
+use core::cell::RefCell;
+use revm_interpreter::{CallOutcome, CreateOutcome};
+use std::{boxed::Box, rc::Rc, sync::Arc};
+
+/// Register handles that fill [ResultAndState::energy_breakdown].
+///
+/// Intrinsic energy is taken from the validation, memory expansion, storage, precompiles
+/// and code deposit from the execution and the refund from the last frame. The `execution`
+/// component is the remainder of the used energy.
+pub fn energy_breakdown_handle_register<'a, EXT: 'a, DB: Database + 'a>(
+    handler: &mut EvmHandler<'a, EXT, DB>,
+) {
+    let breakdown = Rc::new(RefCell::new(EnergyBreakdown::default()));
+    let spec_id = handler.cfg.spec_id;
+
+    // Validation, the breakdown is reset for every transaction.
+    let inner = breakdown.clone();
+    let old_handle = handler.validation.initial_tx_energy.clone();
+    handler.validation.initial_tx_energy = Arc::new(move |env| {
+        let initial_energy = old_handle(env)?;
+        *inner.borrow_mut() = EnergyBreakdown {
+            intrinsic: initial_tx_energy_breakdown(
+                spec_id,
+                &env.tx.data,
+                env.tx.transact_to.is_create(),
+                &env.tx.access_list,
+            ),
+            ..Default::default()
+        };
+        Ok(initial_energy)
+    });
+
+    // Storage instructions, cold if the slot is not yet loaded into the journal.
+    let mut table = handler
+        .take_instruction_table()
+        .expect("Handler must have instruction table");
+    table.convert_boxed();
+    if let InstructionTables::Boxed(table) = &mut table {
+        for op in [opcode::SLOAD, opcode::SSTORE] {
+            let inner = breakdown.clone();
+            let old = core::mem::replace(&mut table[op as usize], Box::new(|_, _| ()));
+            table[op as usize] = Box::new(
+                move |interpreter: &mut Interpreter, host: &mut Evm<'a, EXT, DB>| {
+                    let is_cold = interpreter.stack.peek(0).map_or(false, |key| {
+                        host.context
+                            .evm
+                            .journaled_state
+                            .state
+                            .get(&interpreter.contract.address)
+                            .map_or(true, |account| !account.storage.contains_key(&key))
+                    });
+                    let remaining = interpreter.energy.remaining();
+                    old(interpreter, host);
+                    let cost = remaining.saturating_sub(interpreter.energy.remaining());
+
+                    let storage = &mut inner.borrow_mut().storage;
+                    match (op == opcode::SLOAD, is_cold) {
+                        (true, true) => storage.cold_sload += cost,
+                        (true, false) => storage.warm_sload += cost,
+                        (false, true) => storage.cold_sstore += cost,
+                        (false, false) => storage.warm_sstore += cost,
+                    }
+                },
+            );
+        }
+    }
+    handler.set_instruction_table(table);
+
+    // Precompiles return the result without creating a frame.
+    let inner = breakdown.clone();
+    let old_handle = handler.execution.call.clone();
+    handler.execution.call = Arc::new(
+        move |ctx, inputs| -> Result<FrameOrResult, EVMError<DB::Error>> {
+            let is_precompile = ctx.evm.precompiles.contains_key(&inputs.contract);
+            let frame_or_result = old_handle(ctx, inputs)?;
+            if let FrameOrResult::Result(FrameResult::Call(outcome)) = &frame_or_result {
+                if is_precompile {
+                    inner.borrow_mut().precompile += outcome.result.energy.spent();
+                }
+            }
+            Ok(frame_or_result)
+        },
+    );
+
+    // Memory expansion cost is accumulated in the energy of every frame.
+    let inner = breakdown.clone();
+    let old_handle = handler.execution.call_return.clone();
+    handler.execution.call_return = Arc::new(
+        move |ctx, frame, result| -> Result<CallOutcome, EVMError<DB::Error>> {
+            inner.borrow_mut().memory += result.energy.memory();
+            old_handle(ctx, frame, result)
+        },
+    );
+
+    let inner = breakdown.clone();
+    let old_handle = handler.execution.create_return.clone();
+    handler.execution.create_return = Arc::new(
+        move |ctx, frame, result| -> Result<CreateOutcome, EVMError<DB::Error>> {
+            let spent = result.energy.spent();
+            inner.borrow_mut().memory += result.energy.memory();
+            let outcome = old_handle(ctx, frame, result)?;
+            // code deposit is the only cost charged by the create return.
+            inner.borrow_mut().code_deposit += outcome.result.energy.spent().saturating_sub(spent);
+            Ok(outcome)
+        },
+    );
+
+    // Refund before and after the cap.
+    let inner = breakdown.clone();
+    let old_handle = handler.execution.last_frame_return.clone();
+    handler.execution.last_frame_return = Arc::new(move |ctx, frame_result| {
+        let refund = frame_result.energy().refunded().max(0) as u64;
+        old_handle(ctx, frame_result)?;
+        let refunded = frame_result.energy().refunded().max(0) as u64;
+        let mut breakdown = inner.borrow_mut();
+        breakdown.refunded = refunded;
+        breakdown.refund_capped = refund.saturating_sub(refunded);
+        Ok(())
+    });
+
+    let old_handle = handler.post_execution.output.clone();
+    handler.post_execution.output = Arc::new(
+        move |ctx, frame_result| -> Result<ResultAndState, EVMError<DB::Error>> {
+            let mut output = old_handle(ctx, frame_result)?;
+            let mut breakdown = breakdown.take();
+            breakdown.execution = (output.result.energy_used() + breakdown.refunded)
+                .saturating_sub(breakdown.intrinsic.total())
+                .saturating_sub(breakdown.memory)
+                .saturating_sub(breakdown.storage.total())
+                .saturating_sub(breakdown.precompile)
+                .saturating_sub(breakdown.code_deposit);
+            output.energy_breakdown = Some(breakdown);
+            Ok(output)
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        primitives::{address, Bytecode, Bytes, TransactTo},
+    };
+
+    #[test]
+    fn storage_and_memory_breakdown() {
+        // SSTORE(0, 1), SLOAD(0), MSTORE(0, value), STOP
+        let code = Bytes::from_static(&[
+            0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x54, 0x60, 0x00, 0x52, 0x00,
+        ]);
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.data = Bytes::from_static(&[0, 1]);
+                tx.energy_limit = 100_000;
+            })
+            .append_handler_register(energy_breakdown_handle_register)
+            .build();
+
+        let output = evm.transact().unwrap();
+        let breakdown = output.energy_breakdown.unwrap();
+        assert_eq!(breakdown.intrinsic.base, 21000);
+        assert_eq!(breakdown.intrinsic.calldata, 4 + 16);
+        assert!(breakdown.storage.cold_sstore > 0);
+        assert_eq!(breakdown.storage.warm_sload, 100);
+        assert_eq!(breakdown.memory, 3);
+        assert_eq!(
+            breakdown.intrinsic.total()
+                + breakdown.execution
+                + breakdown.memory
+                + breakdown.storage.total()
+                - breakdown.refunded,
+            output.result.energy_used()
+        );
+    }
+}
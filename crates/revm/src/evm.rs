@@ -3,15 +3,16 @@ use crate::{
     db::{Database, DatabaseCommit, EmptyDB},
     handler::Handler,
     interpreter::{
-        opcode::InstructionTables, Host, Interpreter, InterpreterAction, SStoreResult,
+        opcode::{self, InstructionTables},
+        Host, InstructionResult, Interpreter, InterpreterAction, InterpreterResult, SStoreResult,
         SelfDestructResult, SharedMemory,
     },
     primitives::{
         specification::SpecId, Address, BlockEnv, Bytecode, CfgEnv, EVMError, EVMResult, Env,
-        EnvWithHandlerCfg, ExecutionResult, HandlerCfg, Log, ResultAndState, TransactTo, TxEnv,
-        B256, U256,
+        EnvWithHandlerCfg, ExecutionResult, ExecutionStats, FailureLocation, FixedBytes,
+        FrameLocation, HandlerCfg, Log, ResultAndState, TransactTo, TxEnv, B256, U256,
     },
-    Context, ContextWithHandlerCfg, Frame, FrameOrResult, FrameResult,
+    Context, ContextWithHandlerCfg, Frame, FrameOrResult, FrameResult, Interrupt,
 };
 use core::fmt;
 use revm_interpreter::{CallInputs, CreateInputs};
@@ -28,6 +29,8 @@ pub struct Evm<'a, EXT, DB: Database> {
     /// Handler is a component of the of EVM that contains all the logic. Handler contains specification id
     /// and it different depending on the specified fork.
     pub handler: Handler<'a, Self, EXT, DB>,
+    /// Location of the last failed frame, recorded only if [`CfgEnv::debug`] is enabled.
+    failure_location: Option<FailureLocation>,
 }
 
 impl<EXT, DB> fmt::Debug for Evm<'_, EXT, DB>
@@ -46,7 +49,7 @@ where
 impl<EXT, DB: Database + DatabaseCommit> Evm<'_, EXT, DB> {
     /// Commit the changes to the database.
     pub fn transact_commit(&mut self) -> Result<ExecutionResult, EVMError<DB::Error>> {
-        let ResultAndState { result, state } = self.transact()?;
+        let ResultAndState { result, state, .. } = self.transact()?;
         self.context.evm.db.commit(state);
         Ok(result)
     }
@@ -66,7 +69,11 @@ impl<'a, EXT, DB: Database> Evm<'a, EXT, DB> {
         handler: Handler<'a, Self, EXT, DB>,
     ) -> Evm<'a, EXT, DB> {
         context.evm.journaled_state.set_spec_id(handler.cfg.spec_id);
-        Evm { context, handler }
+        Evm {
+            context,
+            handler,
+            failure_location: None,
+        }
     }
 
     /// Allow for evm setting to be modified by feeding current evm
@@ -108,6 +115,10 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
             .validation()
             .initial_tx_energy(&self.context.evm.env)?;
         let output = self.transact_preverified_inner(initial_energy_spend);
+        if let Err(EVMError::Interrupted) = output {
+            // discard the changes of the unfinished execution.
+            let _ = self.context.evm.journaled_state.finalize();
+        }
         self.handler.post_execution().end(&mut self.context, output)
     }
 
@@ -180,6 +191,10 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
             .tx_against_state(&mut self.context)?;
 
         let output = self.transact_preverified_inner(initial_energy_spend);
+        if let Err(EVMError::Interrupted) = output {
+            // discard the changes of the unfinished execution.
+            let _ = self.context.evm.journaled_state.finalize();
+        }
         self.handler.post_execution().end(&mut self.context, output)
     }
 
@@ -248,6 +263,10 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         let mut call_stack: Vec<Frame> = Vec::with_capacity(1025);
         call_stack.push(first_frame);
 
+        let debug = self.context.evm.env.cfg.debug;
+        let instruction_limit = self.context.evm.env.cfg.instruction_limit;
+        self.failure_location = None;
+
         #[cfg(feature = "memory_limit")]
         let mut shared_memory =
             SharedMemory::new_with_memory_limit(self.context.evm.env.cfg.memory_limit);
@@ -258,11 +277,46 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
 
         // peek last stack frame.
         let mut stack_frame = call_stack.last_mut().unwrap();
+        // frame limit halts every frame down to the first one, like the instruction limit.
+        let mut frame_limit_reached = false;
 
         loop {
+            // interrupt is also checked per frame as calls don't need jumps to loop.
+            if self
+                .context
+                .evm
+                .interrupt
+                .as_mut()
+                .is_some_and(Interrupt::is_interrupted)
+            {
+                return Err(EVMError::Interrupted);
+            }
             // run interpreter
             let interpreter = &mut stack_frame.frame_data_mut().interpreter;
-            let next_action = interpreter.run(shared_memory, instruction_table, self);
+            if frame_limit_reached {
+                interpreter.instruction_result = InstructionResult::CallFrameLimit;
+            }
+            // instructions are only counted when they are limited.
+            let next_action = match instruction_limit {
+                Some(limit) => {
+                    let executed = self
+                        .context
+                        .evm
+                        .execution_stats
+                        .instructions
+                        .unwrap_or_default();
+                    interpreter.instruction_limit = limit.saturating_sub(executed);
+                    let action = interpreter.run_with_instruction_limit(
+                        shared_memory,
+                        instruction_table,
+                        self,
+                    );
+                    self.context.evm.execution_stats.instructions =
+                        Some(executed + interpreter.instruction_count);
+                    action
+                }
+                None => interpreter.run(shared_memory, instruction_table, self),
+            };
 
             // take error and break the loop if there is any.
             // This error is set From Interpreter when it's interacting with Host.
@@ -272,8 +326,28 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
 
             let exec = &mut self.handler.execution;
             let frame_or_result = match next_action {
-                InterpreterAction::Call { inputs } => exec.call(&mut self.context, inputs)?,
-                InterpreterAction::Create { inputs } => exec.create(&mut self.context, inputs)?,
+                InterpreterAction::Call { inputs } => {
+                    let frame_or_result = exec.call(&mut self.context, inputs)?;
+                    if debug {
+                        record_unstarted_frame(
+                            &mut self.failure_location,
+                            &call_stack,
+                            &frame_or_result,
+                        );
+                    }
+                    frame_or_result
+                }
+                InterpreterAction::Create { inputs } => {
+                    let frame_or_result = exec.create(&mut self.context, inputs)?;
+                    if debug {
+                        record_unstarted_frame(
+                            &mut self.failure_location,
+                            &call_stack,
+                            &frame_or_result,
+                        );
+                    }
+                    frame_or_result
+                }
                 InterpreterAction::Return { result } => {
                     // free memory context.
                     shared_memory.free_context();
@@ -283,8 +357,17 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
                         .pop()
                         .expect("We just returned from Interpreter frame");
 
+                    // location is taken before the frame is consumed, the return handling
+                    // can still fail it, like the code deposit of the create.
+                    let location = debug.then(|| {
+                        (
+                            frame_location(&returned_frame, call_stack.len()),
+                            returned_frame.interpreter().return_data_buffer == result.output,
+                        )
+                    });
+
                     let ctx = &mut self.context;
-                    FrameOrResult::Result(match returned_frame {
+                    let result = match returned_frame {
                         Frame::Call(frame) => {
                             // return_call
                             FrameResult::Call(exec.call_return(ctx, frame, result)?)
@@ -293,7 +376,17 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
                             // return_create
                             FrameResult::Create(exec.create_return(ctx, frame, result)?)
                         }
-                    })
+                    };
+                    if let Some((location, forwards_child)) = location {
+                        record_failure(
+                            &mut self.failure_location,
+                            location,
+                            forwards_child,
+                            &call_stack,
+                            result.interpreter_result(),
+                        );
+                    }
+                    FrameOrResult::Result(result)
                 }
                 InterpreterAction::None => unreachable!("InterpreterAction::None is not expected"),
             };
@@ -311,6 +404,8 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
                         return Ok(result);
                     };
                     stack_frame = top_frame;
+                    frame_limit_reached |=
+                        result.instruction_result() == InstructionResult::CallFrameLimit;
                     let ctx = &mut self.context;
                     // Insert result to the top frame.
                     match result {
@@ -331,6 +426,10 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
     /// Transact pre-verified transaction.
     fn transact_preverified_inner(&mut self, initial_energy_spend: u64) -> EVMResult<DB::Error> {
         let ctx = &mut self.context;
+        ctx.evm.execution_stats = ExecutionStats {
+            instructions: ctx.evm.env.cfg.instruction_limit.map(|_| 0),
+            ..Default::default()
+        };
         let pre_exec = self.handler.pre_execution();
 
         // load access list and beneficiary if needed.
@@ -362,9 +461,22 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         // Starts the main running loop.
         let mut result = match first_frame_or_result {
             FrameOrResult::Frame(first_frame) => self.start_the_loop(first_frame)?,
-            FrameOrResult::Result(result) => result,
+            FrameOrResult::Result(result) => {
+                self.failure_location = None;
+                if self.context.evm.env.cfg.debug {
+                    record_failure(
+                        &mut self.failure_location,
+                        transaction_location(&self.context.evm.env.tx, &result),
+                        false,
+                        &[],
+                        result.interpreter_result(),
+                    );
+                }
+                result
+            }
         };
 
+        let failure_location = self.failure_location.take();
         let ctx = &mut self.context;
 
         // handle output of call/create calls.
@@ -378,10 +490,116 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         // Reward beneficiary
         post_exec.reward_beneficiary(ctx, result.energy())?;
         // Returns output of transaction.
-        post_exec.output(ctx, result)
+        let mut output = post_exec.output(ctx, result)?;
+        if !output.result.is_success() {
+            output.failure_location = failure_location;
+        }
+        Ok(output)
     }
 }
 
+/// Location of the current instruction of the frame interpreter.
+fn frame_location(frame: &Frame, depth: usize) -> FrameLocation {
+    let interpreter = frame.interpreter();
+    // program counter points after the executed instruction, limits halt the frame
+    // before the instruction at the program counter is executed.
+    let pc = match interpreter.instruction_result {
+        InstructionResult::InstructionLimit | InstructionResult::CallFrameLimit => {
+            interpreter.program_counter()
+        }
+        _ => interpreter.program_counter().saturating_sub(1),
+    };
+    FrameLocation {
+        address: interpreter.contract.address,
+        pc,
+        opcode: interpreter
+            .contract
+            .bytecode
+            .bytecode()
+            .get(pc)
+            .copied()
+            .unwrap_or(opcode::STOP),
+        depth,
+        selector: interpreter
+            .contract
+            .input
+            .get(..4)
+            .map(FixedBytes::from_slice),
+    }
+}
+
+/// Location of the first frame that failed before it started executing.
+fn transaction_location(tx: &TxEnv, result: &FrameResult) -> FrameLocation {
+    let (address, selector) = match (result, &tx.transact_to) {
+        (FrameResult::Call(_), TransactTo::Call(address)) => {
+            (*address, tx.data.get(..4).map(FixedBytes::from_slice))
+        }
+        (FrameResult::Create(outcome), _) => (outcome.address.unwrap_or_default(), None),
+        _ => (Address::ZERO, None),
+    };
+    FrameLocation {
+        address,
+        pc: 0,
+        opcode: opcode::STOP,
+        depth: 0,
+        selector,
+    }
+}
+
+/// Records the location of the call or create instruction whose frame failed before it
+/// started, like calls that are too deep or exceed the frame limit.
+fn record_unstarted_frame(
+    failure_location: &mut Option<FailureLocation>,
+    call_stack: &[Frame],
+    frame_or_result: &FrameOrResult,
+) {
+    let FrameOrResult::Result(result) = frame_or_result else {
+        return;
+    };
+    let (caller, parents) = call_stack
+        .split_last()
+        .expect("caller frame is on the stack");
+    record_failure(
+        failure_location,
+        frame_location(caller, parents.len()),
+        false,
+        parents,
+        result.interpreter_result(),
+    );
+}
+
+/// Records the location of the failed frame with its final result.
+///
+/// Revert that forwards the output of the failed child keeps the location of the child.
+fn record_failure(
+    failure_location: &mut Option<FailureLocation>,
+    frame: FrameLocation,
+    forwards_child: bool,
+    parents: &[Frame],
+    result: &InterpreterResult,
+) {
+    if result.is_ok() {
+        *failure_location = None;
+        return;
+    }
+    let forwarded = result.is_revert()
+        && forwards_child
+        && failure_location
+            .as_ref()
+            .map_or(false, |location| location.frame.depth > frame.depth);
+    if forwarded {
+        return;
+    }
+    *failure_location = Some(FailureLocation {
+        frame,
+        call_stack: parents
+            .iter()
+            .enumerate()
+            .map(|(depth, frame)| frame_location(frame, depth))
+            .collect(),
+    });
+}
+
 impl<EXT, DB: Database> Host for Evm<'_, EXT, DB> {
     fn env_mut(&mut self) -> &mut Env {
         &mut self.context.evm.env
@@ -467,4 +685,214 @@ impl<EXT, DB: Database> Host for Evm<'_, EXT, DB> {
             .map_err(|e| self.context.evm.error = Err(e))
             .ok()
     }
+
+    fn is_interrupted(&mut self) -> bool {
+        let interrupted = self
+            .context
+            .evm
+            .interrupt
+            .as_mut()
+            .map_or(false, Interrupt::is_interrupted);
+        if interrupted {
+            self.context.evm.error = Err(EVMError::Interrupted);
+        }
+        interrupted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        primitives::{address, fixed_bytes, Bytes, HaltReason},
+    };
+
+    #[test]
+    fn failure_location_of_revert() {
+        // PUSH1 0, PUSH1 0, REVERT
+        let code = Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xfd]);
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .modify_cfg_env(|cfg| cfg.debug = true)
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.data = Bytes::from_static(&[0xaa, 0xbb, 0xcc, 0xdd, 0x01]);
+                tx.energy_limit = 100_000;
+            })
+            .build();
+
+        let output = evm.transact().unwrap();
+        assert!(matches!(output.result, ExecutionResult::Revert { .. }));
+        let location = output.failure_location.unwrap();
+        assert_eq!(location.frame.pc, 4);
+        assert_eq!(location.frame.opcode, opcode::REVERT);
+        assert_eq!(location.frame.depth, 0);
+        assert_eq!(location.frame.selector, Some(fixed_bytes!("aabbccdd")));
+        assert!(location.call_stack.is_empty());
+
+        evm.cfg_mut().debug = false;
+        assert!(evm.transact().unwrap().failure_location.is_none());
+    }
+
+    #[test]
+    fn failure_location_of_create_return() {
+        // MSTORE8(0, 0xef), RETURN(0, 1)
+        let initcode =
+            Bytes::from_static(&[0x60, 0xef, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3]);
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::default())
+            .modify_cfg_env(|cfg| cfg.debug = true)
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TransactTo::Create(crate::interpreter::CreateScheme::Create);
+                tx.data = initcode;
+                tx.energy_limit = 100_000;
+            })
+            .build();
+
+        let output = evm.transact().unwrap();
+        assert!(matches!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::CreateContractStartingWithEF,
+                ..
+            }
+        ));
+        // code is rejected after the frame returned.
+        let location = output.failure_location.unwrap();
+        assert_eq!(location.frame.pc, 9);
+        assert_eq!(location.frame.opcode, opcode::RETURN);
+        assert_eq!(location.frame.depth, 0);
+    }
+
+    #[test]
+    fn instruction_and_frame_limits() {
+        // JUMPDEST, PUSH1 0, JUMP
+        let code = Bytes::from_static(&[0x5b, 0x60, 0x00, 0x56]);
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .modify_cfg_env(|cfg| cfg.instruction_limit = Some(100))
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.energy_limit = 1_000_000;
+            })
+            .build();
+
+        let output = evm.transact().unwrap();
+        assert!(matches!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::InstructionLimit,
+                ..
+            }
+        ));
+        assert_eq!(output.execution_stats.instructions, Some(100));
+        assert_eq!(output.execution_stats.frames, 1);
+
+        evm.cfg_mut().frame_limit = Some(0);
+        let output = evm.transact().unwrap();
+        assert!(matches!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::CallFrameLimit,
+                ..
+            }
+        ));
+        assert_eq!(output.execution_stats.instructions, Some(0));
+    }
+
+    #[test]
+    fn frame_limit_halts_parent_frames() {
+        // CALL(ENERGY, ADDRESS, 0, 0, 0, 0, 0), STOP
+        let code = Bytes::from_static(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x30, 0x5a, 0xf1, 0x00,
+        ]);
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .modify_cfg_env(|cfg| cfg.frame_limit = Some(2))
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.energy_limit = 1_000_000;
+            })
+            .build();
+
+        let output = evm.transact().unwrap();
+        assert!(matches!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::CallFrameLimit,
+                ..
+            }
+        ));
+        assert_eq!(output.execution_stats.frames, 2);
+        // instructions are not counted without the limit.
+        assert_eq!(output.execution_stats.instructions, None);
+    }
+
+    #[test]
+    fn failure_location_of_limit_halts() {
+        // JUMPDEST, PUSH1 0, JUMP
+        let code = Bytes::from_static(&[0x5b, 0x60, 0x00, 0x56]);
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .modify_cfg_env(|cfg| {
+                cfg.instruction_limit = Some(100);
+                cfg.debug = true;
+            })
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.energy_limit = 1_000_000;
+            })
+            .build();
+
+        // limit is reached before the `PUSH1` that follows the 34th `JUMPDEST`.
+        let location = evm.transact().unwrap().failure_location.unwrap();
+        assert_eq!(location.frame.pc, 1);
+        assert_eq!(location.frame.opcode, opcode::PUSH1);
+
+        // first frame is rejected before it starts.
+        evm.cfg_mut().frame_limit = Some(0);
+        let location = evm.transact().unwrap().failure_location.unwrap();
+        assert_eq!(location.frame.pc, 0);
+        assert_eq!(location.frame.depth, 0);
+        assert_eq!(
+            location.frame.address,
+            address!("0000000000000000000000000000000000000000")
+        );
+
+        // CALL(ENERGY, ADDRESS, 0, 0, 0, 0, 0), STOP
+        let code = Bytes::from_static(&[
+            0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x30, 0x5a, 0xf1, 0x00,
+        ]);
+        *evm.db_mut() = BenchmarkDB::new_bytecode(Bytecode::new_raw(code));
+        evm.cfg_mut().instruction_limit = None;
+        evm.cfg_mut().frame_limit = Some(2);
+        let output = evm.transact().unwrap();
+        assert!(matches!(
+            output.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::CallFrameLimit,
+                ..
+            }
+        ));
+        // halted first frame did not execute the `STOP` that follows its call.
+        let location = output.failure_location.unwrap();
+        assert_eq!(location.frame.pc, 13);
+        assert_eq!(location.frame.opcode, opcode::STOP);
+        assert_eq!(location.frame.depth, 0);
+    }
 }
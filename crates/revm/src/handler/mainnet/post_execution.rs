@@ -111,5 +111,11 @@ pub fn output<EXT, DB: Database>(
         }
     };
 
-    Ok(ResultAndState { result, state })
+    Ok(ResultAndState {
+        result,
+        state,
+        energy_breakdown: None,
+        failure_location: None,
+        execution_stats: context.evm.execution_stats,
+    })
 }
@@ -5,10 +5,14 @@ use crate::{
 };
 use auto_impl::auto_impl;
 
+#[cfg(feature = "std")]
+mod coverage;
 #[cfg(feature = "std")]
 mod customprinter;
 #[cfg(all(feature = "std", feature = "serde-json"))]
 mod eip3155;
+#[cfg(all(feature = "std", feature = "serde-json"))]
+mod eip3155_reader;
 mod energy;
 mod handler_register;
 mod noop;
@@ -20,10 +24,19 @@ use revm_interpreter::{CallOutcome, CreateOutcome};
 
 /// [Inspector] implementations.
 pub mod inspectors {
+    #[cfg(feature = "std")]
+    pub use super::coverage::{
+        instruction_offsets, parse_source_map, BranchCoverage, CodeCoverage, CoverageInspector,
+        CoverageReport, Jump, SourceElement, SourceFile, SourceMapError,
+    };
     #[cfg(feature = "std")]
     pub use super::customprinter::CustomPrintTracer;
     #[cfg(all(feature = "std", feature = "serde-json"))]
     pub use super::eip3155::TracerEip3155;
+    #[cfg(all(feature = "std", feature = "serde-json"))]
+    pub use super::eip3155_reader::{
+        TraceLine, TraceReadError, TraceReader, TraceStep, TraceSummary,
+    };
     pub use super::energy::EnergyInspector;
     pub use super::noop::NoOpInspector;
 }
@@ -0,0 +1,601 @@
+//! Code coverage [Inspector] and LCOV/Cobertura reports of the ylem sources.
+//!
+//! Coverage is collected per code hash so deployment (initcode) and runtime code of the
+//! same contract are tracked separately and accumulated over any number of transactions.
+//! Executed program counters are mapped to the source lines through the ylem source maps.
+
+use crate::{
+    interpreter::{opcode, Interpreter},
+    primitives::{Bytes, HashMap, B256},
+    Database, EvmContext, Inspector,
+};
+use core::fmt::{self, Write};
+use std::collections::BTreeMap;
+use std::string::String;
+use std::vec::Vec;
+
+/// Outcomes of the `JUMPI` instruction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BranchCoverage {
+    /// Number of times the jump was taken.
+    pub taken: u64,
+    /// Number of times the execution continued with the next instruction.
+    pub not_taken: u64,
+}
+
+/// Coverage of a single bytecode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodeCoverage {
+    /// Original bytecode that was executed.
+    pub bytecode: Bytes,
+    /// Number of executions of the instruction at the program counter.
+    pub hits: BTreeMap<usize, u64>,
+    /// `JUMPI` outcomes by program counter.
+    pub branches: BTreeMap<usize, BranchCoverage>,
+}
+
+/// [Inspector] that records the executed instructions and branches of every bytecode.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageInspector {
+    contracts: HashMap<B256, CodeCoverage>,
+    /// Code hash of the frame at the depth.
+    frames: Vec<B256>,
+}
+
+impl CoverageInspector {
+    /// Coverage by code hash, initcode is keyed by its hash.
+    pub fn contracts(&self) -> &HashMap<B256, CodeCoverage> {
+        &self.contracts
+    }
+
+    /// Coverage of the code with the hash.
+    pub fn get(&self, hash: &B256) -> Option<&CodeCoverage> {
+        self.contracts.get(hash)
+    }
+
+    /// Coverage of every executed code that starts with the bytecode.
+    ///
+    /// Initcode is executed with the constructor arguments appended, so the
+    /// compiled deployment bytecode only matches the prefix.
+    pub fn find<'a>(&'a self, bytecode: &'a [u8]) -> impl Iterator<Item = &'a CodeCoverage> + 'a {
+        self.contracts
+            .values()
+            .filter(move |coverage| coverage.bytecode.starts_with(bytecode))
+    }
+
+    /// Clears the collected coverage.
+    pub fn clear(&mut self) {
+        self.contracts.clear();
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CoverageInspector {
+    fn initialize_interp(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        // initcode of the CREATE frame has no hash set.
+        let hash = if interp.contract.hash == B256::ZERO {
+            interp.contract.bytecode.hash_slow()
+        } else {
+            interp.contract.hash
+        };
+        self.contracts.entry(hash).or_insert_with(|| CodeCoverage {
+            bytecode: interp.contract.bytecode.original_bytecode(),
+            ..Default::default()
+        });
+
+        let depth = context.journaled_state.depth() as usize;
+        if self.frames.len() <= depth {
+            self.frames.resize(depth + 1, B256::ZERO);
+        }
+        self.frames[depth] = hash;
+    }
+
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        let depth = context.journaled_state.depth() as usize;
+        let Some(coverage) = self
+            .frames
+            .get(depth)
+            .and_then(|hash| self.contracts.get_mut(hash))
+        else {
+            return;
+        };
+
+        let pc = interp.program_counter();
+        *coverage.hits.entry(pc).or_default() += 1;
+
+        if interp.current_opcode() == opcode::JUMPI {
+            // stack is `[.., condition, destination]`.
+            if let Ok(condition) = interp.stack.peek(1) {
+                let branch = coverage.branches.entry(pc).or_default();
+                if condition.is_zero() {
+                    branch.not_taken += 1;
+                } else {
+                    branch.taken += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Error while parsing the source map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceMapError {
+    /// Index of the invalid element.
+    pub index: usize,
+    /// The invalid field.
+    pub field: String,
+}
+
+impl fmt::Display for SourceMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid field {:?} of source map element {}",
+            self.field, self.index
+        )
+    }
+}
+
+impl std::error::Error for SourceMapError {}
+
+/// Kind of the jump instruction in the source map.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Jump {
+    /// Jump into a function.
+    In,
+    /// Return from a function.
+    Out,
+    /// Regular jump or any other instruction.
+    #[default]
+    Regular,
+}
+
+/// Source range of the instruction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourceElement {
+    /// Byte offset in the source file.
+    pub offset: usize,
+    /// Length of the range in bytes.
+    pub length: usize,
+    /// Index of the source file, `None` if the instruction is compiler generated.
+    pub index: Option<usize>,
+    pub jump: Jump,
+    pub modifier_depth: usize,
+}
+
+/// Parses the compressed `s:l:f:j:m` source map of ylem, one element per instruction.
+///
+/// Empty fields are inherited from the previous element.
+pub fn parse_source_map(map: &str) -> Result<Vec<SourceElement>, SourceMapError> {
+    let mut elements = Vec::new();
+    let mut last = SourceElement::default();
+    if map.is_empty() {
+        return Ok(elements);
+    }
+    for (index, element) in map.split(';').enumerate() {
+        let error = |field: &str| SourceMapError {
+            index,
+            field: field.into(),
+        };
+        let number = |field: &str| field.parse::<usize>().map_err(|_| error(field));
+        for (i, field) in element.split(':').enumerate() {
+            if field.is_empty() {
+                continue;
+            }
+            match i {
+                0 => last.offset = number(field)?,
+                1 => last.length = number(field)?,
+                2 => {
+                    last.index = match field {
+                        "-1" => None,
+                        _ => Some(number(field)?),
+                    }
+                }
+                3 => {
+                    last.jump = match field {
+                        "i" => Jump::In,
+                        "o" => Jump::Out,
+                        "-" => Jump::Regular,
+                        _ => return Err(error(field)),
+                    }
+                }
+                4 => last.modifier_depth = number(field)?,
+                _ => return Err(error(field)),
+            }
+        }
+        elements.push(last);
+    }
+    Ok(elements)
+}
+
+/// Program counters of the instructions, skipping the `PUSH` data.
+pub fn instruction_offsets(bytecode: &[u8]) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut pc = 0;
+    while pc < bytecode.len() {
+        offsets.push(pc);
+        let op = bytecode[pc];
+        pc += 1;
+        if (opcode::PUSH1..=opcode::PUSH32).contains(&op) {
+            pc += (op - opcode::PUSH1 + 1) as usize;
+        }
+    }
+    offsets
+}
+
+/// Source file of the ylem compilation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceFile {
+    /// Path written to the reports.
+    pub path: String,
+    pub content: String,
+    /// Byte offsets at which the lines start.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(path: impl Into<String>, content: impl Into<String>) -> Self {
+        let content = content.into();
+        let line_starts = core::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            path: path.into(),
+            content,
+            line_starts,
+        }
+    }
+
+    /// One-based line of the byte offset.
+    pub fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct FileCoverage {
+    /// Hits by line, lines of all mapped instructions are included.
+    lines: BTreeMap<usize, u64>,
+    /// `JUMPI` outcomes by line and program counter, `None` if never executed.
+    branches: BTreeMap<(usize, usize), Option<BranchCoverage>>,
+}
+
+impl FileCoverage {
+    fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|&&hits| hits > 0).count()
+    }
+
+    fn branches_hit(&self) -> usize {
+        self.branches
+            .values()
+            .flatten()
+            .map(|b| (b.taken > 0) as usize + (b.not_taken > 0) as usize)
+            .sum()
+    }
+}
+
+/// Line and branch coverage of the ylem sources.
+#[derive(Clone, Debug)]
+pub struct CoverageReport {
+    /// Sources by the file index of the source maps.
+    sources: Vec<SourceFile>,
+    files: BTreeMap<usize, FileCoverage>,
+}
+
+impl CoverageReport {
+    /// New report of the sources ordered by the source index of the compilation.
+    pub fn new(sources: Vec<SourceFile>) -> Self {
+        Self {
+            sources,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the compiled bytecode with its source map.
+    ///
+    /// Deployment and runtime bytecode are added separately with their own source maps,
+    /// executions of all matching codes in the [CoverageInspector] are counted.
+    pub fn add_bytecode(
+        &mut self,
+        inspector: &CoverageInspector,
+        bytecode: &[u8],
+        source_map: &str,
+    ) -> Result<(), SourceMapError> {
+        let coverages: Vec<_> = inspector.find(bytecode).collect();
+        self.add(&coverages, bytecode, source_map)
+    }
+
+    /// Adds the bytecode with the explicitly given coverage.
+    pub fn add(
+        &mut self,
+        coverages: &[&CodeCoverage],
+        bytecode: &[u8],
+        source_map: &str,
+    ) -> Result<(), SourceMapError> {
+        let elements = parse_source_map(source_map)?;
+        for (pc, element) in instruction_offsets(bytecode).into_iter().zip(elements) {
+            let Some((index, source)) = element
+                .index
+                .and_then(|index| self.sources.get(index).map(|source| (index, source)))
+            else {
+                continue;
+            };
+            let line = source.line(element.offset);
+            let file = self.files.entry(index).or_default();
+
+            let hits = coverages
+                .iter()
+                .filter_map(|c| c.hits.get(&pc))
+                .sum::<u64>();
+            let line_hits = file.lines.entry(line).or_default();
+            *line_hits = (*line_hits).max(hits);
+
+            if bytecode[pc] == opcode::JUMPI {
+                let branch = file.branches.entry((line, pc)).or_default();
+                for coverage in coverages.iter().filter_map(|c| c.branches.get(&pc)) {
+                    let b = branch.get_or_insert_with(Default::default);
+                    b.taken += coverage.taken;
+                    b.not_taken += coverage.not_taken;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Report in the LCOV tracefile format.
+    pub fn lcov(&self) -> String {
+        let mut out = String::new();
+        for (index, file) in &self.files {
+            let _ = writeln!(out, "TN:");
+            let _ = writeln!(out, "SF:{}", self.sources[*index].path);
+            for (line, hits) in &file.lines {
+                let _ = writeln!(out, "DA:{line},{hits}");
+            }
+            let _ = writeln!(out, "LF:{}", file.lines.len());
+            let _ = writeln!(out, "LH:{}", file.lines_hit());
+            for ((line, pc), branch) in &file.branches {
+                match branch {
+                    Some(b) => {
+                        let _ = writeln!(out, "BRDA:{line},{pc},0,{}", b.taken);
+                        let _ = writeln!(out, "BRDA:{line},{pc},1,{}", b.not_taken);
+                    }
+                    None => {
+                        let _ = writeln!(out, "BRDA:{line},{pc},0,-");
+                        let _ = writeln!(out, "BRDA:{line},{pc},1,-");
+                    }
+                }
+            }
+            let _ = writeln!(out, "BRF:{}", file.branches.len() * 2);
+            let _ = writeln!(out, "BRH:{}", file.branches_hit());
+            let _ = writeln!(out, "end_of_record");
+        }
+        out
+    }
+
+    /// Report in the Cobertura XML format.
+    pub fn cobertura(&self) -> String {
+        let (mut lines, mut lines_hit, mut branches, mut branches_hit) = (0, 0, 0, 0);
+        let mut classes = String::new();
+        for (index, file) in &self.files {
+            lines += file.lines.len();
+            lines_hit += file.lines_hit();
+            branches += file.branches.len() * 2;
+            branches_hit += file.branches_hit();
+
+            let path = xml_escape(&self.sources[*index].path);
+            let _ = writeln!(
+                classes,
+                r#"        <class name="{path}" filename="{path}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+                rate(file.lines_hit(), file.lines.len()),
+                rate(file.branches_hit(), file.branches.len() * 2),
+            );
+            let _ = writeln!(classes, "          <methods/>");
+            let _ = writeln!(classes, "          <lines>");
+            for (line, hits) in &file.lines {
+                let (covered, total) = file.branches.range((*line, 0)..=(*line, usize::MAX)).fold(
+                    (0, 0),
+                    |(covered, total), (_, b)| {
+                        let hit =
+                            b.map_or(0, |b| (b.taken > 0) as usize + (b.not_taken > 0) as usize);
+                        (covered + hit, total + 2)
+                    },
+                );
+                if total == 0 {
+                    let _ = writeln!(
+                        classes,
+                        r#"            <line number="{line}" hits="{hits}" branch="false"/>"#
+                    );
+                } else {
+                    let _ = writeln!(
+                        classes,
+                        r#"            <line number="{line}" hits="{hits}" branch="true" condition-coverage="{}% ({covered}/{total})"/>"#,
+                        covered * 100 / total,
+                    );
+                }
+            }
+            let _ = writeln!(classes, "          </lines>");
+            let _ = writeln!(classes, "        </class>");
+        }
+
+        let line_rate = rate(lines_hit, lines);
+        let branch_rate = rate(branches_hit, branches);
+        let mut out = String::new();
+        let _ = writeln!(out, r#"<?xml version="1.0" ?>"#);
+        let _ = writeln!(
+            out,
+            r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+        );
+        let _ = writeln!(
+            out,
+            r#"<coverage line-rate="{line_rate}" branch-rate="{branch_rate}" lines-covered="{lines_hit}" lines-valid="{lines}" branches-covered="{branches_hit}" branches-valid="{branches}" complexity="0" version="1" timestamp="0">"#
+        );
+        let _ = writeln!(out, "  <packages>");
+        let _ = writeln!(
+            out,
+            r#"    <package name="ylem" line-rate="{line_rate}" branch-rate="{branch_rate}" complexity="0">"#
+        );
+        let _ = writeln!(out, "      <classes>");
+        out.push_str(&classes);
+        let _ = writeln!(out, "      </classes>");
+        let _ = writeln!(out, "    </package>");
+        let _ = writeln!(out, "  </packages>");
+        let _ = writeln!(out, "</coverage>");
+        out
+    }
+}
+
+fn rate(hit: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        hit as f64 / total as f64
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        interpreter::CreateScheme,
+        primitives::{address, Bytecode, ExecutionResult, Output, TransactTo},
+        Evm, InMemoryDB,
+    };
+
+    fn execute(code: Bytes) -> CoverageInspector {
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .with_external_context(CoverageInspector::default())
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.energy_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap();
+        evm.into_context().external
+    }
+
+    #[test]
+    fn records_hits_and_branches() {
+        // PUSH1 1, PUSH1 6, JUMPI, STOP, JUMPDEST, STOP
+        let code = Bytes::from_static(&[0x60, 0x01, 0x60, 0x06, 0x57, 0x00, 0x5b, 0x00]);
+        let inspector = execute(code.clone());
+        let coverage = inspector.find(&code).next().unwrap();
+
+        assert_eq!(
+            coverage.hits.keys().copied().collect::<Vec<_>>(),
+            [0, 2, 4, 6, 7]
+        );
+        assert_eq!(
+            coverage.branches[&4],
+            BranchCoverage {
+                taken: 1,
+                not_taken: 0
+            }
+        );
+        assert_eq!(instruction_offsets(&code), [0, 2, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn initcode_and_runtime_are_separate() {
+        // PUSH1 42, PUSH1 0, MSTORE, PUSH1 32, PUSH1 0, RETURN
+        let runtime =
+            Bytes::from_static(&[0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+        // copies the runtime that follows the 12 bytes of the initcode and returns it.
+        let mut initcode = vec![
+            0x60, 0x0a, 0x60, 0x0c, 0x60, 0x00, 0x39, 0x60, 0x0a, 0x60, 0x00, 0xf3,
+        ];
+        initcode.extend_from_slice(&runtime);
+        // constructor arguments are appended to the compiled initcode.
+        let mut deployment = initcode.clone();
+        deployment.extend_from_slice(&[0x01; 32]);
+        let deployment = Bytes::from(deployment);
+
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .with_external_context(CoverageInspector::default())
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to = TransactTo::Create(CreateScheme::Create);
+                tx.data = deployment.clone();
+                tx.energy_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let created = match evm.transact_commit().unwrap() {
+            ExecutionResult::Success {
+                output: Output::Create(_, Some(address)),
+                ..
+            } => address,
+            result => panic!("deployment failed: {result:?}"),
+        };
+        evm.tx_mut().transact_to = TransactTo::Call(created);
+        evm.tx_mut().data = Bytes::new();
+        assert!(evm.transact_commit().unwrap().is_success());
+        let inspector = evm.into_context().external;
+
+        assert_eq!(inspector.contracts().len(), 2);
+        // initcode is keyed by the hash of the executed code with the arguments.
+        let deploy_hash = Bytecode::new_raw(deployment.clone()).hash_slow();
+        let deploy_coverage = inspector.get(&deploy_hash).unwrap();
+        assert_eq!(deploy_coverage.bytecode, deployment);
+        assert_eq!(
+            deploy_coverage.hits.keys().copied().collect::<Vec<_>>(),
+            [0, 2, 4, 6, 7, 9, 11]
+        );
+        let found: Vec<_> = inspector.find(&initcode).collect();
+        assert_eq!(found, [deploy_coverage]);
+
+        let runtime_hash = Bytecode::new_raw(runtime.clone()).hash_slow();
+        let runtime_coverage = inspector.get(&runtime_hash).unwrap();
+        assert_eq!(
+            runtime_coverage.hits.keys().copied().collect::<Vec<_>>(),
+            [0, 2, 4, 5, 7, 9]
+        );
+        assert_eq!(
+            inspector.find(&runtime).collect::<Vec<_>>(),
+            [runtime_coverage]
+        );
+    }
+
+    #[test]
+    fn lcov_report() {
+        let code = Bytes::from_static(&[0x60, 0x01, 0x60, 0x06, 0x57, 0x00, 0x5b, 0x00]);
+        let inspector = execute(code.clone());
+
+        let mut report = CoverageReport::new(vec![SourceFile::new("A.ylm", "a\nb\nc\n")]);
+        report
+            .add_bytecode(&inspector, &code, "0:1:0:-;;2:1;4:1;0:1;")
+            .unwrap();
+        assert_eq!(
+            report.lcov(),
+            "TN:\nSF:A.ylm\nDA:1,1\nDA:2,1\nDA:3,0\nLF:3\nLH:2\n\
+             BRDA:2,4,0,1\nBRDA:2,4,1,0\nBRF:2\nBRH:1\nend_of_record\n"
+        );
+        assert!(report.cobertura().contains(
+            r#"<line number="2" hits="1" branch="true" condition-coverage="50% (1/2)"/>"#
+        ));
+
+        assert!(parse_source_map("0:1:x").is_err());
+    }
+}
@@ -61,6 +61,9 @@ impl<DB: Database> Inspector<DB> for CustomPrintTracer {
         inputs: &CallInputs,
         outcome: CallOutcome,
     ) -> CallOutcome {
+        if let Some(reason) = outcome.result.revert_reason() {
+            println!("SM REVERT: {:?}, reason: {}", inputs.contract, reason);
+        }
         self.energy_inspector.call_end(context, inputs, outcome)
     }
 
@@ -70,6 +73,12 @@ impl<DB: Database> Inspector<DB> for CustomPrintTracer {
         inputs: &CreateInputs,
         outcome: CreateOutcome,
     ) -> CreateOutcome {
+        if let Some(reason) = outcome.result.revert_reason() {
+            println!(
+                "CREATE REVERT: caller:{:?}, reason: {}",
+                inputs.caller, reason
+            );
+        }
         self.energy_inspector.create_end(context, inputs, outcome)
     }
 
@@ -88,6 +88,9 @@ struct Summary {
     pass: bool,
 
     // Optional fields:
+    /// Decoded revert reason of the reverted transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
     /// Time in nanoseconds needed to execute the transaction
     #[serde(default, skip_serializing_if = "Option::is_none")]
     time: Option<u128>,
@@ -177,6 +180,7 @@ impl TracerEip3155 {
                     context.inner.env().tx.energy_limit - self.energy_inspector.energy_remaining(),
                 ),
                 pass: result.is_ok(),
+                error: result.revert_reason().map(|reason| reason.to_string()),
 
                 time: None,
                 fork: Some(spec_name.to_string()),
@@ -0,0 +1,228 @@
+//! Streaming parser of the traces written by [TracerEip3155](super::TracerEip3155).
+
+use crate::primitives::{Bytes, B256, U256};
+use core::fmt;
+use serde::{Deserialize, Deserializer};
+use std::io::{self, BufRead};
+use std::string::String;
+use std::vec::Vec;
+
+/// Operation of the [EIP-3155](https://eips.ethereum.org/EIPS/eip-3155) trace.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceStep {
+    /// Program counter
+    pub pc: u64,
+    /// OpCode
+    pub op: u8,
+    /// Energy left before executing this operation
+    #[serde(deserialize_with = "quantity")]
+    pub energy: u64,
+    /// Energy cost of this operation
+    #[serde(deserialize_with = "quantity")]
+    pub energy_cost: u64,
+    /// Stack before executing this operation, top of the stack is the last item
+    pub stack: Vec<U256>,
+    /// Depth of the call stack
+    pub depth: u64,
+    /// Data returned by the last call
+    #[serde(default)]
+    pub return_data: Bytes,
+    /// Amount of **global** energy refunded
+    #[serde(deserialize_with = "quantity")]
+    pub refund: u64,
+    /// Size of memory array
+    #[serde(deserialize_with = "quantity")]
+    pub mem_size: u64,
+    /// Name of the operation
+    #[serde(default)]
+    pub op_name: Option<String>,
+    /// Description of an error
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Memory before executing this operation, if it was traced
+    #[serde(default)]
+    pub memory: Option<Bytes>,
+}
+
+/// Summary written at the end of the trace of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceSummary {
+    /// Root of the state trie after executing the transaction
+    pub state_root: B256,
+    /// Return values of the function
+    pub output: Bytes,
+    /// All energy used by the transaction
+    #[serde(deserialize_with = "quantity")]
+    pub energy_used: u64,
+    /// Whether transaction was executed successfully
+    pub pass: bool,
+    /// Decoded revert reason of the reverted transaction
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Time in nanoseconds needed to execute the transaction
+    #[serde(default)]
+    pub time: Option<u128>,
+    /// Name of the fork rules used for execution
+    #[serde(default)]
+    pub fork: Option<String>,
+}
+
+/// Line of the trace.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum TraceLine {
+    Step(TraceStep),
+    Summary(TraceSummary),
+}
+
+/// Error of the line of the trace.
+#[derive(Debug)]
+pub enum TraceReadError {
+    Io(io::Error),
+    /// Line, counted from one, is not a step nor a summary.
+    Json(usize, serde_json::Error),
+}
+
+impl fmt::Display for TraceReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Json(line, e) => write!(f, "line {line}: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for TraceReadError {}
+
+/// Iterator over the lines of the trace.
+///
+/// Trace is read line by line, so traces that do not fit in memory can be processed. Empty
+/// lines are skipped.
+#[derive(Debug)]
+pub struct TraceReader<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+}
+
+impl<R: BufRead> TraceReader<R> {
+    /// Creates new reader of the trace.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            line: 0,
+        }
+    }
+
+    /// Number of the last line read, counted from one.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl<R: BufRead> Iterator for TraceReader<R> {
+    type Item = Result<TraceLine, TraceReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(e) => return Some(Err(TraceReadError::Io(e))),
+            }
+            let line = self.buf.trim();
+            if !line.is_empty() {
+                return Some(
+                    serde_json::from_str(line).map_err(|e| TraceReadError::Json(self.line, e)),
+                );
+            }
+        }
+    }
+}
+
+/// Deserializes number written as JSON number, hex string or decimal string.
+fn quantity<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Quantity {
+        Number(u64),
+        String(String),
+    }
+
+    match Quantity::deserialize(deserializer)? {
+        Quantity::Number(number) => Ok(number),
+        Quantity::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => s.parse(),
+        }
+        .map_err(serde::de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        inspector_handle_register,
+        inspectors::TracerEip3155,
+        primitives::{address, bytes, Bytecode, TransactTo},
+        Evm,
+    };
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn read_written_trace() {
+        let buffer = SharedBuffer::default();
+        // PUSH1 1, PUSH1 2, ADD, STOP
+        let code = bytes!("6001600201");
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .modify_tx_env(|tx| {
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.energy_limit = 100_000;
+            })
+            .with_external_context(TracerEip3155::new(Box::new(buffer.clone())))
+            .append_handler_register(inspector_handle_register)
+            .build();
+        evm.transact().unwrap();
+
+        let trace = buffer.0.lock().unwrap().clone();
+        let lines = TraceReader::new(trace.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let TraceLine::Step(add) = &lines[2] else {
+            panic!("third line is not a step");
+        };
+        assert_eq!((add.pc, add.op, add.depth), (4, 0x01, 1));
+        assert_eq!(add.stack, vec![U256::from(1), U256::from(2)]);
+        assert_eq!(add.energy_cost, 3);
+        assert!(matches!(lines.last(), Some(TraceLine::Summary(s)) if s.pass));
+    }
+
+    #[test]
+    fn report_invalid_line() {
+        let trace = "\n{\"pc\":0}\n";
+        let error = TraceReader::new(trace.as_bytes()).next().unwrap();
+        assert!(matches!(error, Err(TraceReadError::Json(2, _))));
+    }
+}
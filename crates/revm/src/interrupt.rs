@@ -0,0 +1,129 @@
+//! Externally controlled interrupt of the execution.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Number of checks between the reads of the clock.
+#[cfg(feature = "std")]
+const DEADLINE_CHECK_INTERVAL: u32 = 1024;
+
+/// Handle that stops the running execution from another thread or after a deadline.
+///
+/// The interrupt is checked on every jump and every new frame, the deadline is only compared
+/// with the clock every `DEADLINE_CHECK_INTERVAL` checks. The interrupted transaction returns
+/// [EVMError::Interrupted](crate::primitives::EVMError::Interrupted) without any state changes.
+#[derive(Clone, Debug, Default)]
+pub struct Interrupt {
+    flag: Arc<AtomicBool>,
+    #[cfg(feature = "std")]
+    deadline: Option<std::time::Instant>,
+    /// Checks left until the clock is read again.
+    #[cfg(feature = "std")]
+    checks_until_clock: u32,
+}
+
+impl Interrupt {
+    /// Creates new interrupt that is not triggered.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interrupt that is triggered at the deadline.
+    #[cfg(feature = "std")]
+    pub fn with_deadline(mut self, deadline: std::time::Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Interrupt that is triggered after the timeout from now.
+    #[cfg(feature = "std")]
+    pub fn with_timeout(self, timeout: std::time::Duration) -> Self {
+        self.with_deadline(std::time::Instant::now() + timeout)
+    }
+
+    /// Triggers the interrupt, the execution is stopped on the next check.
+    #[inline]
+    pub fn interrupt(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Clears the triggered flag, the deadline is kept.
+    #[inline]
+    pub fn reset(&self) {
+        self.flag.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns true if the interrupt was triggered or the deadline has passed.
+    #[inline]
+    pub fn is_interrupted(&mut self) -> bool {
+        if self.flag.load(Ordering::Relaxed) {
+            return true;
+        }
+        #[cfg(feature = "std")]
+        if let Some(deadline) = self.deadline {
+            if self.checks_until_clock == 0 {
+                self.checks_until_clock = DEADLINE_CHECK_INTERVAL;
+                return std::time::Instant::now() >= deadline;
+            }
+            self.checks_until_clock -= 1;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::BenchmarkDB,
+        primitives::{address, Bytecode, Bytes, EVMError, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn interrupt_infinite_loop() {
+        // JUMPDEST, PUSH1 0, JUMP
+        let code = Bytes::from_static(&[0x5b, 0x60, 0x00, 0x56]);
+        let interrupt = Interrupt::new();
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .with_interrupt(interrupt.clone())
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.energy_limit = 1_000_000_000;
+            })
+            .build();
+
+        interrupt.interrupt();
+        assert!(matches!(evm.transact(), Err(EVMError::Interrupted)));
+        assert!(evm.context.evm.journaled_state.state.is_empty());
+        assert_eq!(evm.context.evm.journaled_state.depth, 0);
+
+        interrupt.reset();
+        evm.context.evm.interrupt = Some(interrupt.with_timeout(std::time::Duration::ZERO));
+        assert!(matches!(evm.transact(), Err(EVMError::Interrupted)));
+    }
+
+    #[test]
+    fn interrupt_code_without_jumps() {
+        // STOP
+        let code = Bytes::from_static(&[0x00]);
+        let interrupt = Interrupt::new();
+        interrupt.interrupt();
+        let mut evm = Evm::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code)))
+            .with_interrupt(interrupt)
+            .modify_tx_env(|tx| {
+                tx.clear();
+                tx.caller = address!("1000000000000000000000000000000000000000");
+                tx.transact_to =
+                    TransactTo::Call(address!("0000000000000000000000000000000000000000"));
+                tx.energy_limit = 1_000_000;
+            })
+            .build();
+        assert!(matches!(evm.transact(), Err(EVMError::Interrupted)));
+    }
+}
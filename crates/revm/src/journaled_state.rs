@@ -152,6 +152,25 @@ impl JournaledState {
         account.info.code = Some(code);
     }
 
+    /// Sets the balance of the account, used by cheatcodes that deal the balance.
+    ///
+    /// Assume account is warm
+    #[inline]
+    pub fn set_balance(&mut self, address: Address, balance: U256) {
+        let account = self.state.get_mut(&address).unwrap();
+        Self::touch_account(self.journal.last_mut().unwrap(), &address, account);
+
+        self.journal
+            .last_mut()
+            .unwrap()
+            .push(JournalEntry::BalanceChange {
+                address,
+                had_balance: account.info.balance,
+            });
+
+        account.info.balance = balance;
+    }
+
     #[inline]
     pub fn inc_nonce(&mut self, address: Address) -> Option<u64> {
         let account = self.state.get_mut(&address).unwrap();
@@ -355,6 +374,12 @@ impl JournaledState {
                     let to = state.get_mut(&to).unwrap();
                     to.info.balance -= balance;
                 }
+                JournalEntry::BalanceChange {
+                    address,
+                    had_balance,
+                } => {
+                    state.get_mut(&address).unwrap().info.balance = had_balance;
+                }
                 JournalEntry::NonceChange { address } => {
                     state.get_mut(&address).unwrap().info.nonce -= 1;
                 }
@@ -800,6 +825,10 @@ pub enum JournalEntry {
         to: Address,
         balance: U256,
     },
+    /// Balance of the account is set
+    /// Action: Set the balance
+    /// Revert: Set the balance back to the previous value
+    BalanceChange { address: Address, had_balance: U256 },
     /// Increment nonce
     /// Action: Increment nonce by one
     /// Revert: Decrement nonce by one
@@ -11,8 +11,13 @@ extern crate alloc as std;
 
 // Define modules.
 
+#[cfg(feature = "abi")]
+pub mod abi;
 mod builder;
+#[cfg(feature = "cheatcodes")]
+pub mod cheatcodes;
 mod context;
+mod energy_breakdown;
 
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
@@ -22,13 +27,17 @@ mod evm;
 mod frame;
 pub mod handler;
 mod inspector;
+mod interrupt;
 mod journaled_state;
 #[cfg(feature = "optimism")]
 pub mod optimism;
+mod simulate;
 
 // Export items.
 
 pub use builder::EvmBuilder;
+#[cfg(feature = "cheatcodes")]
+pub use cheatcodes::{cheatcode_handle_register, CHEATCODE_ADDRESS};
 pub use context::{
     Context, ContextPrecompile, ContextPrecompiles, ContextStatefulPrecompile,
     ContextStatefulPrecompileArc, ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
@@ -38,13 +47,16 @@ pub use db::{
     CacheState, DBBox, State, StateBuilder, StateDBBox, TransitionAccount, TransitionState,
 };
 pub use db::{Database, DatabaseCommit, DatabaseRef, InMemoryDB};
+pub use energy_breakdown::energy_breakdown_handle_register;
 pub use evm::{Evm, CALL_STACK_LIMIT};
 pub use frame::{CallFrame, CreateFrame, Frame, FrameData, FrameOrResult, FrameResult};
 pub use handler::Handler;
 pub use inspector::{
     inspector_handle_register, inspector_instruction, inspectors, GetInspector, Inspector,
 };
+pub use interrupt::Interrupt;
 pub use journaled_state::{JournalCheckpoint, JournalEntry, JournaledState};
+pub use simulate::{simulate, SimulatedBlock, SimulationBlock};
 // export Optimism types, helpers, and constants
 #[cfg(feature = "optimism")]
 pub use optimism::{L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT};
@@ -361,6 +361,9 @@ pub fn end<SPEC: Spec, EXT, DB: Database>(
                     energy_used,
                 },
                 state,
+                energy_breakdown: None,
+                failure_location: None,
+                execution_stats: context.evm.execution_stats,
             })
         } else {
             Err(err)
@@ -0,0 +1,156 @@
+use crate::{
+    db::{CacheDB, Database, DatabaseCommit, DatabaseRef},
+    primitives::{
+        BlockEnv, CfgEnvWithHandlerCfg, EVMError, EVMResultGeneric, ResultAndState, TxEnv,
+    },
+    Evm,
+};
+use std::vec::Vec;
+
+/// Block of transactions that are simulated in sequence.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimulationBlock {
+    /// Block environment the transactions are executed in.
+    pub block: BlockEnv,
+    /// Transactions of the block.
+    pub transactions: Vec<TxEnv>,
+}
+
+impl SimulationBlock {
+    /// Creates new simulation block.
+    pub fn new(block: BlockEnv, transactions: Vec<TxEnv>) -> Self {
+        Self {
+            block,
+            transactions,
+        }
+    }
+}
+
+/// Outcome of a simulated block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulatedBlock<DBError> {
+    /// Block environment the transactions were executed in.
+    pub block: BlockEnv,
+    /// Result of every transaction, in the order they were executed.
+    ///
+    /// Successful results contain the logs of the transaction and the state it changed.
+    /// Transactions that failed validation have their error recorded and do not change the state.
+    pub results: Vec<EVMResultGeneric<ResultAndState, DBError>>,
+}
+
+impl<EXT, DB: Database + DatabaseCommit> Evm<'_, EXT, DB> {
+    /// Executes the blocks of transactions in sequence, committing changes of every
+    /// transaction before the next one is executed.
+    ///
+    /// To leave the original database untouched the EVM should be built on top of an
+    /// ephemeral overlay, see [`simulate`].
+    ///
+    /// Returns an error only if the database fails, in which case the simulation is stopped.
+    pub fn simulate(
+        &mut self,
+        blocks: Vec<SimulationBlock>,
+    ) -> Result<Vec<SimulatedBlock<DB::Error>>, EVMError<DB::Error>> {
+        let mut simulated = Vec::with_capacity(blocks.len());
+        for SimulationBlock {
+            block,
+            transactions,
+        } in blocks
+        {
+            *self.block_mut() = block;
+            let mut results = Vec::with_capacity(transactions.len());
+            for tx in transactions {
+                *self.tx_mut() = tx;
+                match self.transact() {
+                    Ok(result_and_state) => {
+                        self.db_mut().commit(result_and_state.state.clone());
+                        results.push(Ok(result_and_state));
+                    }
+                    Err(EVMError::Database(error)) => return Err(EVMError::Database(error)),
+                    Err(error) => {
+                        // discard accounts loaded while validating the transaction.
+                        let _ = self.context.evm.journaled_state.finalize();
+                        results.push(Err(error));
+                    }
+                }
+            }
+            simulated.push(SimulatedBlock {
+                block: self.block().clone(),
+                results,
+            });
+        }
+        Ok(simulated)
+    }
+}
+
+/// Simulates the blocks of transactions on top of the database.
+///
+/// Transactions are executed in sequence over a [CacheDB] overlay so every transaction
+/// sees the changes of the previous ones, while the given database is never modified.
+pub fn simulate<DB: DatabaseRef>(
+    db: DB,
+    cfg: CfgEnvWithHandlerCfg,
+    blocks: Vec<SimulationBlock>,
+) -> Result<Vec<SimulatedBlock<DB::Error>>, EVMError<DB::Error>> {
+    Evm::builder()
+        .with_db(CacheDB::new(db))
+        .with_cfg_env_with_handler_cfg(cfg)
+        .build()
+        .simulate(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        primitives::{AccountInfo, Address, CfgEnv, InvalidTransaction, SpecId, TransactTo, U256},
+    };
+
+    #[test]
+    fn simulate_chained_transactions() {
+        let caller = Address::with_last_byte(1);
+        let receiver = Address::with_last_byte(2);
+        let mut base = InMemoryDB::default();
+        base.insert_account_info(caller, AccountInfo::from_balance(U256::from(10)));
+
+        let transfer = |nonce| TxEnv {
+            caller,
+            transact_to: TransactTo::Call(receiver),
+            value: U256::from(6),
+            nonce: Some(nonce),
+            ..Default::default()
+        };
+        let blocks = vec![
+            SimulationBlock::new(BlockEnv::default(), vec![transfer(0)]),
+            SimulationBlock::new(BlockEnv::default(), vec![transfer(1), transfer(0)]),
+        ];
+        let cfg = CfgEnvWithHandlerCfg::new_with_spec_id(CfgEnv::default(), SpecId::LATEST);
+        let simulated = simulate(&base, cfg, blocks).unwrap();
+
+        let first = simulated[0].results[0].as_ref().unwrap();
+        assert!(first.result.is_success());
+        assert_eq!(first.state[&receiver].info.balance, U256::from(6));
+
+        // second transfer sees the nonce and balance changed by the first one.
+        assert!(matches!(
+            simulated[1].results[0],
+            Err(EVMError::Transaction(
+                InvalidTransaction::LackOfFundForMaxFee { .. }
+            ))
+        ));
+        assert!(matches!(
+            simulated[1].results[1],
+            Err(EVMError::Transaction(InvalidTransaction::NonceTooLow {
+                tx: 0,
+                state: 1
+            }))
+        ));
+
+        // base database is untouched.
+        assert_eq!(
+            base.basic_ref(caller).unwrap().unwrap().balance,
+            U256::from(10)
+        );
+        assert!(base.basic_ref(receiver).unwrap().is_none());
+    }
+}
@@ -1,2 +1,7 @@
 #[doc(hidden)]
 pub use crate::context::evm_context::test_utils::*;
+
+#[cfg(feature = "test-utils")]
+pub mod contract;
+#[cfg(feature = "test-utils")]
+pub mod fuzz;
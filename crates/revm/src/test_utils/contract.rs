@@ -0,0 +1,475 @@
+//! Test harness of the ylem contracts.
+//!
+//! [TestEnv] deploys contract [Artifact]s into a [CacheDB] and returns [ContractHandle]s
+//! whose functions are called by the ABI signature. Return values, events and custom errors
+//! are decoded with the ABI of the contract.
+
+use super::fuzz::execute;
+use crate::{
+    abi::{DecodedError, ErrorDecoder},
+    db::{CacheDB, EmptyDB},
+    primitives::{
+        AccountInfo, Address, Bytes, CreateScheme, ExecutionResult, HaltReason, IcanAddress, Log,
+        Output, RevertReason, TransactTo, U256,
+    },
+    Database,
+};
+use alloy_dyn_abi::{DynSolValue, EventExt, FunctionExt, JsonAbiExt};
+use alloy_json_abi::{Function, JsonAbi};
+use alloy_sol_types::SolCall;
+use core::fmt;
+use serde::Deserialize;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// Error of the contract call.
+#[derive(Debug)]
+pub enum ContractError {
+    /// Invalid artifact or ABI JSON.
+    Json(serde_json::Error),
+    /// Values do not match the ABI.
+    Abi(alloy_dyn_abi::Error),
+    /// Transaction could not be executed.
+    Evm(String),
+    /// Function with the signature is not in the ABI.
+    UnknownFunction(String),
+    /// Execution reverted, with the custom error if it is defined in the ABI.
+    Reverted {
+        reason: RevertReason,
+        error: Option<DecodedError>,
+        output: Bytes,
+    },
+    /// Execution halted.
+    Halted(HaltReason),
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "invalid artifact: {e}"),
+            Self::Abi(e) => write!(f, "ABI coding failed: {e}"),
+            Self::Evm(e) => write!(f, "transaction failed: {e}"),
+            Self::UnknownFunction(signature) => write!(f, "unknown function {signature}"),
+            Self::Reverted {
+                error: Some(error), ..
+            } => write!(f, "execution reverted: {error}"),
+            Self::Reverted { reason, .. } => write!(f, "execution reverted: {reason}"),
+            Self::Halted(reason) => write!(f, "execution halted: {reason:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ContractError {}
+
+impl From<serde_json::Error> for ContractError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<alloy_dyn_abi::Error> for ContractError {
+    fn from(e: alloy_dyn_abi::Error) -> Self {
+        Self::Abi(e)
+    }
+}
+
+impl From<super::fuzz::FuzzError> for ContractError {
+    fn from(e: super::fuzz::FuzzError) -> Self {
+        Self::Evm(e.to_string())
+    }
+}
+
+/// Event decoded with the ABI.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedEvent {
+    /// Emitter of the event.
+    pub address: Address,
+    pub name: String,
+    pub indexed: Vec<DynSolValue>,
+    pub body: Vec<DynSolValue>,
+}
+
+/// Compiled contract, ABI together with the deployment bytecode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Artifact {
+    pub abi: JsonAbi,
+    pub bytecode: Bytes,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArtifactBytecode {
+    Hex(Bytes),
+    Object { object: Bytes },
+}
+
+#[derive(Deserialize)]
+struct ArtifactJson {
+    abi: JsonAbi,
+    #[serde(alias = "bin")]
+    bytecode: ArtifactBytecode,
+}
+
+impl Artifact {
+    /// Artifact from the ABI JSON and the bytecode.
+    pub fn new(abi: &str, bytecode: Bytes) -> Result<Self, ContractError> {
+        Ok(Self {
+            abi: serde_json::from_str(abi)?,
+            bytecode,
+        })
+    }
+
+    /// Parses the artifact JSON with the `abi` and `bytecode` (or `bin`) fields, the
+    /// bytecode is either a hex string or an object with the hex `object`.
+    pub fn from_json(json: &str) -> Result<Self, ContractError> {
+        let artifact: ArtifactJson = serde_json::from_str(json)?;
+        let bytecode = match artifact.bytecode {
+            ArtifactBytecode::Hex(bytecode) | ArtifactBytecode::Object { object: bytecode } => {
+                bytecode
+            }
+        };
+        Ok(Self {
+            abi: artifact.abi,
+            bytecode,
+        })
+    }
+}
+
+/// Deployed contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractHandle {
+    pub address: Address,
+    /// ICAN address for the network id of the [TestEnv].
+    pub ican: IcanAddress,
+    pub abi: JsonAbi,
+}
+
+impl ContractHandle {
+    /// Function by its signature, e.g. `transfer(address,uint256)`, or by its name if it is
+    /// not overloaded.
+    pub fn function(&self, signature: &str) -> Result<&Function, ContractError> {
+        let unknown = || ContractError::UnknownFunction(signature.to_string());
+        match signature.split_once('(') {
+            Some((name, _)) => self
+                .abi
+                .function(name)
+                .and_then(|functions| functions.iter().find(|f| f.signature() == signature))
+                .ok_or_else(unknown),
+            None => match self.abi.function(signature).map(Vec::as_slice) {
+                Some([function]) => Ok(function),
+                _ => Err(unknown()),
+            },
+        }
+    }
+
+    /// Decodes the logs emitted by this contract with the events of the ABI.
+    pub fn decode_logs(&self, logs: &[Log]) -> Vec<DecodedEvent> {
+        logs.iter()
+            .filter(|log| log.address == self.address)
+            .filter_map(|log| {
+                self.abi.events().find_map(|event| {
+                    let topics = log.data.topics();
+                    if !event.anonymous && topics.first() != Some(&event.selector()) {
+                        return None;
+                    }
+                    let decoded = event
+                        .decode_log_parts(topics.iter().copied(), &log.data.data, true)
+                        .ok()?;
+                    Some(DecodedEvent {
+                        address: log.address,
+                        name: event.name.clone(),
+                        indexed: decoded.indexed,
+                        body: decoded.body,
+                    })
+                })
+            })
+            .collect()
+    }
+
+    /// Decodes the revert output with the custom errors of the ABI.
+    pub fn decode_error(&self, output: &[u8]) -> Option<DecodedError> {
+        ErrorDecoder::new(&self.abi).decode(output)
+    }
+}
+
+/// Outcome of the successful call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallOutput {
+    /// Decoded return values.
+    pub values: Vec<DynSolValue>,
+    /// Events of the called contract.
+    pub events: Vec<DecodedEvent>,
+    /// All emitted logs, including the ones of other contracts.
+    pub logs: Vec<Log>,
+    pub energy_used: u64,
+}
+
+/// Contracts deployed by a single deployer into an in-memory state.
+#[derive(Clone, Debug)]
+pub struct TestEnv {
+    pub db: CacheDB<EmptyDB>,
+    pub network_id: u64,
+    /// Caller of the deployments and calls.
+    pub deployer: Address,
+    pub energy_limit: u64,
+}
+
+impl TestEnv {
+    /// Creates the environment with a funded deployer.
+    pub fn new(network_id: u64) -> Self {
+        let deployer = Address::with_last_byte(0x10);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            deployer,
+            AccountInfo::from_balance(U256::from(10).pow(U256::from(24))),
+        );
+        Self {
+            db,
+            network_id,
+            deployer,
+            energy_limit: 30_000_000,
+        }
+    }
+
+    /// Current nonce of the deployer.
+    pub fn deployer_nonce(&mut self) -> u64 {
+        let deployer = self.deployer;
+        self.db
+            .basic(deployer)
+            .ok()
+            .flatten()
+            .map_or(0, |info| info.nonce)
+    }
+
+    /// Address of the next contract created by the deployer.
+    pub fn next_address(&mut self) -> Address {
+        let nonce = self.deployer_nonce();
+        self.deployer.create(nonce, self.network_id).to_address()
+    }
+
+    /// ICAN address of the address on the configured network.
+    pub fn ican(&self, address: Address) -> IcanAddress {
+        address.to_ican(self.network_id)
+    }
+
+    /// Deploys the artifact with the constructor arguments.
+    pub fn deploy(
+        &mut self,
+        artifact: &Artifact,
+        args: &[DynSolValue],
+    ) -> Result<ContractHandle, ContractError> {
+        let mut data = artifact.bytecode.to_vec();
+        if let Some(constructor) = &artifact.abi.constructor {
+            data.extend(constructor.abi_encode_input(args)?);
+        }
+        let expected = self.next_address();
+        let handle = ContractHandle {
+            address: expected,
+            ican: self.ican(expected),
+            abi: artifact.abi.clone(),
+        };
+        let result = self.transact(
+            TransactTo::Create(CreateScheme::Create),
+            data.into(),
+            U256::ZERO,
+            true,
+        )?;
+        match result {
+            ExecutionResult::Success {
+                output: Output::Create(_, Some(address)),
+                ..
+            } => {
+                debug_assert_eq!(address, expected);
+                Ok(handle)
+            }
+            result => Err(handle_failure(&handle, result)),
+        }
+    }
+
+    /// Calls the function of the contract and commits the state.
+    pub fn call(
+        &mut self,
+        contract: &ContractHandle,
+        signature: &str,
+        args: &[DynSolValue],
+    ) -> Result<CallOutput, ContractError> {
+        self.call_with_value(contract, signature, args, U256::ZERO)
+    }
+
+    /// Calls the payable function of the contract with the value and commits the state.
+    pub fn call_with_value(
+        &mut self,
+        contract: &ContractHandle,
+        signature: &str,
+        args: &[DynSolValue],
+        value: U256,
+    ) -> Result<CallOutput, ContractError> {
+        self.call_function(contract, signature, args, value, true)
+    }
+
+    /// Calls the function of the contract without committing the state.
+    pub fn view(
+        &mut self,
+        contract: &ContractHandle,
+        signature: &str,
+        args: &[DynSolValue],
+    ) -> Result<Vec<DynSolValue>, ContractError> {
+        Ok(self
+            .call_function(contract, signature, args, U256::ZERO, false)?
+            .values)
+    }
+
+    /// Calls the contract with the call type generated by the `sol!` macro and commits the state.
+    pub fn call_sol<C: SolCall>(
+        &mut self,
+        contract: &ContractHandle,
+        call: &C,
+    ) -> Result<C::Return, ContractError> {
+        let result = self.transact(
+            TransactTo::Call(contract.address),
+            call.abi_encode().into(),
+            U256::ZERO,
+            true,
+        )?;
+        match result {
+            ExecutionResult::Success { output, .. } => {
+                C::abi_decode_returns(output.data(), true).map_err(|e| ContractError::Abi(e.into()))
+            }
+            result => Err(handle_failure(contract, result)),
+        }
+    }
+
+    fn call_function(
+        &mut self,
+        contract: &ContractHandle,
+        signature: &str,
+        args: &[DynSolValue],
+        value: U256,
+        commit: bool,
+    ) -> Result<CallOutput, ContractError> {
+        let function = contract.function(signature)?;
+        let result = self.transact(
+            TransactTo::Call(contract.address),
+            function.abi_encode_input(args)?.into(),
+            value,
+            commit,
+        )?;
+        match result {
+            ExecutionResult::Success {
+                output,
+                logs,
+                energy_used,
+                ..
+            } => Ok(CallOutput {
+                values: function.abi_decode_output(output.data(), true)?,
+                events: contract.decode_logs(&logs),
+                logs,
+                energy_used,
+            }),
+            result => Err(handle_failure(contract, result)),
+        }
+    }
+
+    fn transact(
+        &mut self,
+        transact_to: TransactTo,
+        data: Bytes,
+        value: U256,
+        commit: bool,
+    ) -> Result<ExecutionResult, ContractError> {
+        Ok(execute(
+            &mut self.db,
+            self.network_id,
+            self.energy_limit,
+            self.deployer,
+            transact_to,
+            data,
+            value,
+            commit,
+        )?)
+    }
+}
+
+fn handle_failure(contract: &ContractHandle, result: ExecutionResult) -> ContractError {
+    match result {
+        ExecutionResult::Revert { output, .. } => ContractError::Reverted {
+            reason: RevertReason::decode(&output),
+            error: contract.decode_error(&output),
+            output,
+        },
+        ExecutionResult::Halt { reason, .. } => ContractError::Halted(reason),
+        ExecutionResult::Success { .. } => {
+            ContractError::Evm("contract was not created".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Constructor stores the argument into slot 0, `get()` returns it and any other
+    // call reverts with the `Denied(uint256)` custom error of the slot value.
+    const ABI: &str = r#"[
+        {"type":"constructor","inputs":[{"name":"x","type":"uint256"}],"stateMutability":"nonpayable"},
+        {"type":"function","name":"get","inputs":[],"outputs":[{"name":"","type":"uint256"}],"stateMutability":"view"},
+        {"type":"function","name":"set","inputs":[{"name":"x","type":"uint256"}],"outputs":[],"stateMutability":"nonpayable"},
+        {"type":"error","name":"Denied","inputs":[{"name":"x","type":"uint256"}]}
+    ]"#;
+
+    fn artifact() -> Artifact {
+        let abi: JsonAbi = serde_json::from_str(ABI).unwrap();
+        let get = abi.function("get").unwrap()[0].selector();
+        let denied = abi.errors().next().unwrap().selector();
+
+        // selector == get ? return(sload(0)) : revert(Denied(sload(0)))
+        let mut runtime = vec![0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c, 0x63];
+        runtime.extend(get.as_slice());
+        runtime.extend([0x14, 0x60, 0x25, 0x57, 0x63]);
+        runtime.extend(denied.as_slice());
+        runtime.extend([
+            0x60, 0xe0, 0x1b, 0x60, 0x00, 0x52, 0x60, 0x00, 0x54, 0x60, 0x04, 0x52, 0x60, 0x24,
+            0x60, 0x00, 0xfd, 0x5b, 0x60, 0x00, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00,
+            0xf3,
+        ]);
+
+        // sstore(0, constructor argument) and return the runtime.
+        let len = runtime.len() as u8;
+        let mut bytecode = vec![
+            0x60, 0x20, 0x60, 0x20, 0x38, 0x03, 0x60, 0x00, 0x39, 0x60, 0x00, 0x51, 0x60, 0x00,
+            0x55, 0x60, len, 0x60, 0x1b, 0x60, 0x00, 0x39, 0x60, len, 0x60, 0x00, 0xf3,
+        ];
+        bytecode.extend(runtime);
+        Artifact {
+            abi,
+            bytecode: bytecode.into(),
+        }
+    }
+
+    #[test]
+    fn deploy_call_and_decode_error() {
+        let mut env = TestEnv::new(1);
+        let expected = env.next_address();
+        let contract = env
+            .deploy(&artifact(), &[DynSolValue::from(U256::from(7))])
+            .unwrap();
+        assert_eq!(contract.address, expected);
+        assert_eq!(contract.ican, expected.to_ican(1));
+        assert_eq!(env.deployer_nonce(), 1);
+
+        let values = env.view(&contract, "get()", &[]).unwrap();
+        assert_eq!(values, [DynSolValue::from(U256::from(7))]);
+
+        let error = env
+            .call(&contract, "set", &[DynSolValue::from(U256::from(1))])
+            .unwrap_err();
+        let ContractError::Reverted {
+            error: Some(error), ..
+        } = error
+        else {
+            panic!("unexpected error {error}");
+        };
+        assert_eq!(error.name, "Denied");
+        assert_eq!(error.args, [DynSolValue::from(U256::from(7))]);
+    }
+}
@@ -0,0 +1,758 @@
+//! ABI driven property and invariant fuzzing of the ylem contracts.
+//!
+//! Contracts are deployed into a [CacheDB] and random sequences of calls are generated
+//! from their ABI JSON. Invariants are checked after every call and failing sequences are
+//! shrunk to a minimal reproducer.
+//!
+//! Invariants are either Rust closures registered with [Fuzzer::invariant] or contract
+//! functions without parameters named `invariant*` that must return `true`.
+
+use crate::{
+    db::{CacheDB, EmptyDB},
+    primitives::{
+        AccountInfo, Address, Bytes, CreateScheme, ExecutionResult, Output, TransactTo, I256, U256,
+    },
+    Evm,
+};
+use alloy_dyn_abi::{DynSolType, DynSolValue, FunctionExt, JsonAbiExt, Specifier};
+use alloy_json_abi::{Function, JsonAbi, StateMutability};
+use core::{cell::Cell, fmt};
+use std::boxed::Box;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// Error of the fuzzing harness itself, broken invariants are reported in [FuzzReport].
+#[derive(Debug)]
+pub enum FuzzError {
+    /// Invalid ABI JSON.
+    Json(serde_json::Error),
+    /// Values do not match the ABI.
+    Abi(alloy_dyn_abi::Error),
+    /// Transaction could not be executed.
+    Evm(String),
+    /// Deployment of the contract failed.
+    Deploy(ExecutionResult),
+    /// [FuzzConfig::senders] is empty, calls need at least one sender.
+    NoSenders,
+}
+
+impl fmt::Display for FuzzError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "invalid ABI: {e}"),
+            Self::Abi(e) => write!(f, "ABI encoding failed: {e}"),
+            Self::Evm(e) => write!(f, "transaction failed: {e}"),
+            Self::Deploy(result) => write!(f, "deployment failed: {result:?}"),
+            Self::NoSenders => write!(f, "no senders configured"),
+        }
+    }
+}
+
+impl std::error::Error for FuzzError {}
+
+impl From<serde_json::Error> for FuzzError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<alloy_dyn_abi::Error> for FuzzError {
+    fn from(e: alloy_dyn_abi::Error) -> Self {
+        Self::Abi(e)
+    }
+}
+
+/// Configuration of the [Fuzzer].
+#[derive(Clone, Debug)]
+pub struct FuzzConfig {
+    /// Number of generated call sequences.
+    pub runs: usize,
+    /// Number of calls in every sequence.
+    pub depth: usize,
+    /// Seed of the generator, runs with the same seed are reproducible.
+    pub seed: u64,
+    /// Callers of the generated calls, funded with `balance`. The first sender deploys the
+    /// contracts, at least one sender is required.
+    pub senders: Vec<Address>,
+    pub balance: U256,
+    pub energy_limit: u64,
+    pub network_id: u64,
+    /// Treat reverted calls as a broken invariant.
+    pub fail_on_revert: bool,
+    /// Maximum number of sequences executed while shrinking.
+    pub shrink_limit: usize,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self {
+            runs: 256,
+            depth: 16,
+            seed: 0x5eed,
+            senders: (1..=3).map(|i| Address::with_last_byte(0x10 + i)).collect(),
+            balance: U256::from(10).pow(U256::from(24)),
+            energy_limit: 30_000_000,
+            network_id: 1,
+            fail_on_revert: false,
+            shrink_limit: 1000,
+        }
+    }
+}
+
+/// Generated call of the contract function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzCall {
+    pub sender: Address,
+    pub target: Address,
+    pub function: Function,
+    pub args: Vec<DynSolValue>,
+    /// Transferred value, only set for payable functions.
+    pub value: U256,
+}
+
+impl FuzzCall {
+    /// ABI encoded input of the call.
+    pub fn calldata(&self) -> Result<Bytes, FuzzError> {
+        Ok(self.function.abi_encode_input(&self.args)?.into())
+    }
+}
+
+impl fmt::Display for FuzzCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {}.{}(",
+            self.sender, self.target, self.function.name
+        )?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{arg:?}")?;
+        }
+        write!(f, ")")?;
+        if !self.value.is_zero() {
+            write!(f, " value: {}", self.value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shrunk sequence of calls that breaks the invariant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Counterexample {
+    /// Name of the broken invariant.
+    pub invariant: String,
+    pub reason: String,
+    pub sequence: Vec<FuzzCall>,
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "invariant {} broken: {}", self.invariant, self.reason)?;
+        for (i, call) in self.sequence.iter().enumerate() {
+            writeln!(f, "  {i}: {call}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of [Fuzzer::run].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzReport {
+    /// Number of executed sequences.
+    pub runs: usize,
+    /// Number of executed calls, without the shrinking.
+    pub calls: usize,
+    pub counterexample: Option<Counterexample>,
+}
+
+/// State available to the invariant closures after every call.
+pub struct InvariantContext<'a> {
+    pub db: &'a mut CacheDB<EmptyDB>,
+    /// Addresses of the deployed contracts.
+    pub targets: &'a [Address],
+    network_id: u64,
+    energy_limit: u64,
+}
+
+impl InvariantContext<'_> {
+    /// Calls the function without committing the state, returning the decoded outputs.
+    pub fn call(
+        &mut self,
+        target: Address,
+        function: &Function,
+        args: &[DynSolValue],
+    ) -> Result<Vec<DynSolValue>, String> {
+        let calldata = function.abi_encode_input(args).map_err(|e| e.to_string())?;
+        let result = execute(
+            self.db,
+            self.network_id,
+            self.energy_limit,
+            Address::ZERO,
+            TransactTo::Call(target),
+            calldata.into(),
+            U256::ZERO,
+            false,
+        )
+        .map_err(|e| e.to_string())?;
+        match result {
+            ExecutionResult::Success { output, .. } => function
+                .abi_decode_output(output.data(), true)
+                .map_err(|e| e.to_string()),
+            result => Err(format!("{} reverted: {result:?}", function.name)),
+        }
+    }
+}
+
+type Invariant = Box<dyn Fn(&mut InvariantContext<'_>) -> Result<(), String>>;
+
+struct Target {
+    address: Address,
+    /// Fuzzed functions with the resolved parameter types.
+    functions: Vec<(Function, Vec<DynSolType>)>,
+    /// `invariant*` functions of the contract.
+    invariants: Vec<Function>,
+}
+
+/// ABI driven fuzzer of the deployed contracts.
+pub struct Fuzzer {
+    config: FuzzConfig,
+    db: CacheDB<EmptyDB>,
+    targets: Vec<Target>,
+    invariants: Vec<(String, Invariant)>,
+    rng: Rng,
+}
+
+impl Fuzzer {
+    /// Creates the fuzzer with an empty state and funded senders.
+    pub fn new(config: FuzzConfig) -> Result<Self, FuzzError> {
+        if config.senders.is_empty() {
+            return Err(FuzzError::NoSenders);
+        }
+        let mut db = CacheDB::new(EmptyDB::default());
+        for sender in &config.senders {
+            db.insert_account_info(*sender, AccountInfo::from_balance(config.balance));
+        }
+        Ok(Self {
+            rng: Rng(config.seed | 1),
+            config,
+            db,
+            targets: Vec::new(),
+            invariants: Vec::new(),
+        })
+    }
+
+    /// State the sequences are executed on, accounts can be set up before [Fuzzer::run].
+    pub fn db_mut(&mut self) -> &mut CacheDB<EmptyDB> {
+        &mut self.db
+    }
+
+    /// Deploys the contract with the constructor arguments and adds it to the fuzzed targets.
+    pub fn deploy(
+        &mut self,
+        abi: &str,
+        bytecode: Bytes,
+        args: &[DynSolValue],
+    ) -> Result<Address, FuzzError> {
+        let abi: JsonAbi = serde_json::from_str(abi)?;
+        let mut data = bytecode.to_vec();
+        if let Some(constructor) = &abi.constructor {
+            data.extend(constructor.abi_encode_input(args)?);
+        }
+        let result = execute(
+            &mut self.db,
+            self.config.network_id,
+            self.config.energy_limit,
+            self.config.senders[0],
+            TransactTo::Create(CreateScheme::Create),
+            data.into(),
+            U256::ZERO,
+            true,
+        )?;
+        let ExecutionResult::Success {
+            output: Output::Create(_, Some(address)),
+            ..
+        } = result
+        else {
+            return Err(FuzzError::Deploy(result));
+        };
+        self.add_target(address, &abi)?;
+        Ok(address)
+    }
+
+    /// Fuzzes the already deployed contract with the ABI JSON.
+    pub fn target(&mut self, address: Address, abi: &str) -> Result<(), FuzzError> {
+        let abi: JsonAbi = serde_json::from_str(abi)?;
+        self.add_target(address, &abi)
+    }
+
+    /// Registers the invariant checked after every call.
+    pub fn invariant(
+        &mut self,
+        name: impl Into<String>,
+        invariant: impl Fn(&mut InvariantContext<'_>) -> Result<(), String> + 'static,
+    ) -> &mut Self {
+        self.invariants.push((name.into(), Box::new(invariant)));
+        self
+    }
+
+    fn add_target(&mut self, address: Address, abi: &JsonAbi) -> Result<(), FuzzError> {
+        let mut target = Target {
+            address,
+            functions: Vec::new(),
+            invariants: Vec::new(),
+        };
+        for function in abi.functions() {
+            if function.name.starts_with("invariant") && function.inputs.is_empty() {
+                target.invariants.push(function.clone());
+                continue;
+            }
+            if matches!(
+                function.state_mutability,
+                StateMutability::Pure | StateMutability::View
+            ) {
+                continue;
+            }
+            let types = function
+                .inputs
+                .iter()
+                .map(|param| param.resolve())
+                .collect::<Result<Vec<_>, _>>()?;
+            if types.iter().all(supported) {
+                target.functions.push((function.clone(), types));
+            }
+        }
+        self.targets.push(target);
+        Ok(())
+    }
+
+    /// Executes the configured number of random sequences, stopping at the first broken invariant.
+    pub fn run(&mut self) -> Result<FuzzReport, FuzzError> {
+        let mut report = FuzzReport {
+            runs: 0,
+            calls: 0,
+            counterexample: None,
+        };
+        if self
+            .targets
+            .iter()
+            .all(|target| target.functions.is_empty())
+        {
+            return Ok(report);
+        }
+        for _ in 0..self.config.runs {
+            report.runs += 1;
+            let mut db = self.db.clone();
+            let mut sequence = Vec::with_capacity(self.config.depth);
+            for _ in 0..self.config.depth {
+                sequence.push(self.generate_call());
+                report.calls += 1;
+                if let Some((invariant, _)) = self.step(&mut db, sequence.last().unwrap())? {
+                    let sequence = self.shrink(sequence, &invariant)?;
+                    let (invariant, reason) = self
+                        .replay(&sequence)?
+                        .expect("shrunk sequence breaks the invariant");
+                    report.counterexample = Some(Counterexample {
+                        invariant,
+                        reason,
+                        sequence,
+                    });
+                    return Ok(report);
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Executes the sequence from the initial state, returning the broken invariant and the reason.
+    pub fn replay(&self, sequence: &[FuzzCall]) -> Result<Option<(String, String)>, FuzzError> {
+        let mut db = self.db.clone();
+        for call in sequence {
+            if let Some(failure) = self.step(&mut db, call)? {
+                return Ok(Some(failure));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Executes the call and checks the invariants.
+    fn step(
+        &self,
+        db: &mut CacheDB<EmptyDB>,
+        call: &FuzzCall,
+    ) -> Result<Option<(String, String)>, FuzzError> {
+        let result = execute(
+            db,
+            self.config.network_id,
+            self.config.energy_limit,
+            call.sender,
+            TransactTo::Call(call.target),
+            call.calldata()?,
+            call.value,
+            true,
+        )?;
+        if self.config.fail_on_revert && !result.is_success() {
+            return Ok(Some(("revert".into(), format!("{result:?}"))));
+        }
+
+        let addresses: Vec<_> = self.targets.iter().map(|target| target.address).collect();
+        let mut context = InvariantContext {
+            db,
+            targets: &addresses,
+            network_id: self.config.network_id,
+            energy_limit: self.config.energy_limit,
+        };
+        for target in &self.targets {
+            for function in &target.invariants {
+                match context.call(target.address, function, &[]) {
+                    Ok(output) if output == [DynSolValue::Bool(true)] => {}
+                    Ok(output) => {
+                        return Ok(Some((
+                            function.name.clone(),
+                            format!("returned {output:?}"),
+                        )))
+                    }
+                    Err(reason) => return Ok(Some((function.name.clone(), reason))),
+                }
+            }
+        }
+        for (name, invariant) in &self.invariants {
+            if let Err(reason) = invariant(&mut context) {
+                return Ok(Some((name.clone(), reason)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Removes calls and simplifies the arguments while the same invariant stays broken.
+    fn shrink(
+        &self,
+        mut sequence: Vec<FuzzCall>,
+        invariant: &str,
+    ) -> Result<Vec<FuzzCall>, FuzzError> {
+        let attempts = Cell::new(0);
+        let exhausted = || attempts.get() >= self.config.shrink_limit;
+        let fails = |sequence: &[FuzzCall]| -> Result<bool, FuzzError> {
+            attempts.set(attempts.get() + 1);
+            Ok(self
+                .replay(sequence)?
+                .is_some_and(|(name, _)| name == invariant))
+        };
+
+        let mut shrunk = true;
+        while shrunk && !exhausted() {
+            shrunk = false;
+
+            let mut i = 0;
+            while i < sequence.len() && !exhausted() {
+                let mut candidate = sequence.clone();
+                candidate.remove(i);
+                if fails(&candidate)? {
+                    sequence = candidate;
+                    shrunk = true;
+                } else {
+                    i += 1;
+                }
+            }
+
+            for i in 0..sequence.len() {
+                if !sequence[i].value.is_zero() {
+                    let mut candidate = sequence.clone();
+                    candidate[i].value = U256::ZERO;
+                    if fails(&candidate)? {
+                        sequence = candidate;
+                        shrunk = true;
+                    }
+                }
+                for j in 0..sequence[i].args.len() {
+                    for value in shrink_value(&sequence[i].args[j]) {
+                        if exhausted() {
+                            break;
+                        }
+                        let mut candidate = sequence.clone();
+                        candidate[i].args[j] = value;
+                        if fails(&candidate)? {
+                            sequence = candidate;
+                            shrunk = true;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(sequence)
+    }
+
+    fn generate_call(&mut self) -> FuzzCall {
+        let targets: Vec<_> = self
+            .targets
+            .iter()
+            .filter(|target| !target.functions.is_empty())
+            .collect();
+        let target = targets[self.rng.below(targets.len())];
+        let (function, types) = &target.functions[self.rng.below(target.functions.len())];
+
+        let mut addresses = self.config.senders.clone();
+        addresses.extend(self.targets.iter().map(|target| target.address));
+        let generator = &mut Generator {
+            rng: &mut self.rng,
+            addresses: &addresses,
+            network_id: self.config.network_id,
+        };
+        let args = types.iter().map(|ty| generator.value(ty)).collect();
+        let value = if function.state_mutability == StateMutability::Payable {
+            U256::from(generator.rng.next() % 1_000_000_000_000_000_000)
+        } else {
+            U256::ZERO
+        };
+        let sender = self.config.senders[self.rng.below(self.config.senders.len())];
+        FuzzCall {
+            sender,
+            target: target.address,
+            function: function.clone(),
+            args,
+            value,
+        }
+    }
+}
+
+/// Executes the transaction on the database, committing the state if `commit` is set.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn execute(
+    db: &mut CacheDB<EmptyDB>,
+    network_id: u64,
+    energy_limit: u64,
+    caller: Address,
+    transact_to: TransactTo,
+    data: Bytes,
+    value: U256,
+    commit: bool,
+) -> Result<ExecutionResult, FuzzError> {
+    let mut evm = Evm::builder()
+        .with_db(db)
+        .modify_cfg_env(|cfg| cfg.network_id = network_id)
+        .modify_tx_env(|tx| {
+            tx.caller = caller;
+            tx.transact_to = transact_to;
+            tx.data = data;
+            tx.value = value;
+            tx.energy_limit = energy_limit;
+        })
+        .build();
+    let result = if commit {
+        evm.transact_commit()
+    } else {
+        evm.transact().map(|result| result.result)
+    };
+    result.map_err(|e| FuzzError::Evm(format!("{e:?}")))
+}
+
+/// Whether values of the type can be generated.
+fn supported(ty: &DynSolType) -> bool {
+    match ty {
+        DynSolType::Bool
+        | DynSolType::Int(_)
+        | DynSolType::Uint(_)
+        | DynSolType::FixedBytes(_)
+        | DynSolType::Address
+        | DynSolType::Bytes
+        | DynSolType::String => true,
+        DynSolType::Array(ty) | DynSolType::FixedArray(ty, _) => supported(ty),
+        DynSolType::Tuple(types) => types.iter().all(supported),
+        _ => false,
+    }
+}
+
+/// Simpler values of the argument, the simplest first.
+fn shrink_value(value: &DynSolValue) -> Vec<DynSolValue> {
+    match value {
+        DynSolValue::Bool(true) => vec![DynSolValue::Bool(false)],
+        DynSolValue::Uint(v, bits) if !v.is_zero() => {
+            vec![
+                DynSolValue::Uint(U256::ZERO, *bits),
+                DynSolValue::Uint(*v >> 1, *bits),
+            ]
+        }
+        DynSolValue::Int(v, bits) if !v.is_zero() => {
+            vec![
+                DynSolValue::Int(I256::ZERO, *bits),
+                DynSolValue::Int(v.asr(1), *bits),
+            ]
+        }
+        DynSolValue::Bytes(bytes) if !bytes.is_empty() => vec![
+            DynSolValue::Bytes(Vec::new()),
+            DynSolValue::Bytes(bytes[..bytes.len() / 2].to_vec()),
+        ],
+        DynSolValue::String(s) if !s.is_empty() => {
+            let half = s
+                .char_indices()
+                .nth(s.chars().count() / 2)
+                .map_or(0, |(i, _)| i);
+            vec![
+                DynSolValue::String(String::new()),
+                DynSolValue::String(s[..half].to_string()),
+            ]
+        }
+        DynSolValue::Array(values) if !values.is_empty() => vec![
+            DynSolValue::Array(Vec::new()),
+            DynSolValue::Array(values[..values.len() - 1].to_vec()),
+        ],
+        DynSolValue::FixedArray(values) | DynSolValue::Tuple(values) => {
+            let fixed = matches!(value, DynSolValue::FixedArray(_));
+            let mut candidates = Vec::new();
+            for (i, value) in values.iter().enumerate() {
+                for shrunk in shrink_value(value) {
+                    let mut values = values.clone();
+                    values[i] = shrunk;
+                    candidates.push(if fixed {
+                        DynSolValue::FixedArray(values)
+                    } else {
+                        DynSolValue::Tuple(values)
+                    });
+                }
+            }
+            candidates
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Xorshift generator, the harness has to be reproducible from the seed.
+#[derive(Clone, Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn u256(&mut self) -> U256 {
+        U256::from_limbs([self.next(), self.next(), self.next(), self.next()])
+    }
+}
+
+struct Generator<'a> {
+    rng: &'a mut Rng,
+    /// Senders and targets, used for most of the generated addresses.
+    addresses: &'a [Address],
+    network_id: u64,
+}
+
+impl Generator<'_> {
+    fn value(&mut self, ty: &DynSolType) -> DynSolValue {
+        match ty {
+            DynSolType::Bool => DynSolValue::Bool(self.rng.next() & 1 == 1),
+            DynSolType::Uint(bits) => {
+                let mask = U256::MAX >> (256 - bits);
+                DynSolValue::Uint(self.word() & mask, *bits)
+            }
+            DynSolType::Int(bits) => {
+                let shift = 256 - bits;
+                let value = I256::from_raw(self.word() << shift).asr(shift);
+                DynSolValue::Int(value, *bits)
+            }
+            DynSolType::FixedBytes(size) => {
+                let mut word = self.rng.u256().to_be_bytes::<32>();
+                word[*size..].fill(0);
+                DynSolValue::FixedBytes(word.into(), *size)
+            }
+            DynSolType::Address => {
+                let address = if self.rng.below(4) == 0 {
+                    Address::from_word(self.rng.u256().to_be_bytes::<32>().into())
+                } else {
+                    self.addresses[self.rng.below(self.addresses.len())]
+                };
+                DynSolValue::Address(address.to_ican(self.network_id))
+            }
+            DynSolType::Bytes => DynSolValue::Bytes(self.bytes()),
+            DynSolType::String => DynSolValue::String(
+                self.bytes()
+                    .into_iter()
+                    .map(|b| char::from(b'a' + b % 26))
+                    .collect(),
+            ),
+            DynSolType::Array(ty) => {
+                let len = self.rng.below(5);
+                DynSolValue::Array((0..len).map(|_| self.value(ty)).collect())
+            }
+            DynSolType::FixedArray(ty, len) => {
+                DynSolValue::FixedArray((0..*len).map(|_| self.value(ty)).collect())
+            }
+            DynSolType::Tuple(types) => {
+                DynSolValue::Tuple(types.iter().map(|ty| self.value(ty)).collect())
+            }
+            _ => unreachable!("unsupported types are not fuzzed"),
+        }
+    }
+
+    /// Random word biased towards the edge values.
+    fn word(&mut self) -> U256 {
+        match self.rng.below(8) {
+            0 => U256::ZERO,
+            1 => U256::from(1),
+            2 => U256::MAX,
+            3 => U256::from(self.rng.next() % 1024),
+            _ => self.rng.u256(),
+        }
+    }
+
+    fn bytes(&mut self) -> Vec<u8> {
+        let len = self.rng.below(65);
+        (0..len).map(|_| self.rng.next() as u8).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+
+    // set(uint256) storing the argument into the slot 0, the selector is ignored.
+    const RUNTIME: [u8; 7] = [0x60, 0x04, 0x35, 0x60, 0x00, 0x55, 0x00];
+    const ABI: &str = r#"[{"type":"function","name":"set","inputs":[{"name":"x","type":"uint256"}],"outputs":[],"stateMutability":"nonpayable"}]"#;
+
+    #[test]
+    fn shrinks_broken_invariant() {
+        let mut initcode = vec![
+            0x60, 0x07, 0x60, 0x0c, 0x60, 0x00, 0x39, 0x60, 0x07, 0x60, 0x00, 0xf3,
+        ];
+        initcode.extend(RUNTIME);
+
+        let mut fuzzer = Fuzzer::new(FuzzConfig::default()).unwrap();
+        let address = fuzzer.deploy(ABI, initcode.into(), &[]).unwrap();
+        fuzzer.invariant("below 1000", move |ctx| {
+            let value = ctx.db.storage(address, U256::ZERO).unwrap();
+            if value < U256::from(1000) {
+                Ok(())
+            } else {
+                Err(format!("slot 0 is {value}"))
+            }
+        });
+
+        let report = fuzzer.run().unwrap();
+        let counterexample = report.counterexample.unwrap();
+        assert_eq!(counterexample.invariant, "below 1000");
+        assert_eq!(counterexample.sequence.len(), 1);
+        let DynSolValue::Uint(value, 256) = counterexample.sequence[0].args[0] else {
+            panic!("unexpected argument");
+        };
+        assert!(value >= U256::from(1000) && value < U256::from(2000));
+    }
+
+    #[test]
+    fn senders_are_required() {
+        let config = FuzzConfig {
+            senders: Vec::new(),
+            ..Default::default()
+        };
+        assert!(matches!(Fuzzer::new(config), Err(FuzzError::NoSenders)));
+    }
+}